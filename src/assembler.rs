@@ -0,0 +1,868 @@
+//! A two-pass assembler for the 6502 family. It knows every instruction and addressing mode of
+//! the opcode tables of the selected variant, undocumented opcodes included.
+//!
+//! The syntax is the usual one:
+//! - `;` starts a comment.
+//! - `name:` defines a label at the current address. Labels starting with a dot (`.loop:`)
+//!   are local: they belong to the last global label, so the same name can be reused after
+//!   each of them.
+//! - `name = expression` defines a constant.
+//! - Numbers are decimal, hexadecimal (`$FF`), binary (`%1010`) or characters (`'a'`), and `*`
+//!   is the address of the current line. Expressions combine them with `+ - * / % & | ^ << >>`
+//!   and parentheses, as well as the unary `-`, `~`, `<` (low byte) and `>` (high byte).
+//! - The directives are `.org ADDRESS`, `.byte` and `.word` (lists of expressions, `.byte`
+//!   also takes strings), `.text "string"` and `.include "file"`.
+//!
+//! The zero page addressing modes are picked whenever the operand is known to fit in a byte by
+//! the time its line is first seen. Forward references get the absolute modes.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::bus::{Bus, Memory};
+use crate::error::{Error, Result};
+use crate::opcode::{self, AddressingMode, Opcode};
+use crate::p6502::{Variant, P6502};
+
+/// How deep `.include` directives can be nested.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// The number of addressing modes, used to index the encoding table.
+const MODES: usize = AddressingMode::ZeroPageRelative as usize + 1;
+
+/// A block of contiguous bytes produced by the assembler.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    /// The address of the first byte
+    pub start: u16,
+    /// The assembled bytes
+    pub bytes: Vec<u8>,
+}
+
+/// The output of the assembler.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Assembly {
+    /// The assembled code and data, in source order. A new chunk begins at every `.org`.
+    pub chunks: Vec<Chunk>,
+    /// Every label and constant which fits in 16 bits. Local labels are named after their
+    /// global label, as in `main.loop`.
+    pub symbols: BTreeMap<String, u16>,
+}
+
+impl Assembly {
+    /// Returns the address of the first assembled byte, or the default start address
+    /// ($0600) if nothing has been assembled.
+    pub fn start(&self) -> u16 {
+        self.chunks
+            .first()
+            .map_or(P6502::PROGRAM_START, |chunk| chunk.start)
+    }
+
+    /// Writes every chunk to a bus.
+    pub fn load_into<B: Bus + ?Sized>(&self, bus: &mut B) {
+        for chunk in self.chunks.iter() {
+            for (offset, value) in chunk.bytes.iter().enumerate() {
+                bus.write(chunk.start.wrapping_add(offset as u16), *value);
+            }
+        }
+    }
+
+    /// Creates a memory containing the program. Unless the program sets the reset vector
+    /// itself, it is pointed at the first assembled byte.
+    pub fn to_memory(&self) -> Memory {
+        let mut memory: Memory = Memory::default();
+        let start: u16 = self.start();
+        memory.write(P6502::RESET_VECTOR, start as u8);
+        memory.write(P6502::RESET_VECTOR + 1, (start >> 8) as u8);
+        self.load_into(&mut memory);
+        memory
+    }
+}
+
+/// Assembles a program for a variant of the 6502. The source can't include other files.
+pub fn assemble(source: &str, variant: Variant) -> Result<Assembly> {
+    assemble_with(source, variant, |_| None)
+}
+
+/// Assembles a program for a variant of the 6502. `include` is given the file name of every
+/// `.include` directive and returns its contents, or `None` if it can't be read.
+pub fn assemble_with<F>(source: &str, variant: Variant, mut include: F) -> Result<Assembly>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut lines: Vec<Line> = vec![];
+    expand(source, None, &mut include, 0, &mut lines)?;
+
+    let mut assembler: Assembler = Assembler::new(variant);
+    assembler.pass(&lines, false)?;
+    assembler.pass(&lines, true)?;
+
+    let symbols: BTreeMap<String, u16> = assembler
+        .symbols
+        .iter()
+        .filter(|(_, value)| (0..=0xFFFF).contains(*value))
+        .map(|(name, value)| (name.clone(), *value as u16))
+        .collect();
+    Ok(Assembly {
+        chunks: assembler.chunks,
+        symbols,
+    })
+}
+
+/// Assembles a program file. The files it includes are looked for in its directory. Requires
+/// the `std` feature.
+#[cfg(feature = "std")]
+pub fn assemble_file(path: &str, variant: Variant) -> Result<Assembly> {
+    let source: String = std::fs::read_to_string(path)?;
+    let directory: std::path::PathBuf = std::path::Path::new(path)
+        .parent()
+        .map(|parent| parent.to_path_buf())
+        .unwrap_or_default();
+
+    assemble_with(&source, variant, |name| {
+        std::fs::read_to_string(directory.join(name)).ok()
+    })
+}
+
+/// A line of source code, after the includes have been expanded.
+struct Line {
+    /// The file the line comes from, or `None` for the main source
+    file: Option<String>,
+    /// The line number, counted from 1
+    number: usize,
+    text: String,
+}
+
+impl Line {
+    /// Returns an error pointing at this line.
+    fn error(&self, message: String) -> Error {
+        Error::Assemble {
+            file: self.file.clone(),
+            line: self.number,
+            message,
+        }
+    }
+}
+
+/// Splits a source into lines, replacing the `.include` directives with the lines of the
+/// included files.
+fn expand<F>(
+    source: &str,
+    file: Option<&str>,
+    include: &mut F,
+    depth: usize,
+    lines: &mut Vec<Line>,
+) -> Result<()>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    for (index, text) in source.lines().enumerate() {
+        let line: Line = Line {
+            file: file.map(|name| name.to_string()),
+            number: index + 1,
+            text: strip_comment(text).to_string(),
+        };
+
+        let (directive, argument) = split_word(line.text.trim());
+        if !directive.eq_ignore_ascii_case(".include") {
+            lines.push(line);
+            continue;
+        }
+
+        let name: String = match parse_string(argument.trim()) {
+            Some(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            None => return Err(line.error("expected a file name between quotes".to_string())),
+        };
+        if depth >= MAX_INCLUDE_DEPTH {
+            return Err(line.error(format!("too many nested includes in '{}'", name)));
+        }
+        match include(&name) {
+            Some(contents) => expand(&contents, Some(&name), include, depth + 1, lines)?,
+            None => return Err(line.error(format!("unable to include '{}'", name))),
+        }
+    }
+
+    Ok(())
+}
+
+/// The state of the assembler during a pass.
+struct Assembler {
+    /// The opcode of every instruction and addressing mode available, by mnemonic
+    encodings: BTreeMap<String, [Option<u8>; MODES]>,
+    symbols: BTreeMap<String, i64>,
+    chunks: Vec<Chunk>,
+    /// The address of the next byte. It may go past $FFFF, which is an error.
+    pc: u32,
+    /// The last global label, which local labels belong to
+    scope: String,
+    /// Whether each instruction with both a zero page and an absolute form got the absolute
+    /// one during the first pass, in source order. The second pass must keep the same sizes.
+    wide: Vec<bool>,
+    /// How many entries of `wide` have been used during the current pass
+    wide_index: usize,
+}
+
+impl Assembler {
+    fn new(variant: Variant) -> Self {
+        let mut encodings: BTreeMap<String, [Option<u8>; MODES]> = BTreeMap::new();
+        for byte in 0..=255_u8 {
+            let decoded: Opcode = opcode::decode(variant, byte);
+            let modes: &mut [Option<u8>; MODES] = encodings
+                .entry(decoded.mnemonic.to_string())
+                .or_insert([None; MODES]);
+            let slot: &mut Option<u8> = &mut modes[decoded.mode as usize];
+
+            // Several opcodes may do the same, like the undocumented NOPs: the documented one
+            // or else the first one wins.
+            let replace: bool = match *slot {
+                None => true,
+                Some(previous) => {
+                    !opcode::is_documented(variant, previous)
+                        && opcode::is_documented(variant, byte)
+                }
+            };
+            if replace {
+                *slot = Some(byte);
+            }
+        }
+
+        Self {
+            encodings,
+            symbols: BTreeMap::new(),
+            chunks: vec![],
+            pc: P6502::PROGRAM_START as u32,
+            scope: String::new(),
+            wide: vec![],
+            wide_index: 0,
+        }
+    }
+
+    /// Goes through the whole source. The first pass finds the address of every label and the
+    /// size of every instruction. The second one, `last`, produces the bytes.
+    fn pass(&mut self, lines: &[Line], last: bool) -> Result<()> {
+        self.chunks.clear();
+        self.pc = P6502::PROGRAM_START as u32;
+        self.scope.clear();
+        self.wide_index = 0;
+
+        for line in lines.iter() {
+            self.line(&line.text, last)
+                .map_err(|message| line.error(message))?;
+        }
+
+        Ok(())
+    }
+
+    /// Assembles a line, without its comment.
+    fn line(&mut self, text: &str, last: bool) -> core::result::Result<(), String> {
+        let mut text: &str = text.trim();
+
+        // Labels
+        while let Some(colon) = text.find(':') {
+            let name: &str = text[..colon].trim();
+            if !is_identifier(name) {
+                break;
+            }
+            let full_name: String = self.full_name(name);
+            if !name.starts_with('.') {
+                self.scope = name.to_string();
+            }
+            self.define(full_name, self.pc as i64, last)?;
+            text = text[colon + 1..].trim();
+        }
+
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        // Constants
+        if let Some(equals) = text.find('=') {
+            let name: &str = text[..equals].trim();
+            if is_identifier(name) {
+                let full_name: String = self.full_name(name);
+                return match self.evaluate(&text[equals + 1..], last)? {
+                    Some(value) => self.define(full_name, value, last),
+                    None => Ok(()),
+                };
+            }
+        }
+
+        let (word, operand) = split_word(text);
+        if word.starts_with('.') {
+            self.directive(word, operand.trim(), last)
+        } else {
+            self.instruction(word, operand.trim(), last)
+        }
+    }
+
+    /// Gives a local label the name of its global label.
+    fn full_name(&self, name: &str) -> String {
+        if name.starts_with('.') {
+            format!("{}{}", self.scope, name)
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Sets the value of a symbol, which can only be defined once.
+    fn define(&mut self, name: String, value: i64, last: bool) -> core::result::Result<(), String> {
+        if !last && self.symbols.contains_key(&name) {
+            return Err(format!("'{}' is already defined", name));
+        }
+        self.symbols.insert(name, value);
+        Ok(())
+    }
+
+    fn directive(
+        &mut self,
+        directive: &str,
+        operand: &str,
+        last: bool,
+    ) -> core::result::Result<(), String> {
+        match directive.to_ascii_lowercase().as_str() {
+            ".org" => match self.evaluate(operand, true)? {
+                Some(value) if (0..=0xFFFF).contains(&value) => {
+                    self.pc = value as u32;
+                    self.chunks.push(Chunk {
+                        start: value as u16,
+                        bytes: vec![],
+                    });
+                    Ok(())
+                }
+                _ => Err(format!("invalid address '{}'", operand)),
+            },
+            ".byte" => {
+                for item in split_list(operand) {
+                    match parse_string(item) {
+                        Some(bytes) => self.emit(&bytes)?,
+                        None => {
+                            let value: i64 = self.evaluate(item, last)?.unwrap_or(0);
+                            self.emit(&[byte(value)?])?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            ".word" => {
+                for item in split_list(operand) {
+                    let value: i64 = self.evaluate(item, last)?.unwrap_or(0);
+                    if !(-0x8000..=0xFFFF).contains(&value) {
+                        return Err(format!("{} doesn't fit in a word", value));
+                    }
+                    self.emit(&[value as u8, (value >> 8) as u8])?;
+                }
+                Ok(())
+            }
+            ".text" => match parse_string(operand) {
+                Some(bytes) => self.emit(&bytes),
+                None => Err("expected a string between quotes".to_string()),
+            },
+            _ => Err(format!("unknown directive '{}'", directive)),
+        }
+    }
+
+    fn instruction(
+        &mut self,
+        mnemonic: &str,
+        operand: &str,
+        last: bool,
+    ) -> core::result::Result<(), String> {
+        let modes: [Option<u8>; MODES] = match self.encodings.get(&mnemonic.to_ascii_uppercase()) {
+            Some(modes) => *modes,
+            None => return Err(format!("unknown instruction '{}'", mnemonic)),
+        };
+        let has = |mode: AddressingMode| modes[mode as usize].is_some();
+
+        let (mode, expression): (AddressingMode, &str) = match parse_operand(operand) {
+            Operand::None if has(AddressingMode::Implied) => (AddressingMode::Implied, ""),
+            Operand::None | Operand::Accumulator => (AddressingMode::Accumulator, ""),
+            Operand::Immediate(expression) => (AddressingMode::Immediate, expression),
+            Operand::Direct(expression) if has(AddressingMode::Relative) => {
+                (AddressingMode::Relative, expression)
+            }
+            Operand::Direct(expression) => self.choose_width(
+                AddressingMode::ZeroPage,
+                AddressingMode::Absolute,
+                &modes,
+                expression,
+            )?,
+            Operand::IndexedX(expression) => self.choose_width(
+                AddressingMode::ZeroPageX,
+                AddressingMode::AbsoluteX,
+                &modes,
+                expression,
+            )?,
+            Operand::IndexedY(expression) => self.choose_width(
+                AddressingMode::ZeroPageY,
+                AddressingMode::AbsoluteY,
+                &modes,
+                expression,
+            )?,
+            Operand::IndirectX(expression) if has(AddressingMode::AbsoluteIndexedIndirect) => {
+                (AddressingMode::AbsoluteIndexedIndirect, expression)
+            }
+            Operand::IndirectX(expression) => (AddressingMode::IndexedIndirect, expression),
+            Operand::IndirectY(expression) => (AddressingMode::IndirectIndexed, expression),
+            Operand::Indirect(expression) if has(AddressingMode::Indirect) => {
+                (AddressingMode::Indirect, expression)
+            }
+            Operand::Indirect(expression) => (AddressingMode::ZeroPageIndirect, expression),
+            Operand::Pair(expression, _) => (AddressingMode::ZeroPageRelative, expression),
+        };
+
+        let opcode: u8 = match modes[mode as usize] {
+            Some(opcode) => opcode,
+            None => {
+                return Err(format!(
+                    "'{}' can't take the operand '{}'",
+                    mnemonic, operand
+                ))
+            }
+        };
+        let value: i64 = match mode {
+            AddressingMode::Implied | AddressingMode::Accumulator => 0,
+            _ => self.evaluate(expression, last)?.unwrap_or(0),
+        };
+
+        match mode {
+            AddressingMode::Implied | AddressingMode::Accumulator => self.emit(&[opcode]),
+            AddressingMode::Relative => {
+                let offset: u8 = self.branch_offset(value, 2, last)?;
+                self.emit(&[opcode, offset])
+            }
+            AddressingMode::ZeroPageRelative => {
+                let target: &str = match parse_operand(operand) {
+                    Operand::Pair(_, target) => target,
+                    _ => unreachable!(),
+                };
+                let target: i64 = self.evaluate(target, last)?.unwrap_or(0);
+                let offset: u8 = self.branch_offset(target, 3, last)?;
+                self.emit(&[opcode, byte(value)?, offset])
+            }
+            _ if mode.operand_size() == 1 => self.emit(&[opcode, byte(value)?]),
+            _ => {
+                if !(0..=0xFFFF).contains(&value) {
+                    return Err(format!("${:x} is not an address", value));
+                }
+                self.emit(&[opcode, value as u8, (value >> 8) as u8])
+            }
+        }
+    }
+
+    /// Chooses between the zero page and the absolute form of an addressing mode. The choice
+    /// made during the first pass is kept in the second one.
+    fn choose_width<'o>(
+        &mut self,
+        zero_page: AddressingMode,
+        absolute: AddressingMode,
+        modes: &[Option<u8>; MODES],
+        expression: &'o str,
+    ) -> core::result::Result<(AddressingMode, &'o str), String> {
+        let has_zero_page: bool = modes[zero_page as usize].is_some();
+        let has_absolute: bool = modes[absolute as usize].is_some();
+        if !has_zero_page || !has_absolute {
+            let mode: AddressingMode = if has_zero_page { zero_page } else { absolute };
+            return Ok((mode, expression));
+        }
+
+        let wide: bool = match self.wide.get(self.wide_index) {
+            Some(wide) => *wide,
+            None => {
+                let wide: bool = !matches!(self.evaluate(expression, false)?, Some(0..=0xFF));
+                self.wide.push(wide);
+                wide
+            }
+        };
+        self.wide_index += 1;
+
+        Ok((if wide { absolute } else { zero_page }, expression))
+    }
+
+    /// Returns the offset of a branch to `target` from an instruction of `size` bytes.
+    fn branch_offset(
+        &self,
+        target: i64,
+        size: i64,
+        last: bool,
+    ) -> core::result::Result<u8, String> {
+        let offset: i64 = target - (self.pc as i64 + size);
+        if last && !(-128..=127).contains(&offset) {
+            return Err(format!("branch target ${:04x} is out of range", target));
+        }
+        Ok(offset as u8)
+    }
+
+    /// Appends bytes at the current address.
+    fn emit(&mut self, bytes: &[u8]) -> core::result::Result<(), String> {
+        if self.pc + bytes.len() as u32 > 0x10000 {
+            return Err("the program goes past $FFFF".to_string());
+        }
+        if self.chunks.is_empty() {
+            self.chunks.push(Chunk {
+                start: self.pc as u16,
+                bytes: vec![],
+            });
+        }
+
+        if let Some(chunk) = self.chunks.last_mut() {
+            chunk.bytes.extend_from_slice(bytes);
+        }
+        self.pc += bytes.len() as u32;
+        Ok(())
+    }
+
+    /// Evaluates an expression. Returns `None` if it uses a symbol which is not defined yet,
+    /// which is only an error when `required`.
+    fn evaluate(&self, text: &str, required: bool) -> core::result::Result<Option<i64>, String> {
+        let mut parser: Parser = Parser {
+            text: text.as_bytes(),
+            position: 0,
+            assembler: self,
+            undefined: None,
+        };
+
+        let value: i64 = parser.expression(0)?;
+        parser.skip_spaces();
+        if parser.position < parser.text.len() {
+            return Err(format!("unexpected '{}'", &text[parser.position..]));
+        }
+
+        match parser.undefined {
+            Some(name) if required => Err(format!("'{}' is not defined", name)),
+            Some(_) => Ok(None),
+            None => Ok(Some(value)),
+        }
+    }
+}
+
+/// The syntax of an operand, with its expressions.
+enum Operand<'a> {
+    None,
+    Accumulator,
+    Immediate(&'a str),
+    Direct(&'a str),
+    IndexedX(&'a str),
+    IndexedY(&'a str),
+    IndirectX(&'a str),
+    IndirectY(&'a str),
+    Indirect(&'a str),
+    Pair(&'a str, &'a str),
+}
+
+fn parse_operand(operand: &str) -> Operand<'_> {
+    let operand: &str = operand.trim();
+
+    if operand.is_empty() {
+        return Operand::None;
+    }
+    if operand.eq_ignore_ascii_case("a") {
+        return Operand::Accumulator;
+    }
+    if let Some(expression) = operand.strip_prefix('#') {
+        return Operand::Immediate(expression);
+    }
+
+    if operand.starts_with('(') {
+        if let Some(close) = matching_parenthesis(operand) {
+            let inner: &str = &operand[1..close];
+            let rest: &str = operand[close + 1..].trim();
+
+            if rest.is_empty() {
+                return match split_index(inner) {
+                    Some((expression, 'X')) => Operand::IndirectX(expression),
+                    _ => Operand::Indirect(inner),
+                };
+            }
+            if let Some((_, 'Y')) = split_index(rest) {
+                return Operand::IndirectY(inner);
+            }
+        }
+    }
+
+    match split_index(operand) {
+        Some((expression, 'X')) => Operand::IndexedX(expression),
+        Some((expression, 'Y')) => Operand::IndexedY(expression),
+        _ => match split_list(operand).as_slice() {
+            [first, second] => Operand::Pair(first, second),
+            _ => Operand::Direct(operand),
+        },
+    }
+}
+
+/// Splits `expression,X` or `expression,Y` into the expression and the index register.
+fn split_index(text: &str) -> Option<(&str, char)> {
+    let comma: usize = text.rfind(',')?;
+    let register: &str = text[comma + 1..].trim();
+    if register.eq_ignore_ascii_case("x") {
+        Some((&text[..comma], 'X'))
+    } else if register.eq_ignore_ascii_case("y") {
+        Some((&text[..comma], 'Y'))
+    } else {
+        None
+    }
+}
+
+/// Returns the position of the parenthesis closing the one which starts the text.
+fn matching_parenthesis(text: &str) -> Option<usize> {
+    let mut depth: usize = 0;
+    for (index, character) in text.char_indices() {
+        match character {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits a list at the commas which are not between quotes or parentheses.
+fn split_list(text: &str) -> Vec<&str> {
+    let mut items: Vec<&str> = vec![];
+    let mut depth: usize = 0;
+    let mut quote: Option<char> = None;
+    let mut start: usize = 0;
+
+    for (index, character) in text.char_indices() {
+        match (quote, character) {
+            (Some(open), _) if character == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(character),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, ',') if depth == 0 => {
+                items.push(text[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    let last: &str = text[start..].trim();
+    if !last.is_empty() || !items.is_empty() {
+        items.push(last);
+    }
+    items
+}
+
+/// Removes the comment of a line, if any.
+fn strip_comment(text: &str) -> &str {
+    let mut quote: Option<char> = None;
+    for (index, character) in text.char_indices() {
+        match (quote, character) {
+            (Some(open), _) if character == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(character),
+            (None, ';') => return &text[..index],
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Splits the first word of a line from the rest.
+fn split_word(text: &str) -> (&str, &str) {
+    match text.find(char::is_whitespace) {
+        Some(index) => (&text[..index], &text[index..]),
+        None => (text, ""),
+    }
+}
+
+/// Returns the bytes of a string between double quotes.
+fn parse_string(text: &str) -> Option<Vec<u8>> {
+    let inner: &str = text.strip_prefix('"')?.strip_suffix('"')?;
+    Some(inner.bytes().collect())
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut characters = text.chars();
+    match characters.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' || first == '.' => {
+            characters.all(|character| character.is_ascii_alphanumeric() || character == '_')
+        }
+        _ => false,
+    }
+}
+
+/// Checks that a value fits in a byte, either signed or unsigned.
+fn byte(value: i64) -> core::result::Result<u8, String> {
+    if (-0x80..=0xFF).contains(&value) {
+        Ok(value as u8)
+    } else {
+        Err(format!("{} doesn't fit in a byte", value))
+    }
+}
+
+/// The binary operators, from the lowest to the highest precedence.
+const OPERATORS: [&[&str]; 6] = [
+    &["|"],
+    &["^"],
+    &["&"],
+    &["<<", ">>"],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+/// A recursive descent parser of expressions.
+struct Parser<'a> {
+    text: &'a [u8],
+    position: usize,
+    assembler: &'a Assembler,
+    /// The first symbol found which is not defined
+    undefined: Option<String>,
+}
+
+impl Parser<'_> {
+    fn skip_spaces(&mut self) {
+        while self.position < self.text.len() && self.text[self.position].is_ascii_whitespace() {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.position).copied()
+    }
+
+    /// Parses the operators of a precedence level and the higher ones.
+    fn expression(&mut self, level: usize) -> core::result::Result<i64, String> {
+        if level == OPERATORS.len() {
+            return self.unary();
+        }
+
+        let mut value: i64 = self.expression(level + 1)?;
+        loop {
+            self.skip_spaces();
+            let rest: &[u8] = &self.text[self.position..];
+            let operator: &str = match OPERATORS[level]
+                .iter()
+                .find(|operator| rest.starts_with(operator.as_bytes()))
+            {
+                Some(operator) => operator,
+                None => return Ok(value),
+            };
+            self.position += operator.len();
+
+            let right: i64 = self.expression(level + 1)?;
+            value = match operator {
+                "|" => value | right,
+                "^" => value ^ right,
+                "&" => value & right,
+                "<<" => value.wrapping_shl(right as u32),
+                ">>" => value.wrapping_shr(right as u32),
+                "+" => value.wrapping_add(right),
+                "-" => value.wrapping_sub(right),
+                "*" => value.wrapping_mul(right),
+                _ if right == 0 && self.undefined.is_none() => {
+                    return Err("division by zero".to_string())
+                }
+                _ if right == 0 => 0,
+                "/" => value.wrapping_div(right),
+                _ => value.wrapping_rem(right),
+            };
+        }
+    }
+
+    fn unary(&mut self) -> core::result::Result<i64, String> {
+        self.skip_spaces();
+        let operator: Option<u8> = self.peek();
+        match operator {
+            Some(b'-') | Some(b'~') | Some(b'<') | Some(b'>') | Some(b'+') => {
+                self.position += 1;
+                let value: i64 = self.unary()?;
+                Ok(match operator {
+                    Some(b'-') => value.wrapping_neg(),
+                    Some(b'~') => !value,
+                    Some(b'<') => value & 0xFF,
+                    Some(b'>') => (value >> 8) & 0xFF,
+                    _ => value,
+                })
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> core::result::Result<i64, String> {
+        self.skip_spaces();
+        let start: usize = self.position;
+
+        match self.peek() {
+            Some(b'(') => {
+                self.position += 1;
+                let value: i64 = self.expression(0)?;
+                self.skip_spaces();
+                if self.peek() != Some(b')') {
+                    return Err("missing ')'".to_string());
+                }
+                self.position += 1;
+                Ok(value)
+            }
+            Some(b'*') => {
+                self.position += 1;
+                Ok(self.assembler.pc as i64)
+            }
+            Some(b'$') => {
+                self.position += 1;
+                self.number(16)
+            }
+            Some(b'%') => {
+                self.position += 1;
+                self.number(2)
+            }
+            Some(b'0'..=b'9') => self.number(10),
+            Some(b'\'') => match (self.text.get(start + 1), self.text.get(start + 2)) {
+                (Some(character), Some(b'\'')) => {
+                    self.position += 3;
+                    Ok(*character as i64)
+                }
+                _ => Err("invalid character constant".to_string()),
+            },
+            Some(character) if character.is_ascii_alphabetic() || b"_.".contains(&character) => {
+                self.position += 1;
+                while let Some(character) = self.peek() {
+                    if !character.is_ascii_alphanumeric() && character != b'_' {
+                        break;
+                    }
+                    self.position += 1;
+                }
+
+                let name: &str = core::str::from_utf8(&self.text[start..self.position])
+                    .map_err(|_| "invalid symbol".to_string())?;
+                let full_name: String = self.assembler.full_name(name);
+                match self.assembler.symbols.get(&full_name) {
+                    Some(value) => Ok(*value),
+                    None => {
+                        if self.undefined.is_none() {
+                            self.undefined = Some(full_name);
+                        }
+                        Ok(0)
+                    }
+                }
+            }
+            Some(_) => Err(format!(
+                "unexpected '{}'",
+                String::from_utf8_lossy(&self.text[start..])
+            )),
+            None => Err("missing operand".to_string()),
+        }
+    }
+
+    fn number(&mut self, radix: u32) -> core::result::Result<i64, String> {
+        let start: usize = self.position;
+        while let Some(character) = self.peek() {
+            if !(character as char).is_digit(radix) {
+                break;
+            }
+            self.position += 1;
+        }
+
+        let digits: &str = core::str::from_utf8(&self.text[start..self.position]).unwrap_or("");
+        i64::from_str_radix(digits, radix).map_err(|_| "invalid number".to_string())
+    }
+}
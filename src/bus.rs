@@ -0,0 +1,450 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+
+use crate::error::{Error, Result};
+use crate::p6502::P6502;
+use crate::savestate::Reader;
+
+/// Represents everything the CPU is attached to through its address and data buses. Any
+/// type implementing this trait (plain RAM, ROM, memory-mapped devices...) can be plugged
+/// into a `P6502`.
+pub trait Bus {
+    /// Reads a byte from the specified address. Reading may have side effects on devices.
+    fn read(&mut self, addr: u16) -> u8;
+
+    /// Writes a byte to the specified address.
+    fn write(&mut self, addr: u16, data: u8);
+
+    /// Returns the byte at the specified address without any side effects. Used by the
+    /// interactive prompt to inspect the memory.
+    fn peek(&self, addr: u16) -> u8;
+
+    /// Changes the byte at the specified address on behalf of a debugger, bypassing write
+    /// protections where the bus has them. Writes normally by default.
+    fn poke(&mut self, addr: u16, data: u8) {
+        self.write(addr, data);
+    }
+
+    /// Called every time the CPU consumes clock cycles. Does nothing by default.
+    fn tick(&mut self, _cycles: usize) {}
+
+    /// Erases the contents of the RAM attached to the bus. Does nothing by default.
+    fn clear(&mut self) {}
+
+    /// Returns whether a device on the bus is holding the IRQ line. Never does by default.
+    fn irq(&self) -> bool {
+        false
+    }
+
+    /// Returns whether a device on the bus is holding the NMI line. The CPU only reacts to the
+    /// moment the line becomes active. Never does by default.
+    fn nmi(&self) -> bool {
+        false
+    }
+
+    /// Appends the state of the bus, such as the contents of its memory and the registers of its
+    /// devices, to a save state. Saves nothing by default.
+    fn save_state(&self, _state: &mut Vec<u8>) {}
+
+    /// Restores the state appended by `save_state`, which is passed whole. Does nothing by
+    /// default.
+    fn load_state(&mut self, _state: &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns the address of an access the bus could not carry out since the last call, if
+    /// any. The CPU checks it after every instruction and stops with `Error::BusFault`. Never
+    /// faults by default.
+    fn fault(&mut self) -> Option<u16> {
+        None
+    }
+}
+
+/// A single access of the CPU to the bus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BusAccess {
+    /// The address accessed
+    pub addr: u16,
+    /// The data read or written
+    pub data: u8,
+    /// Whether the CPU wrote to the address rather than read from it
+    pub write: bool,
+}
+
+/// Represents the computer's memory. Consistists only of an `u8` slice of fixed length
+/// and equal to 64 K (= 65536 = $10000).
+pub struct Memory {
+    pub data: [u8; Memory::MAX_SIZE],
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self {
+            data: [0_u8; Self::MAX_SIZE],
+        }
+    }
+}
+
+impl Memory {
+    const MAX_SIZE: usize = 64 * 1024;
+
+    /// Creates a memory containing the program at the default start address and with the
+    /// reset vector pointing to it.
+    pub fn from_program_vec(program: Vec<u8>) -> Self {
+        let mut memory: Self = Self::default();
+        memory.write(P6502::RESET_VECTOR, P6502::PROGRAM_START as u8);
+        memory.write(P6502::RESET_VECTOR + 1, (P6502::PROGRAM_START >> 8) as u8);
+
+        for (index, value) in program.iter().enumerate() {
+            memory.write(P6502::PROGRAM_START.wrapping_add(index as u16), *value);
+        }
+
+        memory
+    }
+}
+
+impl Bus for Memory {
+    /// Reads a byte from the specified memory address and returns the value.
+    fn read(&mut self, addr: u16) -> u8 {
+        self.data[addr as usize]
+    }
+
+    /// Writes a byte to the specified memory address.
+    fn write(&mut self, addr: u16, data: u8) {
+        self.data[addr as usize] = data;
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.data[addr as usize]
+    }
+
+    /// Clears the memory by setting all the elements of the slice to `0_u8`.
+    fn clear(&mut self) {
+        self.data = [0_u8; Memory::MAX_SIZE];
+    }
+
+    /// Saves the 64 KB.
+    fn save_state(&self, state: &mut Vec<u8>) {
+        state.extend_from_slice(&self.data);
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<()> {
+        if state.len() != Memory::MAX_SIZE {
+            return Err(Error::SaveState(format!(
+                "expected {} bytes of memory, found {}",
+                Memory::MAX_SIZE,
+                state.len()
+            )));
+        }
+        self.data.copy_from_slice(state);
+        Ok(())
+    }
+}
+
+/// Describes what is attached to a range of addresses of a `MemoryMap`.
+pub enum Region {
+    /// Readable and writable memory.
+    Ram(Vec<u8>),
+    /// Read-only memory. Writes are either ignored or reported, as per the map's `RomWrites`.
+    Rom(Vec<u8>),
+    /// Redirects every access to another range of addresses, which is repeated as many times as
+    /// needed to fill the mirrored range. For instance, mirroring $0000-$07FF over $0800-$1FFF
+    /// makes $0800, $1000 and $1800 aliases of $0000.
+    Mirror { start: u16, size: u32 },
+    /// A memory-mapped device. It receives addresses relative to the beginning of its range.
+    Device(Box<dyn Bus>),
+    /// Nothing is attached. Reads return the last value seen on the data bus (open bus) and
+    /// writes are ignored.
+    Unmapped,
+}
+
+/// What a `MemoryMap` does when the CPU tries to write into a ROM region.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RomWrites {
+    /// The write is silently discarded.
+    #[default]
+    Ignore,
+    /// The write is discarded but recorded in `MemoryMap::rom_writes`.
+    Report,
+    /// The write is discarded and reported to the CPU as a bus fault, which stops the execution.
+    Fault,
+}
+
+/// A region of the address space, from `start` to `end` (both included).
+struct Mapping {
+    start: u16,
+    end: u16,
+    region: Region,
+}
+
+/// A declarative description of the address space, built from ranges of RAM, ROM, mirrors,
+/// devices and unmapped addresses. Ranges declared later take precedence over earlier ones,
+/// so a ROM can be laid over a bigger RAM area. Addresses not covered by any range are unmapped.
+///
+/// ```
+/// # use kw6502::bus::{Bus, MemoryMap};
+/// # let rom_image: Vec<u8> = vec![0xEA; 0x2000];
+/// let mut map: MemoryMap = MemoryMap::new()
+///     .ram(0x0000..=0x07FF)
+///     .mirror(0x0800..=0x1FFF, 0x0000..=0x07FF)
+///     .rom(0xE000..=0xFFFF, rom_image);
+///
+/// map.write(0x0810, 0x42);
+/// assert_eq!(map.peek(0x0010), 0x42);
+/// ```
+#[derive(Default)]
+pub struct MemoryMap {
+    mappings: Vec<Mapping>,
+    /// The policy for writes into ROM regions.
+    pub rom_policy: RomWrites,
+    /// The writes into ROM that have been attempted (address and data), when reporting them.
+    pub rom_writes: Vec<(u16, u8)>,
+    /// The last value seen on the data bus, returned when reading from unmapped addresses.
+    open_bus: u8,
+    /// The address of the last faulty write into ROM, until the CPU checks it.
+    fault: Option<u16>,
+}
+
+impl MemoryMap {
+    /// Mirrors may point to other mirrors, but not deeper than this.
+    const MAX_MIRROR_DEPTH: usize = 8;
+
+    /// Creates an empty map where every address is unmapped.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps the range to zero-initialized RAM.
+    pub fn ram(self, range: RangeInclusive<u16>) -> Self {
+        let size: usize = Self::range_size(&range);
+        self.map(range, Region::Ram(vec![0_u8; size]))
+    }
+
+    /// Maps the range to a ROM with the specified contents. If `contents` is shorter than the
+    /// range the remaining bytes are zero, and if it is longer it gets truncated.
+    pub fn rom(self, range: RangeInclusive<u16>, contents: Vec<u8>) -> Self {
+        self.map(range, Region::Rom(contents))
+    }
+
+    /// Makes `range` an alias of `target`, repeating it as many times as needed.
+    pub fn mirror(self, range: RangeInclusive<u16>, target: RangeInclusive<u16>) -> Self {
+        let region: Region = Region::Mirror {
+            start: *target.start(),
+            size: Self::range_size(&target) as u32,
+        };
+        self.map(range, region)
+    }
+
+    /// Attaches a memory-mapped device to the range.
+    pub fn device(self, range: RangeInclusive<u16>, device: Box<dyn Bus>) -> Self {
+        self.map(range, Region::Device(device))
+    }
+
+    /// Marks the range as unmapped (open bus).
+    pub fn unmapped(self, range: RangeInclusive<u16>) -> Self {
+        self.map(range, Region::Unmapped)
+    }
+
+    /// Sets the policy for writes into ROM.
+    pub fn rom_writes(mut self, policy: RomWrites) -> Self {
+        self.rom_policy = policy;
+        self
+    }
+
+    /// Maps any kind of region to the range. The contents of a RAM or ROM region are resized to
+    /// the range, padded with zeros or truncated.
+    pub fn map(mut self, range: RangeInclusive<u16>, region: Region) -> Self {
+        let mut region: Region = region;
+        if let Region::Ram(bytes) | Region::Rom(bytes) = &mut region {
+            bytes.resize(Self::range_size(&range), 0);
+        }
+
+        self.mappings.push(Mapping {
+            start: *range.start(),
+            end: *range.end(),
+            region,
+        });
+        self
+    }
+
+    /// Copies `data` into the map starting at `start`, writing into RAM and ROM alike. Meant to
+    /// load programs and images rather than to be used while emulating.
+    pub fn load(&mut self, start: u16, data: &[u8]) {
+        for (offset, value) in data.iter().enumerate() {
+            let addr: u16 = start.wrapping_add(offset as u16);
+            if let Some((index, offset)) = self.resolve(addr) {
+                match &mut self.mappings[index].region {
+                    Region::Ram(bytes) | Region::Rom(bytes) => bytes[offset] = *value,
+                    Region::Device(device) => device.write(offset as u16, *value),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Returns the number of addresses in a range.
+    fn range_size(range: &RangeInclusive<u16>) -> usize {
+        (*range.end() as usize + 1).saturating_sub(*range.start() as usize)
+    }
+
+    /// Finds the mapping which handles an address, following mirrors. Returns its index and
+    /// the offset of the address within it.
+    fn resolve(&self, addr: u16) -> Option<(usize, usize)> {
+        let mut addr: u16 = addr;
+
+        for _ in 0..Self::MAX_MIRROR_DEPTH {
+            let index: usize = self
+                .mappings
+                .iter()
+                .rposition(|mapping| mapping.start <= addr && addr <= mapping.end)?;
+            let mapping: &Mapping = &self.mappings[index];
+            let offset: usize = (addr - mapping.start) as usize;
+
+            match mapping.region {
+                Region::Mirror { start, size } if size > 0 => {
+                    addr = start.wrapping_add((offset % size as usize) as u16);
+                }
+                Region::Mirror { .. } => return None,
+                _ => return Some((index, offset)),
+            }
+        }
+
+        None
+    }
+}
+
+impl Bus for MemoryMap {
+    fn read(&mut self, addr: u16) -> u8 {
+        if let Some((index, offset)) = self.resolve(addr) {
+            match &mut self.mappings[index].region {
+                Region::Ram(bytes) | Region::Rom(bytes) => self.open_bus = bytes[offset],
+                Region::Device(device) => self.open_bus = device.read(offset as u16),
+                _ => {}
+            }
+        }
+
+        self.open_bus
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        self.open_bus = data;
+
+        if let Some((index, offset)) = self.resolve(addr) {
+            match &mut self.mappings[index].region {
+                Region::Ram(bytes) => bytes[offset] = data,
+                Region::Rom(_) if self.rom_policy == RomWrites::Report => {
+                    self.rom_writes.push((addr, data));
+                }
+                Region::Rom(_) if self.rom_policy == RomWrites::Fault => self.fault = Some(addr),
+                Region::Device(device) => device.write(offset as u16, data),
+                _ => {}
+            }
+        }
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        match self.resolve(addr) {
+            Some((index, offset)) => match &self.mappings[index].region {
+                Region::Ram(bytes) | Region::Rom(bytes) => bytes[offset],
+                Region::Device(device) => device.peek(offset as u16),
+                _ => self.open_bus,
+            },
+            None => self.open_bus,
+        }
+    }
+
+    fn poke(&mut self, addr: u16, data: u8) {
+        self.load(addr, &[data]);
+    }
+
+    fn tick(&mut self, cycles: usize) {
+        for mapping in self.mappings.iter_mut() {
+            if let Region::Device(device) = &mut mapping.region {
+                device.tick(cycles);
+            }
+        }
+    }
+
+    /// Saves the contents of the RAM and ROM regions and the state of every device, in the
+    /// order they were mapped, followed by the value on the data bus.
+    fn save_state(&self, state: &mut Vec<u8>) {
+        for mapping in self.mappings.iter() {
+            match &mapping.region {
+                Region::Ram(bytes) | Region::Rom(bytes) => state.extend_from_slice(bytes),
+                Region::Device(device) => {
+                    let mut device_state: Vec<u8> = Vec::new();
+                    device.save_state(&mut device_state);
+                    state.extend_from_slice(&(device_state.len() as u32).to_le_bytes());
+                    state.extend_from_slice(&device_state);
+                }
+                _ => {}
+            }
+        }
+        state.push(self.open_bus);
+    }
+
+    /// Restores a state saved by a map with the same regions.
+    fn load_state(&mut self, state: &[u8]) -> Result<()> {
+        let mut reader: Reader = Reader::new(state);
+        for mapping in self.mappings.iter_mut() {
+            match &mut mapping.region {
+                Region::Ram(bytes) | Region::Rom(bytes) => {
+                    let size: usize = bytes.len();
+                    bytes.copy_from_slice(reader.take(size)?);
+                }
+                Region::Device(device) => {
+                    let size: usize = reader.u32()? as usize;
+                    device.load_state(reader.take(size)?)?;
+                }
+                _ => {}
+            }
+        }
+        self.open_bus = reader.byte()?;
+        reader.finish()
+    }
+
+    /// Returns the faulty write into ROM, or else the first fault of a device, translated to
+    /// the address the CPU accessed.
+    fn fault(&mut self) -> Option<u16> {
+        if let Some(addr) = self.fault.take() {
+            return Some(addr);
+        }
+
+        self.mappings
+            .iter_mut()
+            .find_map(|mapping| match &mut mapping.region {
+                Region::Device(device) => device
+                    .fault()
+                    .map(|offset| mapping.start.wrapping_add(offset)),
+                _ => None,
+            })
+    }
+
+    fn irq(&self) -> bool {
+        self.mappings.iter().any(|mapping| match &mapping.region {
+            Region::Device(device) => device.irq(),
+            _ => false,
+        })
+    }
+
+    fn nmi(&self) -> bool {
+        self.mappings.iter().any(|mapping| match &mapping.region {
+            Region::Device(device) => device.nmi(),
+            _ => false,
+        })
+    }
+
+    /// Clears every RAM region and device. ROM contents are kept.
+    fn clear(&mut self) {
+        for mapping in self.mappings.iter_mut() {
+            match &mut mapping.region {
+                Region::Ram(bytes) => bytes.iter_mut().for_each(|byte| *byte = 0),
+                Region::Device(device) => device.clear(),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,773 @@
+//! A debugger for the CPU: stepping over and out of subroutines, breakpoints with optional
+//! conditions on the registers and the memory, watchpoints on the accesses to the memory, a
+//! history of the last instructions executed to step back through, and access to the registers
+//! by name. The interactive prompt is built on it, and so can be other front-ends.
+
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+
+use crate::bus::{Bus, BusAccess};
+use crate::disassembler::Symbols;
+use crate::error::{Error, Result};
+use crate::opcode::Mnemonic;
+use crate::p6502::{Snapshot, StepResult, StepStatus, P6502};
+
+/// The registers and flags of the CPU, as named by the debugger.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Register {
+    A,
+    X,
+    Y,
+    Sp,
+    Pc,
+    /// The status flags as a byte
+    P,
+    /// Negative flag
+    N,
+    /// Overflow flag
+    V,
+    /// Decimal flag
+    D,
+    /// Interrupt mask
+    I,
+    /// Zero flag
+    Z,
+    /// Carry flag
+    C,
+}
+
+impl Register {
+    /// Finds a register by its name, in any case. The stack pointer can also be called `S`.
+    pub fn parse(name: &str) -> Option<Register> {
+        match name.to_ascii_uppercase().as_str() {
+            "A" => Some(Register::A),
+            "X" => Some(Register::X),
+            "Y" => Some(Register::Y),
+            "SP" | "S" => Some(Register::Sp),
+            "PC" => Some(Register::Pc),
+            "P" => Some(Register::P),
+            "N" => Some(Register::N),
+            "V" => Some(Register::V),
+            "D" => Some(Register::D),
+            "I" => Some(Register::I),
+            "Z" => Some(Register::Z),
+            "C" => Some(Register::C),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the register. Flags are either 0 or 1.
+    pub fn get<B: Bus>(self, cpu: &P6502<B>) -> u16 {
+        match self {
+            Register::A => cpu.a as u16,
+            Register::X => cpu.x as u16,
+            Register::Y => cpu.y as u16,
+            Register::Sp => cpu.sp as u16,
+            Register::Pc => cpu.pc,
+            Register::P => cpu.flags.as_binary() as u16,
+            Register::N => cpu.flags.n as u16,
+            Register::V => cpu.flags.v as u16,
+            Register::D => cpu.flags.d as u16,
+            Register::I => cpu.flags.i as u16,
+            Register::Z => cpu.flags.z as u16,
+            Register::C => cpu.flags.c as u16,
+        }
+    }
+
+    /// Changes the value of the register. The 8-bit registers keep the low byte of the value
+    /// and the flags are set by any value but 0.
+    pub fn set<B: Bus>(self, cpu: &mut P6502<B>, value: u16) {
+        match self {
+            Register::A => cpu.a = value as u8,
+            Register::X => cpu.x = value as u8,
+            Register::Y => cpu.y = value as u8,
+            Register::Sp => cpu.sp = value as u8,
+            Register::Pc => cpu.pc = value,
+            Register::P => cpu.flags = crate::p6502::P6502Flags::from_binary(value as u8),
+            Register::N => cpu.flags.n = value != 0,
+            Register::V => cpu.flags.v = value != 0,
+            Register::D => cpu.flags.d = value != 0,
+            Register::I => cpu.flags.i = value != 0,
+            Register::Z => cpu.flags.z = value != 0,
+            Register::C => cpu.flags.c = value != 0,
+        }
+    }
+}
+
+/// An expression on the registers and the memory, such as `A == $10 && [count] != 0`. It can
+/// use numbers (decimal, `$` hexadecimal or `%` binary), symbols, registers, memory bytes
+/// (`[ADDRESS]`), parentheses, `+ - &`, comparisons (`== != < <= > >=`), `&&`, `||` and `!`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Condition {
+    text: String,
+    expression: Expression,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Expression {
+    Number(u32),
+    Register(Register),
+    Memory(Box<Expression>),
+    Not(Box<Expression>),
+    Binary(Box<Expression>, Operator, Box<Expression>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    BitAnd,
+}
+
+/// The binary operators, from the lowest to the highest precedence. Longer operators come
+/// first so that `<=` is not taken for `<`.
+const OPERATORS: [&[(&str, Operator)]; 4] = [
+    &[("||", Operator::Or)],
+    &[("&&", Operator::And)],
+    &[
+        ("==", Operator::Equal),
+        ("!=", Operator::NotEqual),
+        ("<=", Operator::LessOrEqual),
+        (">=", Operator::GreaterOrEqual),
+        ("<", Operator::Less),
+        (">", Operator::Greater),
+    ],
+    &[
+        ("+", Operator::Add),
+        ("-", Operator::Subtract),
+        ("&", Operator::BitAnd),
+    ],
+];
+
+impl Condition {
+    /// Parses a condition. Symbols are replaced by their addresses.
+    pub fn parse(text: &str, symbols: &Symbols) -> Result<Condition> {
+        let mut parser: Parser = Parser {
+            text: text.as_bytes(),
+            position: 0,
+            symbols,
+        };
+
+        let expression: Expression = parser.expression(0)?;
+        parser.skip_spaces();
+        if parser.position < parser.text.len() {
+            return Err(parser.error("unexpected characters"));
+        }
+
+        Ok(Condition {
+            text: text.trim().to_string(),
+            expression,
+        })
+    }
+
+    /// Returns whether the condition holds. The memory is only peeked.
+    pub fn evaluate<B: Bus>(&self, cpu: &P6502<B>) -> bool {
+        evaluate(&self.expression, cpu) != 0
+    }
+
+    /// Returns the condition as it was written.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+fn evaluate<B: Bus>(expression: &Expression, cpu: &P6502<B>) -> u32 {
+    match expression {
+        Expression::Number(value) => *value,
+        Expression::Register(register) => register.get(cpu) as u32,
+        Expression::Memory(addr) => cpu.memory.peek(evaluate(addr, cpu) as u16) as u32,
+        Expression::Not(value) => (evaluate(value, cpu) == 0) as u32,
+        Expression::Binary(left, operator, right) => {
+            let left: u32 = evaluate(left, cpu);
+            // Like in most languages, the right side of && and || is only needed sometimes.
+            match operator {
+                Operator::Or if left != 0 => return 1,
+                Operator::And if left == 0 => return 0,
+                _ => {}
+            }
+            let right: u32 = evaluate(right, cpu);
+
+            match operator {
+                Operator::Or | Operator::And => (right != 0) as u32,
+                Operator::Equal => (left == right) as u32,
+                Operator::NotEqual => (left != right) as u32,
+                Operator::Less => (left < right) as u32,
+                Operator::LessOrEqual => (left <= right) as u32,
+                Operator::Greater => (left > right) as u32,
+                Operator::GreaterOrEqual => (left >= right) as u32,
+                Operator::Add => left.wrapping_add(right),
+                Operator::Subtract => left.wrapping_sub(right),
+                Operator::BitAnd => left & right,
+            }
+        }
+    }
+}
+
+/// A recursive descent parser of conditions.
+struct Parser<'a> {
+    text: &'a [u8],
+    position: usize,
+    symbols: &'a Symbols,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> Error {
+        Error::Expression(format!("{} at column {}", message, self.position + 1))
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.position < self.text.len() && self.text[self.position].is_ascii_whitespace() {
+            self.position += 1;
+        }
+    }
+
+    fn expression(&mut self, level: usize) -> Result<Expression> {
+        if level == OPERATORS.len() {
+            return self.unary();
+        }
+
+        let mut left: Expression = self.expression(level + 1)?;
+        loop {
+            self.skip_spaces();
+            let rest: &[u8] = &self.text[self.position..];
+            let (symbol, operator): (&str, Operator) = match OPERATORS[level]
+                .iter()
+                .find(|(symbol, _)| rest.starts_with(symbol.as_bytes()))
+            {
+                Some(entry) => *entry,
+                None => return Ok(left),
+            };
+            // A single & must not be the beginning of &&.
+            if symbol == "&" && rest.starts_with(b"&&") {
+                return Ok(left);
+            }
+            self.position += symbol.len();
+
+            let right: Expression = self.expression(level + 1)?;
+            left = Expression::Binary(Box::new(left), operator, Box::new(right));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expression> {
+        self.skip_spaces();
+        if self.text.get(self.position) == Some(&b'!') {
+            self.position += 1;
+            return Ok(Expression::Not(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expression> {
+        self.skip_spaces();
+
+        match self.text.get(self.position).copied() {
+            Some(b'(') | Some(b'[') => {
+                let open: u8 = self.text[self.position];
+                self.position += 1;
+                let inner: Expression = self.expression(0)?;
+                self.skip_spaces();
+
+                let close: u8 = if open == b'(' { b')' } else { b']' };
+                if self.text.get(self.position) != Some(&close) {
+                    return Err(self.error(&format!("missing '{}'", close as char)));
+                }
+                self.position += 1;
+
+                if open == b'(' {
+                    Ok(inner)
+                } else {
+                    Ok(Expression::Memory(Box::new(inner)))
+                }
+            }
+            Some(b'$') => {
+                self.position += 1;
+                self.number(16)
+            }
+            Some(b'%') => {
+                self.position += 1;
+                self.number(2)
+            }
+            Some(b'0'..=b'9') => self.number(10),
+            Some(character) if character.is_ascii_alphabetic() || b"_.".contains(&character) => {
+                let start: usize = self.position;
+                while let Some(character) = self.text.get(self.position) {
+                    if !character.is_ascii_alphanumeric() && !b"_.".contains(character) {
+                        break;
+                    }
+                    self.position += 1;
+                }
+
+                let name: &str = core::str::from_utf8(&self.text[start..self.position])
+                    .map_err(|_| self.error("invalid name"))?;
+                if let Some(register) = Register::parse(name) {
+                    return Ok(Expression::Register(register));
+                }
+                match self.symbols.find(name) {
+                    Some(addr) => Ok(Expression::Number(addr as u32)),
+                    None => Err(Error::Expression(format!("unknown name '{}'", name))),
+                }
+            }
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("missing operand")),
+        }
+    }
+
+    fn number(&mut self, radix: u32) -> Result<Expression> {
+        let start: usize = self.position;
+        while let Some(character) = self.text.get(self.position) {
+            if !(*character as char).is_digit(radix) {
+                break;
+            }
+            self.position += 1;
+        }
+
+        let digits: &str = core::str::from_utf8(&self.text[start..self.position]).unwrap_or("");
+        u32::from_str_radix(digits, radix)
+            .map(Expression::Number)
+            .map_err(|_| self.error("invalid number"))
+    }
+}
+
+/// A place where the execution stops, if its condition holds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Breakpoint {
+    /// The number which identifies the breakpoint
+    pub id: usize,
+    /// The address of the instruction
+    pub address: u16,
+    /// The condition, if any
+    pub condition: Option<Condition>,
+    /// Whether it can stop the execution
+    pub enabled: bool,
+}
+
+/// The accesses to the memory a watchpoint looks for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Watch {
+    /// The CPU reads data from the range. Fetching the bytes of the instruction itself doesn't
+    /// count, but dummy reads do.
+    Read,
+    /// The CPU writes to the range.
+    Write,
+    /// The CPU reads from or writes to the range.
+    Access,
+    /// The CPU is about to execute an instruction in the range.
+    Execute,
+}
+
+/// A range of addresses where the execution stops when they are accessed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Watchpoint {
+    /// The number which identifies the watchpoint. Breakpoints and watchpoints never share one.
+    pub id: usize,
+    /// The addresses watched
+    pub range: RangeInclusive<u16>,
+    /// The kind of access
+    pub watch: Watch,
+    /// The value read or written (the opcode, when executing), if only that one matters
+    pub value: Option<u8>,
+    /// Whether it can stop the execution
+    pub enabled: bool,
+}
+
+impl Watchpoint {
+    /// Returns whether the access triggers the watchpoint.
+    fn matches(&self, access: &BusAccess) -> bool {
+        self.enabled
+            && self.range.contains(&access.addr)
+            && self.value.map_or(true, |value| value == access.data)
+    }
+}
+
+/// Why the debugger gave the control back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    /// The requested instruction, subroutine or function has been executed.
+    Done,
+    /// A breakpoint has been reached: its instruction is the next one.
+    Breakpoint(usize),
+    /// A watchpoint has been triggered by the instruction at `pc`. The execution stops once
+    /// the instruction is finished, except for `Watch::Execute`, which stops before it runs.
+    /// Going backwards, it always stops before the instruction.
+    Watchpoint {
+        id: usize,
+        pc: u16,
+        access: BusAccess,
+    },
+    /// The CPU is no longer running: it halted at a BRK, executed a STP or is waiting for an
+    /// interrupt.
+    Halted(StepStatus),
+    /// There is no earlier instruction in the history to go back to.
+    HistoryStart,
+}
+
+/// The number of instructions a debugger remembers by default.
+pub const HISTORY_LIMIT: usize = 10_000;
+
+/// What it takes to undo an executed instruction.
+#[derive(Clone, Debug)]
+struct Record {
+    /// The registers and internal state before the instruction
+    snapshot: Snapshot,
+    /// The cycles elapsed before the instruction
+    cycles: usize,
+    /// The addresses written to, with the values they held before, in order
+    overwritten: Vec<(u16, u8)>,
+    /// What the instruction did
+    result: StepResult,
+}
+
+/// Runs a CPU under the control of breakpoints and watchpoints.
+#[derive(Clone, Debug)]
+pub struct Debugger {
+    breakpoints: Vec<Breakpoint>,
+    watchpoints: Vec<Watchpoint>,
+    next_id: usize,
+    history: VecDeque<Record>,
+    history_limit: usize,
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        Self {
+            breakpoints: Vec::new(),
+            watchpoints: Vec::new(),
+            next_id: 0,
+            history: VecDeque::new(),
+            history_limit: HISTORY_LIMIT,
+        }
+    }
+}
+
+impl Debugger {
+    /// Creates a debugger without breakpoints nor watchpoints, which remembers the last
+    /// `HISTORY_LIMIT` instructions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a breakpoint and returns its identifier.
+    pub fn add_breakpoint(&mut self, address: u16, condition: Option<Condition>) -> usize {
+        self.next_id += 1;
+        self.breakpoints.push(Breakpoint {
+            id: self.next_id,
+            address,
+            condition,
+            enabled: true,
+        });
+        self.next_id
+    }
+
+    /// Removes a breakpoint. Returns whether it existed.
+    pub fn remove_breakpoint(&mut self, id: usize) -> bool {
+        let count: usize = self.breakpoints.len();
+        self.breakpoints.retain(|breakpoint| breakpoint.id != id);
+        self.breakpoints.len() != count
+    }
+
+    /// Enables or disables a breakpoint. Returns whether it exists.
+    pub fn enable_breakpoint(&mut self, id: usize, enabled: bool) -> bool {
+        match self
+            .breakpoints
+            .iter_mut()
+            .find(|breakpoint| breakpoint.id == id)
+        {
+            Some(breakpoint) => {
+                breakpoint.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the breakpoints, in the order they were added.
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Returns the identifier of the breakpoint which stops the CPU at its current address,
+    /// if any.
+    pub fn breakpoint_hit<B: Bus>(&self, cpu: &P6502<B>) -> Option<usize> {
+        self.breakpoints
+            .iter()
+            .find(|breakpoint| {
+                breakpoint.enabled
+                    && breakpoint.address == cpu.pc
+                    && breakpoint
+                        .condition
+                        .as_ref()
+                        .map_or(true, |condition| condition.evaluate(cpu))
+            })
+            .map(|breakpoint| breakpoint.id)
+    }
+
+    /// Adds a watchpoint and returns its identifier.
+    pub fn add_watchpoint(
+        &mut self,
+        range: RangeInclusive<u16>,
+        watch: Watch,
+        value: Option<u8>,
+    ) -> usize {
+        self.next_id += 1;
+        self.watchpoints.push(Watchpoint {
+            id: self.next_id,
+            range,
+            watch,
+            value,
+            enabled: true,
+        });
+        self.next_id
+    }
+
+    /// Removes a watchpoint. Returns whether it existed.
+    pub fn remove_watchpoint(&mut self, id: usize) -> bool {
+        let count: usize = self.watchpoints.len();
+        self.watchpoints.retain(|watchpoint| watchpoint.id != id);
+        self.watchpoints.len() != count
+    }
+
+    /// Enables or disables a watchpoint. Returns whether it exists.
+    pub fn enable_watchpoint(&mut self, id: usize, enabled: bool) -> bool {
+        match self
+            .watchpoints
+            .iter_mut()
+            .find(|watchpoint| watchpoint.id == id)
+        {
+            Some(watchpoint) => {
+                watchpoint.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the watchpoints, in the order they were added.
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    /// Returns the first watchpoint triggered by an executed instruction, if any.
+    fn access_watched(&self, result: &StepResult) -> Option<Stop> {
+        // The bytes of the instruction are fetched, not read as data. When an interrupt is
+        // serviced, the instruction is the first one of its handler.
+        let size: u16 = result.instruction.size();
+        let fetched = |addr: u16| addr.wrapping_sub(result.address) < size;
+
+        for access in result.accesses.iter() {
+            let hit: Option<&Watchpoint> = self.watchpoints.iter().find(|watchpoint| {
+                let kind: bool = match watchpoint.watch {
+                    Watch::Read => !access.write && !fetched(access.addr),
+                    Watch::Write => access.write,
+                    Watch::Access => access.write || !fetched(access.addr),
+                    Watch::Execute => false,
+                };
+                kind && watchpoint.matches(access)
+            });
+
+            if let Some(watchpoint) = hit {
+                return Some(Stop::Watchpoint {
+                    id: watchpoint.id,
+                    pc: result.address,
+                    access: *access,
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Returns the execute watchpoint triggered by the next instruction, if any.
+    fn execution_watched<B: Bus>(&self, cpu: &P6502<B>) -> Option<Stop> {
+        let access: BusAccess = BusAccess {
+            addr: cpu.pc,
+            data: cpu.memory.peek(cpu.pc),
+            write: false,
+        };
+
+        self.watchpoints
+            .iter()
+            .find(|watchpoint| watchpoint.watch == Watch::Execute && watchpoint.matches(&access))
+            .map(|watchpoint| Stop::Watchpoint {
+                id: watchpoint.id,
+                pc: cpu.pc,
+                access,
+            })
+    }
+
+    /// Executes one instruction.
+    pub fn step<B: Bus>(&mut self, cpu: &mut P6502<B>) -> Result<Stop> {
+        self.run(cpu, |_, _| true)
+    }
+
+    /// Executes one instruction, or a whole subroutine if the instruction is a JSR.
+    pub fn next<B: Bus>(&mut self, cpu: &mut P6502<B>) -> Result<Stop> {
+        if cpu.memory.peek(cpu.pc) != P6502::INS_JSR_ABS {
+            return self.step(cpu);
+        }
+
+        // The subroutine has returned once the stack is back where it was after the JSR.
+        let return_address: u16 = cpu.pc.wrapping_add(3);
+        let sp: u8 = cpu.sp;
+        self.run(cpu, |cpu, _| cpu.pc == return_address && cpu.sp == sp)
+    }
+
+    /// Executes instructions until the current subroutine returns.
+    pub fn finish<B: Bus>(&mut self, cpu: &mut P6502<B>) -> Result<Stop> {
+        // Interrupts in between leave the stack where it was, whereas returning pops the
+        // return address.
+        let sp: u8 = cpu.sp;
+        self.run(cpu, |cpu, result| {
+            matches!(result.instruction.mnemonic, Mnemonic::Rts | Mnemonic::Rti) && cpu.sp > sp
+        })
+    }
+
+    /// Executes instructions until a breakpoint is reached, a watchpoint is triggered or the
+    /// CPU halts.
+    pub fn resume<B: Bus>(&mut self, cpu: &mut P6502<B>) -> Result<Stop> {
+        self.run(cpu, |_, _| false)
+    }
+
+    /// Executes instructions like `resume`, but gives the control back with `Stop::Done` after
+    /// `instructions` of them, so that a front-end can check for requests in between.
+    pub fn resume_for<B: Bus>(&mut self, cpu: &mut P6502<B>, instructions: usize) -> Result<Stop> {
+        let mut count: usize = 0;
+        let stop: Stop = self.run(cpu, |_, _| {
+            count += 1;
+            count >= instructions
+        })?;
+        if stop != Stop::Done {
+            return Ok(stop);
+        }
+
+        // `run` doesn't look for breakpoints once it is done, but the next call would execute
+        // the instruction at one.
+        if let Some(id) = self.breakpoint_hit(cpu) {
+            return Ok(Stop::Breakpoint(id));
+        }
+        Ok(self.execution_watched(cpu).unwrap_or(Stop::Done))
+    }
+
+    /// Executes instructions until `done` returns `true` after one of them, a breakpoint is
+    /// reached, a watchpoint is triggered or the CPU halts. The first instruction is always
+    /// executed, so the execution can go on from a breakpoint.
+    fn run<B, F>(&mut self, cpu: &mut P6502<B>, mut done: F) -> Result<Stop>
+    where
+        B: Bus,
+        F: FnMut(&P6502<B>, &StepResult) -> bool,
+    {
+        loop {
+            let result: StepResult = self.step_recorded(cpu)?;
+            if result.status != StepStatus::Running {
+                return Ok(Stop::Halted(result.status));
+            }
+            if let Some(stop) = self.access_watched(&result) {
+                return Ok(stop);
+            }
+            if done(cpu, &result) {
+                return Ok(Stop::Done);
+            }
+            if let Some(id) = self.breakpoint_hit(cpu) {
+                return Ok(Stop::Breakpoint(id));
+            }
+            if let Some(stop) = self.execution_watched(cpu) {
+                return Ok(stop);
+            }
+        }
+    }
+
+    /// Sets how many instructions are remembered, forgetting the oldest ones if there are too
+    /// many. Zero turns the history off.
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history_limit = limit;
+        while self.history.len() > limit {
+            self.history.pop_front();
+        }
+    }
+
+    /// Returns how many instructions can be stepped back.
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Forgets the instructions executed so far. Needed whenever the CPU or the memory is
+    /// changed by other means than the debugger, since they could no longer be undone.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Executes one instruction and remembers how to undo it. Instructions started by `tick`
+    /// are not remembered, since their first bus accesses were not recorded.
+    fn step_recorded<B: Bus>(&mut self, cpu: &mut P6502<B>) -> Result<StepResult> {
+        if self.history_limit == 0 || cpu.mid_instruction() {
+            return cpu.step();
+        }
+
+        let snapshot: Snapshot = cpu.snapshot();
+        let cycles: usize = cpu.cycles;
+        let (result, overwritten): (StepResult, Vec<(u16, u8)>) = cpu.step_undoable()?;
+
+        if self.history.len() == self.history_limit {
+            self.history.pop_front();
+        }
+        self.history.push_back(Record {
+            snapshot,
+            cycles,
+            overwritten,
+            result: result.clone(),
+        });
+        Ok(result)
+    }
+
+    /// Undoes the last instruction executed by the debugger. Devices see the values written
+    /// back into their registers, but their internal state is not restored.
+    pub fn step_back<B: Bus>(&mut self, cpu: &mut P6502<B>) -> Stop {
+        match self.undo(cpu) {
+            Some(_) => Stop::Done,
+            None => Stop::HistoryStart,
+        }
+    }
+
+    /// Undoes instructions until a breakpoint is reached, an undone instruction triggers a
+    /// watchpoint or there is no history left. The first instruction is always undone, so the
+    /// execution can go back from a breakpoint.
+    pub fn reverse_resume<B: Bus>(&mut self, cpu: &mut P6502<B>) -> Stop {
+        while let Some(result) = self.undo(cpu) {
+            if let Some(stop) = self.access_watched(&result) {
+                return stop;
+            }
+            if let Some(id) = self.breakpoint_hit(cpu) {
+                return Stop::Breakpoint(id);
+            }
+            if let Some(stop) = self.execution_watched(cpu) {
+                return stop;
+            }
+        }
+
+        Stop::HistoryStart
+    }
+
+    /// Undoes the last instruction in the history and returns what it did, if there is one.
+    fn undo<B: Bus>(&mut self, cpu: &mut P6502<B>) -> Option<StepResult> {
+        let record: Record = self.history.pop_back()?;
+        for (addr, data) in record.overwritten.iter().rev() {
+            cpu.memory.poke(*addr, *data);
+        }
+        cpu.restore(record.snapshot);
+        cpu.cycles = record.cycles;
+        cpu.error = None;
+
+        Some(record.result)
+    }
+}
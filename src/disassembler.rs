@@ -0,0 +1,196 @@
+//! A disassembler for the 6502 family, built on the same opcode tables as the CPU. It turns
+//! the contents of a bus into `ADDR  BYTES  MNEMONIC OPERAND` lines, naming the addresses found
+//! in a symbol table.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::bus::Bus;
+use crate::opcode::{self, AddressingMode, Opcode};
+use crate::p6502::Variant;
+
+/// Names for addresses, used by the disassembler instead of numbers.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Symbols {
+    names: BTreeMap<u16, String>,
+}
+
+impl Symbols {
+    /// Creates an empty symbol table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a symbol table from names and their values, such as the symbols of an
+    /// `Assembly`. When several names share a value, the first one in alphabetical order wins.
+    pub fn from_names(names: &BTreeMap<String, u16>) -> Self {
+        let mut symbols: Self = Self::new();
+        for (name, addr) in names.iter() {
+            symbols.insert(*addr, name);
+        }
+        symbols
+    }
+
+    /// Names an address, unless it already has a name.
+    pub fn insert(&mut self, addr: u16, name: &str) {
+        self.names.entry(addr).or_insert_with(|| name.to_string());
+    }
+
+    /// Returns the name of an address, if any.
+    pub fn get(&self, addr: u16) -> Option<&str> {
+        self.names.get(&addr).map(|name| name.as_str())
+    }
+
+    /// Returns the address of a name, if any.
+    pub fn find(&self, name: &str) -> Option<u16> {
+        self.names
+            .iter()
+            .find(|(_, value)| value.as_str() == name)
+            .map(|(addr, _)| *addr)
+    }
+
+    /// Returns whether the table has no names.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// A disassembled instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    /// The address of the opcode
+    pub address: u16,
+    /// The opcode and its operand
+    pub bytes: Vec<u8>,
+    /// The description of the opcode
+    pub opcode: Opcode,
+    /// The operand in assembly syntax, with the addresses replaced by their symbols
+    pub operand: String,
+    /// The symbol of the address of the instruction, if any
+    pub label: Option<String>,
+}
+
+impl Instruction {
+    /// Returns the instruction in assembly syntax, as in `LDA ($10),Y`.
+    pub fn text(&self) -> String {
+        if self.operand.is_empty() {
+            self.opcode.mnemonic.to_string()
+        } else {
+            format!("{} {}", self.opcode.mnemonic, self.operand)
+        }
+    }
+
+    /// Returns the address of the next instruction.
+    pub fn next_address(&self) -> u16 {
+        self.address.wrapping_add(self.bytes.len() as u16)
+    }
+}
+
+impl fmt::Display for Instruction {
+    /// Writes the instruction as `ADDR  BYTES  MNEMONIC OPERAND`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bytes: Vec<String> = self
+            .bytes
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect();
+        write!(
+            f,
+            "{:04X}  {:<8}  {}",
+            self.address,
+            bytes.join(" "),
+            self.text()
+        )
+    }
+}
+
+/// Disassembles the instruction at an address. The bus is only peeked, so devices don't
+/// notice.
+pub fn disassemble_instruction<B: Bus + ?Sized>(
+    bus: &B,
+    variant: Variant,
+    address: u16,
+    symbols: &Symbols,
+) -> Instruction {
+    let opcode: Opcode = opcode::decode(variant, bus.peek(address));
+    let bytes: Vec<u8> = (0..opcode.size())
+        .map(|offset| bus.peek(address.wrapping_add(offset)))
+        .collect();
+
+    let byte: u8 = bytes.get(1).copied().unwrap_or(0);
+    let word: u16 = ((bytes.get(2).copied().unwrap_or(0) as u16) << 8) | byte as u16;
+    let zero_page: String = name(symbols, byte as u16, 2);
+    let absolute: String = name(symbols, word, 4);
+    let next: u16 = address.wrapping_add(opcode.size());
+
+    let operand: String = match opcode.mode {
+        AddressingMode::Implied => String::new(),
+        AddressingMode::Accumulator => "A".to_string(),
+        AddressingMode::Immediate => format!("#${:02X}", byte),
+        AddressingMode::ZeroPage => zero_page,
+        AddressingMode::ZeroPageX => format!("{},X", zero_page),
+        AddressingMode::ZeroPageY => format!("{},Y", zero_page),
+        AddressingMode::Relative => name(symbols, branch_target(next, byte), 4),
+        AddressingMode::Absolute => absolute,
+        AddressingMode::AbsoluteX => format!("{},X", absolute),
+        AddressingMode::AbsoluteY => format!("{},Y", absolute),
+        AddressingMode::Indirect => format!("({})", absolute),
+        AddressingMode::IndexedIndirect => format!("({},X)", zero_page),
+        AddressingMode::IndirectIndexed => format!("({}),Y", zero_page),
+        AddressingMode::ZeroPageIndirect => format!("({})", zero_page),
+        AddressingMode::AbsoluteIndexedIndirect => format!("({},X)", absolute),
+        AddressingMode::ZeroPageRelative => {
+            let offset: u8 = bytes.get(2).copied().unwrap_or(0);
+            format!(
+                "{},{}",
+                zero_page,
+                name(symbols, branch_target(next, offset), 4)
+            )
+        }
+    };
+
+    Instruction {
+        address,
+        bytes,
+        opcode,
+        operand,
+        label: symbols.get(address).map(|label| label.to_string()),
+    }
+}
+
+/// Disassembles every instruction which starts between two addresses, both included.
+pub fn disassemble<B: Bus + ?Sized>(
+    bus: &B,
+    variant: Variant,
+    start: u16,
+    end: u16,
+    symbols: &Symbols,
+) -> Vec<Instruction> {
+    let mut instructions: Vec<Instruction> = Vec::new();
+    let mut address: u32 = start as u32;
+
+    while address <= end as u32 {
+        let instruction: Instruction =
+            disassemble_instruction(bus, variant, address as u16, symbols);
+        address += instruction.bytes.len() as u32;
+        instructions.push(instruction);
+    }
+
+    instructions
+}
+
+/// Returns the symbol of an address, or the address in hexadecimal with as many digits.
+fn name(symbols: &Symbols, addr: u16, digits: usize) -> String {
+    match symbols.get(addr) {
+        Some(name) => name.to_string(),
+        None => format!("${:01$X}", addr, digits),
+    }
+}
+
+/// Returns the address a branch jumps to.
+fn branch_target(next: u16, offset: u8) -> u16 {
+    next.wrapping_add(offset as i8 as u16)
+}
@@ -0,0 +1,98 @@
+//! The errors reported by the emulator instead of stopping the whole process.
+
+use alloc::string::String;
+use core::fmt;
+
+/// Everything that can go wrong while loading or running a program.
+#[derive(Debug)]
+pub enum Error {
+    /// An undocumented opcode was reached while `IllegalPolicy::Error` was selected. The
+    /// Program Counter is left pointing at it.
+    IllegalOpcode { opcode: u8, pc: u16 },
+    /// A JAM opcode locked up the CPU. Only a reset unlocks it.
+    Jam { opcode: u8, pc: u16 },
+    /// A program file contains something which is not a byte in hexadecimal. Both the line
+    /// and the column are counted from 1.
+    Load {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    /// An assembly program has an error at the specified line (counted from 1) of the main
+    /// source, when `file` is `None`, or of an included file.
+    Assemble {
+        file: Option<String>,
+        line: usize,
+        message: String,
+    },
+    /// The bus could not carry out an access to `addr` made by the instruction at `pc`.
+    BusFault { addr: u16, pc: u16 },
+    /// A debugger expression could not be parsed.
+    Expression(String),
+    /// A save state could not be taken or restored.
+    SaveState(String),
+    /// A program file could not be read.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+}
+
+/// The result of the operations which may fail with an `Error`.
+pub type Result<T> = core::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::IllegalOpcode { opcode, pc } => {
+                write!(f, "illegal opcode ${:02x} at ${:04x}", opcode, pc)
+            }
+            Error::Jam { opcode, pc } => {
+                write!(f, "the CPU jammed at ${:04x} (opcode ${:02x})", pc, opcode)
+            }
+            Error::Load {
+                line,
+                column,
+                token,
+            } => write!(
+                f,
+                "unable to parse '{}' as a hexadecimal byte at line {}, column {}",
+                token, line, column
+            ),
+            Error::Assemble {
+                file: Some(file),
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", file, line, message),
+            Error::Assemble {
+                file: None,
+                line,
+                message,
+            } => write!(f, "line {}: {}", line, message),
+            Error::BusFault { addr, pc } => write!(
+                f,
+                "bus fault accessing ${:04x} from the instruction at ${:04x}",
+                addr, pc
+            ),
+            Error::Expression(message) => write!(f, "{}", message),
+            Error::SaveState(message) => write!(f, "invalid save state: {}", message),
+            #[cfg(feature = "std")]
+            Error::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
@@ -0,0 +1,476 @@
+//! A stub of the GDB remote serial protocol, so that the CPU can be debugged from GDB and the
+//! other front-ends which speak it. Requires the `std` feature.
+//!
+//! The stub works over any stream (`listen` accepts one over TCP) and drives the CPU through a
+//! `Debugger`, whose breakpoints it shares with the interactive prompt. It supports reading and
+//! writing the registers and the memory, software breakpoints, watchpoints, single-stepping and
+//! continuing, forwards and backwards through the history of the debugger. A `continue` can be
+//! interrupted from GDB (Ctrl-C). The registers are numbered as follows, each being sent in
+//! hexadecimal as a single byte except the Program Counter, which takes two in little-endian
+//! order:
+//!
+//! | Number | Register |
+//! |--------|----------|
+//! | 0      | A        |
+//! | 1      | X        |
+//! | 2      | Y        |
+//! | 3      | SP       |
+//! | 4      | PC       |
+//! | 5      | P        |
+
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+use crate::bus::Bus;
+use crate::debugger::{Debugger, Register, Stop, Watch};
+use crate::error::{Error, Result};
+use crate::p6502::{StepStatus, P6502};
+
+/// The registers in the order of their numbers.
+const REGISTERS: [Register; 6] = [
+    Register::A,
+    Register::X,
+    Register::Y,
+    Register::Sp,
+    Register::Pc,
+    Register::P,
+];
+
+/// The signals reported when the CPU stops.
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+const SIGSEGV: u8 = 11;
+
+/// The byte GDB sends to interrupt the execution.
+const INTERRUPT: u8 = 0x03;
+
+/// How many instructions a `continue` executes between two checks for an interrupt.
+const INSTRUCTIONS_PER_POLL: usize = 10_000;
+
+/// A stream connected to GDB, which can be read without waiting so that GDB can interrupt a
+/// `continue`.
+pub trait Connection: Read + Write {
+    /// Reads a byte if one has already arrived, without blocking.
+    fn try_read_byte(&mut self) -> io::Result<Option<u8>>;
+}
+
+impl Connection for TcpStream {
+    fn try_read_byte(&mut self) -> io::Result<Option<u8>> {
+        self.set_nonblocking(true)?;
+        let mut byte: [u8; 1] = [0];
+        let result: io::Result<usize> = self.read(&mut byte);
+        self.set_nonblocking(false)?;
+
+        match result {
+            // A closed stream is noticed by the next blocking read.
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(byte[0])),
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// A GDB session over a stream.
+pub struct GdbStub<S: Connection> {
+    stream: S,
+    /// The debugger identifiers of the breakpoints and watchpoints set by GDB, by type (as in
+    /// the `Z` packets), address and length.
+    points: BTreeMap<(u8, u16, u16), usize>,
+    /// Whether GDB asked to stop acknowledging the packets
+    no_ack: bool,
+    /// The bytes read while looking for an interrupt, which belong to the next packets
+    pending: VecDeque<u8>,
+}
+
+impl<S: Connection> GdbStub<S> {
+    /// Creates a session over a stream connected to GDB.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            points: BTreeMap::new(),
+            no_ack: false,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Returns the stream, once the session is over.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Answers the packets sent by GDB until it detaches, kills the program or closes the
+    /// stream. The breakpoints and watchpoints set from GDB are removed from the debugger at
+    /// the end.
+    pub fn serve<B: Bus>(&mut self, cpu: &mut P6502<B>, debugger: &mut Debugger) -> Result<()> {
+        let result: Result<()> = self.answer_packets(cpu, debugger);
+
+        for ((kind, _, _), id) in core::mem::take(&mut self.points) {
+            if kind == 0 {
+                debugger.remove_breakpoint(id);
+            } else {
+                debugger.remove_watchpoint(id);
+            }
+        }
+
+        result
+    }
+
+    fn answer_packets<B: Bus>(
+        &mut self,
+        cpu: &mut P6502<B>,
+        debugger: &mut Debugger,
+    ) -> Result<()> {
+        while let Some(packet) = self.read_packet()? {
+            let (command, arguments): (u8, &[u8]) = match packet.split_first() {
+                Some((command, arguments)) => (*command, arguments),
+                None => {
+                    self.send(b"")?;
+                    continue;
+                }
+            };
+            let arguments: String = String::from_utf8_lossy(arguments).into_owned();
+
+            // The history can't undo changes made by GDB.
+            if matches!(command, b'G' | b'P' | b'M') {
+                debugger.clear_history();
+            }
+
+            let reply: String = match command {
+                b'?' => format!("S{:02x}", SIGTRAP),
+                b'g' => REGISTERS
+                    .iter()
+                    .map(|register| register_hex(cpu, *register))
+                    .collect(),
+                b'G' => self.write_registers(cpu, &arguments),
+                b'p' => match usize::from_str_radix(&arguments, 16)
+                    .ok()
+                    .and_then(|number| REGISTERS.get(number))
+                {
+                    Some(register) => register_hex(cpu, *register),
+                    None => "E01".to_string(),
+                },
+                b'P' => match arguments.split_once('=') {
+                    Some((number, value)) => {
+                        match (
+                            usize::from_str_radix(number, 16)
+                                .ok()
+                                .and_then(|number| REGISTERS.get(number)),
+                            decode_hex(value),
+                        ) {
+                            (Some(register), Some(bytes)) if !bytes.is_empty() => {
+                                register.set(cpu, little_endian(&bytes));
+                                "OK".to_string()
+                            }
+                            _ => "E01".to_string(),
+                        }
+                    }
+                    None => "E01".to_string(),
+                },
+                b'm' => match parse_range(&arguments) {
+                    Some((addr, length)) => (0..length)
+                        .map(|offset| format!("{:02x}", cpu.memory.peek(addr.wrapping_add(offset))))
+                        .collect(),
+                    None => "E01".to_string(),
+                },
+                b'M' => match arguments
+                    .split_once(':')
+                    .and_then(|(range, data)| parse_range(range).zip(decode_hex(data)))
+                {
+                    Some(((addr, length), bytes)) if bytes.len() == length as usize => {
+                        for (offset, byte) in bytes.iter().enumerate() {
+                            cpu.memory.poke(addr.wrapping_add(offset as u16), *byte);
+                        }
+                        "OK".to_string()
+                    }
+                    _ => "E01".to_string(),
+                },
+                b'Z' | b'z' => self.toggle_point(debugger, command == b'Z', &arguments),
+                b's' | b'c' => {
+                    if let Ok(addr) = u16::from_str_radix(&arguments, 16) {
+                        cpu.pc = addr;
+                        debugger.clear_history();
+                    }
+                    if command == b's' {
+                        let result: Result<Stop> = debugger.step(cpu);
+                        self.stop_reply(debugger, result)
+                    } else {
+                        self.resume(cpu, debugger)?
+                    }
+                }
+                b'b' if arguments == "s" => {
+                    let stop: Stop = debugger.step_back(cpu);
+                    self.stop_reply(debugger, Ok(stop))
+                }
+                b'b' if arguments == "c" => {
+                    let stop: Stop = debugger.reverse_resume(cpu);
+                    self.stop_reply(debugger, Ok(stop))
+                }
+                b'q' if arguments.starts_with("Supported") => {
+                    "PacketSize=1000;QStartNoAckMode+;ReverseStep+;ReverseContinue+".to_string()
+                }
+                b'q' if arguments == "Attached" => "1".to_string(),
+                b'Q' if arguments == "StartNoAckMode" => {
+                    self.send(b"OK")?;
+                    self.no_ack = true;
+                    continue;
+                }
+                b'H' => "OK".to_string(),
+                b'D' => {
+                    self.send(b"OK")?;
+                    return Ok(());
+                }
+                b'k' => return Ok(()),
+                // Anything else is not supported, which is told with an empty reply.
+                _ => String::new(),
+            };
+
+            self.send(reply.as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets every register from a `G` packet.
+    fn write_registers<B: Bus>(&mut self, cpu: &mut P6502<B>, arguments: &str) -> String {
+        let bytes: Vec<u8> = match decode_hex(arguments) {
+            Some(bytes) if bytes.len() >= 7 => bytes,
+            _ => return "E01".to_string(),
+        };
+
+        let mut position: usize = 0;
+        for register in REGISTERS.iter() {
+            let size: usize = if *register == Register::Pc { 2 } else { 1 };
+            register.set(cpu, little_endian(&bytes[position..position + size]));
+            position += size;
+        }
+        "OK".to_string()
+    }
+
+    /// Adds or removes a breakpoint (type 0) or a watchpoint (types 2 to 4, for writes, reads
+    /// and both) as asked by a `Z` or `z` packet.
+    fn toggle_point(&mut self, debugger: &mut Debugger, insert: bool, arguments: &str) -> String {
+        let fields: Vec<&str> = arguments.split(',').collect();
+        let (kind, addr, length): (u8, u16, u16) = match (
+            fields.first().and_then(|text| text.parse::<u8>().ok()),
+            fields
+                .get(1)
+                .and_then(|text| u16::from_str_radix(text, 16).ok()),
+            fields
+                .get(2)
+                .and_then(|text| u16::from_str_radix(text, 16).ok()),
+        ) {
+            (Some(kind), Some(addr), Some(length)) => (kind, addr, length.max(1)),
+            _ => return "E01".to_string(),
+        };
+        let watch: Option<Watch> = match kind {
+            0 => None,
+            2 => Some(Watch::Write),
+            3 => Some(Watch::Read),
+            4 => Some(Watch::Access),
+            _ => return String::new(),
+        };
+
+        let key: (u8, u16, u16) = (kind, addr, length);
+        if insert {
+            // GDB may insert the same point twice, which must not stop twice as often.
+            self.points.entry(key).or_insert_with(|| match watch {
+                Some(watch) => {
+                    debugger.add_watchpoint(addr..=addr.saturating_add(length - 1), watch, None)
+                }
+                None => debugger.add_breakpoint(addr, None),
+            });
+        } else if let Some(id) = self.points.remove(&key) {
+            if watch.is_some() {
+                debugger.remove_watchpoint(id);
+            } else {
+                debugger.remove_breakpoint(id);
+            }
+        }
+        "OK".to_string()
+    }
+
+    /// Executes instructions until the debugger stops or GDB interrupts the execution, and
+    /// returns the stop reply.
+    fn resume<B: Bus>(&mut self, cpu: &mut P6502<B>, debugger: &mut Debugger) -> Result<String> {
+        loop {
+            let result: Result<Stop> = debugger.resume_for(cpu, INSTRUCTIONS_PER_POLL);
+            if !matches!(result, Ok(Stop::Done)) {
+                return Ok(self.stop_reply(debugger, result));
+            }
+            if self.interrupted()? {
+                return Ok(format!("S{:02x}", SIGINT));
+            }
+        }
+    }
+
+    /// Returns whether GDB sent an interrupt. Anything else received is kept for later.
+    fn interrupted(&mut self) -> Result<bool> {
+        while let Some(byte) = self.stream.try_read_byte()? {
+            if byte == INTERRUPT {
+                return Ok(true);
+            }
+            self.pending.push_back(byte);
+        }
+        Ok(false)
+    }
+
+    /// Returns the stop reply for the outcome of a step or a continue.
+    fn stop_reply(&self, debugger: &Debugger, result: Result<Stop>) -> String {
+        match result {
+            Ok(Stop::Watchpoint { id, access, .. }) => {
+                let watch: Option<Watch> = debugger
+                    .watchpoints()
+                    .iter()
+                    .find(|watchpoint| watchpoint.id == id)
+                    .map(|watchpoint| watchpoint.watch);
+                let name: &str = match watch {
+                    Some(Watch::Read) => "rwatch",
+                    Some(Watch::Access) => "awatch",
+                    _ => "watch",
+                };
+                format!("T{:02x}{}:{:04x};", SIGTRAP, name, access.addr)
+            }
+            // The program can still be inspected, so GDB is not told it exited.
+            Ok(Stop::Halted(StepStatus::Stopped)) => format!("S{:02x}", SIGILL),
+            Ok(Stop::HistoryStart) => format!("T{:02x}replaylog:begin;", SIGTRAP),
+            Ok(_) => format!("S{:02x}", SIGTRAP),
+            Err(Error::BusFault { .. }) => format!("S{:02x}", SIGSEGV),
+            Err(_) => format!("S{:02x}", SIGILL),
+        }
+    }
+
+    /// Reads the next packet, acknowledging it. Returns `None` once the stream is closed.
+    fn read_packet(&mut self) -> Result<Option<Vec<u8>>> {
+        loop {
+            // Acknowledgements and interrupts between packets are skipped.
+            loop {
+                match self.read_byte()? {
+                    Some(b'$') => break,
+                    Some(_) => {}
+                    None => return Ok(None),
+                }
+            }
+
+            let mut data: Vec<u8> = Vec::new();
+            let mut checksum: u8 = 0;
+            loop {
+                match self.read_byte()? {
+                    Some(b'#') => break,
+                    Some(byte) => {
+                        checksum = checksum.wrapping_add(byte);
+                        data.push(byte);
+                    }
+                    None => return Ok(None),
+                }
+            }
+            let mut sent: [u8; 2] = [0; 2];
+            for digit in sent.iter_mut() {
+                *digit = match self.read_byte()? {
+                    Some(byte) => byte,
+                    None => return Ok(None),
+                };
+            }
+
+            let valid: bool = core::str::from_utf8(&sent)
+                .ok()
+                .and_then(|text| u8::from_str_radix(text, 16).ok())
+                == Some(checksum);
+            if !self.no_ack {
+                self.stream.write_all(if valid { b"+" } else { b"-" })?;
+            }
+            if valid {
+                return Ok(Some(unescape(&data)));
+            }
+        }
+    }
+
+    fn read_byte(&mut self) -> Result<Option<u8>> {
+        if let Some(byte) = self.pending.pop_front() {
+            return Ok(Some(byte));
+        }
+
+        let mut byte: [u8; 1] = [0];
+        match self.stream.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+
+    /// Sends a packet.
+    fn send(&mut self, data: &[u8]) -> Result<()> {
+        let checksum: u8 = data.iter().fold(0, |sum, byte| sum.wrapping_add(*byte));
+        self.stream.write_all(b"$")?;
+        self.stream.write_all(data)?;
+        write!(self.stream, "#{:02x}", checksum)?;
+        self.stream.flush()?;
+        Ok(())
+    }
+}
+
+/// Waits for GDB to connect to `address` (such as `127.0.0.1:6502`) and serves it until it
+/// detaches.
+pub fn listen<B: Bus>(cpu: &mut P6502<B>, debugger: &mut Debugger, address: &str) -> Result<()> {
+    let listener: TcpListener = TcpListener::bind(address)?;
+    let (stream, _): (TcpStream, _) = listener.accept()?;
+    stream.set_nodelay(true)?;
+    GdbStub::new(stream).serve(cpu, debugger)
+}
+
+/// Returns a register in hexadecimal, in little-endian order.
+fn register_hex<B: Bus>(cpu: &P6502<B>, register: Register) -> String {
+    let value: u16 = register.get(cpu);
+    if register == Register::Pc {
+        format!("{:02x}{:02x}", value & 0xff, value >> 8)
+    } else {
+        format!("{:02x}", value)
+    }
+}
+
+/// Parses an `ADDR,LENGTH` pair in hexadecimal.
+fn parse_range(text: &str) -> Option<(u16, u16)> {
+    let (addr, length): (&str, &str) = text.split_once(',')?;
+    Some((
+        u16::from_str_radix(addr, 16).ok()?,
+        u16::from_str_radix(length, 16).ok()?,
+    ))
+}
+
+/// Decodes pairs of hexadecimal digits.
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(text.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+/// Joins bytes in little-endian order.
+fn little_endian(bytes: &[u8]) -> u16 {
+    bytes
+        .iter()
+        .take(2)
+        .rev()
+        .fold(0, |value, byte| (value << 8) | *byte as u16)
+}
+
+/// Removes the escapes (`}` followed by the byte XOR $20) of the data of a packet.
+fn unescape(data: &[u8]) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(data.len());
+    let mut escaped: bool = false;
+    for byte in data.iter() {
+        if escaped {
+            bytes.push(byte ^ 0x20);
+            escaped = false;
+        } else if *byte == b'}' {
+            escaped = true;
+        } else {
+            bytes.push(*byte);
+        }
+    }
+    bytes
+}
@@ -0,0 +1,57 @@
+//! A cycle-accurate emulator of the MOS 6502 CPU and its relatives.
+//!
+//! The `P6502` CPU is attached to anything implementing `Bus`, such as the flat 64 KB `Memory`
+//! or a `MemoryMap` of RAM, ROM and devices, and can be driven a whole program, an
+//! instruction or a single cycle at a time.
+//!
+//! ```
+//! use kw6502::{BreakPolicy, Memory, P6502};
+//!
+//! // LDA #$2A; STA $10; BRK
+//! let mut cpu: P6502 = P6502::default();
+//! cpu.break_policy = BreakPolicy::Halt;
+//! cpu.set_memory(Memory::from_program_vec(vec![0xa9, 0x2a, 0x85, 0x10, 0x00]));
+//! cpu.reset();
+//! cpu.execute().unwrap();
+//!
+//! assert_eq!(cpu.memory.data[0x0010], 0x2a);
+//! ```
+//!
+//! The core builds without the standard library (it only needs `alloc`) when the default `std`
+//! feature is disabled. The feature adds the interactive prompt, the file loaders and the
+//! command line tool.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod assembler;
+pub mod bus;
+pub mod debugger;
+pub mod disassembler;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod gdb;
+pub mod loader;
+pub mod opcode;
+pub mod p6502;
+#[cfg(feature = "std")]
+pub mod prompt;
+pub mod savestate;
+pub mod tracer;
+
+pub use crate::assembler::{assemble, Assembly};
+pub use crate::bus::{Bus, BusAccess, Memory, MemoryMap, Region, RomWrites};
+pub use crate::debugger::{Breakpoint, Condition, Debugger, Register, Stop, Watch, Watchpoint};
+pub use crate::disassembler::{disassemble, Symbols};
+pub use crate::error::{Error, Result};
+pub use crate::loader::parse_hex_dump;
+#[cfg(feature = "std")]
+pub use crate::loader::read_hex_dump;
+pub use crate::opcode::{AddressingMode, Mnemonic, Opcode};
+pub use crate::p6502::{
+    BreakPolicy, IllegalPolicy, Interrupt, IoPort, P6502Flags, StepResult, StepStatus, Variant,
+    P6502,
+};
+pub use crate::savestate::SaveState;
+pub use crate::tracer::trace_line;
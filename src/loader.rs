@@ -0,0 +1,44 @@
+//! Loaders for the program files the emulator can run. Reading them from files requires the
+//! `std` feature.
+
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::error::{Error, Result};
+
+/// Parses a program written in a hex-dump format: bytes in hexadecimal separated by
+/// whitespace. When `addresses` is set, the first element of each row is an address and gets
+/// ignored.
+pub fn parse_hex_dump(source: &str, addresses: bool) -> Result<Vec<u8>> {
+    let mut program: Vec<u8> = vec![];
+
+    for (line_index, line) in source.lines().enumerate() {
+        let skip: usize = if addresses { 1 } else { 0 };
+
+        for token in line.split_whitespace().skip(skip) {
+            match u8::from_str_radix(token, 16) {
+                Ok(value) => program.push(value),
+                Err(_) => {
+                    // The tokens are slices of the line, so their offset gives the column.
+                    let offset: usize = token.as_ptr() as usize - line.as_ptr() as usize;
+                    return Err(Error::Load {
+                        line: line_index + 1,
+                        column: line[..offset].chars().count() + 1,
+                        token: token.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(program)
+}
+
+/// Reads and parses a file written in a hex-dump format. See `parse_hex_dump`. Requires the
+/// `std` feature.
+#[cfg(feature = "std")]
+pub fn read_hex_dump(path: &str, addresses: bool) -> Result<Vec<u8>> {
+    let source: String = std::fs::read_to_string(path)?;
+    parse_hex_dump(&source, addresses)
+}
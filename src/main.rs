@@ -1,20 +1,38 @@
+use std::fs::File;
+use std::io::BufWriter;
+
 use clap::{App, Arg};
-use std::io::{prelude::*, BufReader};
-use std::{fs, vec};
+use kw6502::debugger::Debugger;
+use kw6502::disassembler::Symbols;
+use kw6502::{assembler, error, gdb, loader, p6502};
 
-mod p6502;
-mod tests;
+/// The options given on the command line.
+struct Arguments {
+    /// Whether the first element of each row of the hex file is an address, to be ignored
+    addresses: bool,
+    /// The path to the hex file containing the program, or to an assembly file ending in `.s`
+    input_file: String,
+    /// The variant of the 6502 to emulate
+    variant: p6502::Variant,
+    /// A file whose symbols the disassembler uses
+    symbols_file: Option<String>,
+    /// Whether to open the prompt before running the program
+    debug: bool,
+    /// The file where every instruction executed is logged (`-` being the standard output)
+    trace_file: Option<String>,
+    /// The address where GDB is awaited instead of running the program
+    gdb_address: Option<String>,
+}
 
-/// Parses the command line arguments. the `addresses` flag is used to ignore the first element
-/// of each row and the `INPUT` arguments must contain the path to hex file containing the program.
-fn parse_args() -> (bool, String) {
-    let matches = App::new("km6502")
+/// Parses the command line arguments.
+fn parse_args() -> Arguments {
+    let matches = App::new("kw6502")
         .version("0.1")
         .author("Gonzalo Sastre")
-        .about("A (not yet) cycle-accurate MOS 6502 CPU emulator.")
+        .about("A cycle-accurate MOS 6502 CPU emulator.")
         .arg(
             Arg::with_name("INPUT")
-                .help("Sets the hex input stream")
+                .help("Sets the hex input stream, or the assembly source if it ends in .s")
                 .required(true)
                 .index(1),
         )
@@ -26,74 +44,171 @@ fn parse_args() -> (bool, String) {
                 .help("Pass this flag if the input file's first row consists of addresses.")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("cpu")
+                .short("c")
+                .long("cpu")
+                .required(false)
+                .help("Sets the CPU to emulate.")
+                .takes_value(true)
+                .possible_values(&["6502", "65c02", "2a03", "6510"])
+                .default_value("6502"),
+        )
+        .arg(
+            Arg::with_name("symbols")
+                .short("s")
+                .long("symbols")
+                .required(false)
+                .help("Sets an assembly file whose labels and constants name the disassembled addresses.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("debug")
+                .short("d")
+                .long("debug")
+                .required(false)
+                .help("Opens the interactive prompt before running the program, to debug it.")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("trace")
+                .short("t")
+                .long("trace")
+                .required(false)
+                .help("Logs every instruction executed to a file, or to the standard output if it is '-', in the nestest format.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("gdb")
+                .short("g")
+                .long("gdb")
+                .required(false)
+                .help("Waits for GDB to connect to the address, such as 127.0.0.1:6502, and lets it debug the program.")
+                .takes_value(true),
+        )
         .get_matches();
 
-    let flags_addresses: bool = matches.is_present("addresses");
-    let input_file: String = matches.value_of("INPUT").unwrap().to_string();
-
-    (flags_addresses, input_file)
+    Arguments {
+        addresses: matches.is_present("addresses"),
+        input_file: matches.value_of("INPUT").unwrap().to_string(),
+        variant: match matches.value_of("cpu") {
+            Some("65c02") => p6502::Variant::Cmos65C02,
+            Some("2a03") => p6502::Variant::Ricoh2A03,
+            Some("6510") => p6502::Variant::Mos6510,
+            _ => p6502::Variant::Nmos6502,
+        },
+        symbols_file: matches.value_of("symbols").map(|path| path.to_string()),
+        debug: matches.is_present("debug"),
+        trace_file: matches.value_of("trace").map(|path| path.to_string()),
+        gdb_address: matches.value_of("gdb").map(|address| address.to_string()),
+    }
 }
 
-/// Reads a program written in a hex-dump format.
-fn read_program(ignore_first_column: bool, path: String, buffer: &mut Vec<u8>) {
-    if let Ok(file) = fs::File::open(&path) {
-        let buf_reader = BufReader::new(file);
-
-        for line in buf_reader.lines() {
-            match line {
-                Ok(line_contents) => {
-                    let contents: Vec<&str> = line_contents.split_whitespace().collect();
-                    if ignore_first_column {
-                        for (index, value) in contents.iter().enumerate() {
-                            if index != 0 {
-                                buffer.push(u8::from_str_radix(value, 16).unwrap());
-                            }
-                        }
-                    } else {
-                        for value in contents.iter() {
-                            match u8::from_str_radix(value, 16) {
-                                Ok(integer_value) => buffer.push(integer_value),
-                                Err(_) => {
-                                    println!("Unable to parse an opcode. Did you provide a file which uses addresses without passing the 'addresses' flag?");
-                                    std::process::exit(1);
-                                }
-                            }
-                        }
-                    }
-                }
-
-                Err(error) => {
-                    println!("Unable to read line contents. The error is: {}", error);
-                    std::process::exit(1);
-                }
+/// Reads a program written in a hex-dump format and returns a memory containing it.
+fn read_program(flags_addresses: bool, input_file: &str) -> p6502::Memory {
+    match loader::read_hex_dump(input_file, flags_addresses) {
+        Ok(program) => p6502::Memory::from_program_vec(program),
+        Err(error::Error::Io(error)) => {
+            println!(
+                "Unable to open the file '{}'. Does it exist? Do you have permission to read it? The error is: {}",
+                input_file, error
+            );
+            std::process::exit(1);
+        }
+        Err(error) => {
+            println!("Unable to load the program: {}.", error);
+            if !flags_addresses {
+                println!("Did you provide a file which uses addresses without passing the 'addresses' flag?");
             }
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Assembles a program written in assembly language and returns a memory containing it, as well
+/// as its symbols.
+fn assemble_program(input_file: &str, variant: p6502::Variant) -> (p6502::Memory, Symbols) {
+    match assembler::assemble_file(input_file, variant) {
+        Ok(assembly) => (assembly.to_memory(), Symbols::from_names(&assembly.symbols)),
+        Err(error::Error::Io(error)) => {
+            println!(
+                "Unable to open the file '{}'. Does it exist? Do you have permission to read it? The error is: {}",
+                input_file, error
+            );
+            std::process::exit(1);
+        }
+        Err(error) => {
+            println!("Unable to assemble the program: {}.", error);
+            std::process::exit(1);
         }
-    } else {
-        println!(
-            "Unable to open the file '{}'. Does it exist? Do you have permission to read it?",
-            &path
-        );
-        std::process::exit(1);
     }
 }
 
 fn main() {
-    let (flags_addresses, input_file): (bool, String) = parse_args(); // Reads the command line arguments.
-    let mut cpu: p6502::P6502 = p6502::P6502::default(); // Creates an new processor instance.
-    cpu.reset(); // Sets the correct initial values.
+    let arguments: Arguments = parse_args(); // Reads the command line arguments.
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(arguments.variant); // Creates an new processor instance.
+    cpu.break_policy = p6502::BreakPolicy::Halt; // Stops the execution once the program is finished.
 
     /* Reads the program and loads its contents into memory. */
-    let mut program: Vec<u8> = vec![];
-    read_program(flags_addresses, input_file, &mut program);
-    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    let (memory, mut symbols): (p6502::Memory, Symbols) = if arguments.input_file.ends_with(".s") {
+        assemble_program(&arguments.input_file, arguments.variant)
+    } else {
+        (
+            read_program(arguments.addresses, &arguments.input_file),
+            Symbols::new(),
+        )
+    };
+    if let Some(symbols_file) = arguments.symbols_file {
+        // Only the symbols matter, so the file gets assembled but not loaded.
+        let (_, file_symbols): (p6502::Memory, Symbols) =
+            assemble_program(&symbols_file, arguments.variant);
+        symbols = file_symbols;
+    }
     cpu.set_memory(memory);
+    cpu.reset(); // Sets the correct initial values and jumps to the program.
 
-    cpu.execute(); // Executes the program.
+    /* Logs the execution if requested. */
+    match arguments.trace_file.as_deref() {
+        Some("-") => cpu.trace_to(std::io::stdout()),
+        Some(path) => match File::create(path) {
+            Ok(file) => cpu.trace_to(BufWriter::new(file)),
+            Err(error) => {
+                println!("Unable to create the trace file '{}': {}", path, error);
+                std::process::exit(1);
+            }
+        },
+        None => {}
+    }
+
+    if let Some(address) = arguments.gdb_address {
+        /* Lets GDB debug the program, then runs the interactive prompt. */
+        println!("Waiting for GDB to connect to {}...", address);
+        if let Err(error) = gdb::listen(&mut cpu, &mut Debugger::new(), &address) {
+            println!("The GDB session failed: {}.", error);
+        }
+        cpu.interactive(&symbols);
+        return;
+    }
+
+    if arguments.debug {
+        /* Runs the interactive prompt before the program, which is debugged from there. */
+        println!(
+            "The program starts at PC=${:04x}. Use 'step', 'break' and 'continue' to run it.",
+            cpu.pc
+        );
+        cpu.interactive(&symbols);
+        return;
+    }
+
+    // Executes the program.
+    if let Err(error) = cpu.execute() {
+        println!("The execution stopped: {}.", error);
+    }
 
     /* Runs the interactive prompt once the program is finished. */
     println!(
         "The program finished at PC=${:04x}. The interactive prompt will now appear.",
         cpu.pc
     );
-    cpu.interactive();
+    cpu.interactive(&symbols);
 }
@@ -0,0 +1,845 @@
+//! The opcode tables of the supported variants of the 6502. Each of their 256 entries
+//! describes an opcode: its instruction, its addressing mode and the number of cycles it takes.
+//! They are the single source of truth for the decoder of the CPU and for any tool which needs
+//! to know about the instruction set.
+
+use core::fmt;
+
+use crate::p6502::Variant;
+
+use self::AddressingMode::*;
+use self::Mnemonic::*;
+
+/// The instructions of the 6502 family, named after their mnemonics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mnemonic {
+    /* Legal instructions */
+    Adc,
+    And,
+    Asl,
+    Bcc,
+    Bcs,
+    Beq,
+    Bit,
+    Bmi,
+    Bne,
+    Bpl,
+    Brk,
+    Bvc,
+    Bvs,
+    Clc,
+    Cld,
+    Cli,
+    Clv,
+    Cmp,
+    Cpx,
+    Cpy,
+    Dec,
+    Dex,
+    Dey,
+    Eor,
+    Inc,
+    Inx,
+    Iny,
+    Jmp,
+    Jsr,
+    Lda,
+    Ldx,
+    Ldy,
+    Lsr,
+    Nop,
+    Ora,
+    Pha,
+    Php,
+    Pla,
+    Plp,
+    Rol,
+    Ror,
+    Rti,
+    Rts,
+    Sbc,
+    Sec,
+    Sed,
+    Sei,
+    Sta,
+    Stx,
+    Sty,
+    Tax,
+    Tay,
+    Tsx,
+    Txa,
+    Txs,
+    Tya,
+
+    /* Undocumented instructions of the NMOS 6502 */
+    Jam,
+    Lax,
+    Sax,
+    Las,
+    Slo,
+    Rla,
+    Sre,
+    Rra,
+    Dcp,
+    Isc,
+    Anc,
+    Alr,
+    Arr,
+    Sbx,
+    Usbc,
+    Ane,
+    Lxa,
+    Sha,
+    Shx,
+    Shy,
+    Tas,
+
+    /* Instructions added by the 65C02. The bit instructions hold the number of the bit. */
+    Bra,
+    Phx,
+    Phy,
+    Plx,
+    Ply,
+    Stz,
+    Trb,
+    Tsb,
+    Wai,
+    Stp,
+    Rmb(u8),
+    Smb(u8),
+    Bbr(u8),
+    Bbs(u8),
+}
+
+impl Mnemonic {
+    /// Returns the mnemonic in uppercase. The bit instructions of the 65C02 lack their bit
+    /// number, which `Display` appends.
+    pub fn name(self) -> &'static str {
+        match self {
+            Adc => "ADC",
+            And => "AND",
+            Asl => "ASL",
+            Bcc => "BCC",
+            Bcs => "BCS",
+            Beq => "BEQ",
+            Bit => "BIT",
+            Bmi => "BMI",
+            Bne => "BNE",
+            Bpl => "BPL",
+            Brk => "BRK",
+            Bvc => "BVC",
+            Bvs => "BVS",
+            Clc => "CLC",
+            Cld => "CLD",
+            Cli => "CLI",
+            Clv => "CLV",
+            Cmp => "CMP",
+            Cpx => "CPX",
+            Cpy => "CPY",
+            Dec => "DEC",
+            Dex => "DEX",
+            Dey => "DEY",
+            Eor => "EOR",
+            Inc => "INC",
+            Inx => "INX",
+            Iny => "INY",
+            Jmp => "JMP",
+            Jsr => "JSR",
+            Lda => "LDA",
+            Ldx => "LDX",
+            Ldy => "LDY",
+            Lsr => "LSR",
+            Nop => "NOP",
+            Ora => "ORA",
+            Pha => "PHA",
+            Php => "PHP",
+            Pla => "PLA",
+            Plp => "PLP",
+            Rol => "ROL",
+            Ror => "ROR",
+            Rti => "RTI",
+            Rts => "RTS",
+            Sbc => "SBC",
+            Sec => "SEC",
+            Sed => "SED",
+            Sei => "SEI",
+            Sta => "STA",
+            Stx => "STX",
+            Sty => "STY",
+            Tax => "TAX",
+            Tay => "TAY",
+            Tsx => "TSX",
+            Txa => "TXA",
+            Txs => "TXS",
+            Tya => "TYA",
+            Jam => "JAM",
+            Lax => "LAX",
+            Sax => "SAX",
+            Las => "LAS",
+            Slo => "SLO",
+            Rla => "RLA",
+            Sre => "SRE",
+            Rra => "RRA",
+            Dcp => "DCP",
+            Isc => "ISC",
+            Anc => "ANC",
+            Alr => "ALR",
+            Arr => "ARR",
+            Sbx => "SBX",
+            Usbc => "USBC",
+            Ane => "ANE",
+            Lxa => "LXA",
+            Sha => "SHA",
+            Shx => "SHX",
+            Shy => "SHY",
+            Tas => "TAS",
+            Bra => "BRA",
+            Phx => "PHX",
+            Phy => "PHY",
+            Plx => "PLX",
+            Ply => "PLY",
+            Stz => "STZ",
+            Trb => "TRB",
+            Tsb => "TSB",
+            Wai => "WAI",
+            Stp => "STP",
+            Rmb(_) => "RMB",
+            Smb(_) => "SMB",
+            Bbr(_) => "BBR",
+            Bbs(_) => "BBS",
+        }
+    }
+}
+
+impl fmt::Display for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rmb(bit) | Smb(bit) | Bbr(bit) | Bbs(bit) => write!(f, "{}{}", self.name(), bit),
+            _ => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+/// The addressing modes of the 6502 family. The abbreviation used in the names of the opcode
+/// constants is shown between brackets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressingMode {
+    /// No operand (---)
+    Implied,
+    /// The accumulator is the operand (ACC)
+    Accumulator,
+    /// #$nn (IMM)
+    Immediate,
+    /// $nn (ZP0)
+    ZeroPage,
+    /// $nn,X (ZPX)
+    ZeroPageX,
+    /// $nn,Y (ZPY)
+    ZeroPageY,
+    /// A signed offset from the next instruction, used by branches (REL)
+    Relative,
+    /// $nnnn (ABS)
+    Absolute,
+    /// $nnnn,X (ABX)
+    AbsoluteX,
+    /// $nnnn,Y (ABY)
+    AbsoluteY,
+    /// ($nnnn), only used by JMP (IND)
+    Indirect,
+    /// ($nn,X) (IDX)
+    IndexedIndirect,
+    /// ($nn),Y (IDY)
+    IndirectIndexed,
+    /// ($nn), 65C02 only (IZP)
+    ZeroPageIndirect,
+    /// ($nnnn,X), 65C02 only and used by JMP (IAX)
+    AbsoluteIndexedIndirect,
+    /// $nn,offset, used by the BBR and BBS instructions of the 65C02 (ZPR)
+    ZeroPageRelative,
+}
+
+impl AddressingMode {
+    /// Returns the number of bytes of the operand, which follow the opcode.
+    pub fn operand_size(self) -> u16 {
+        match self {
+            Implied | Accumulator => 0,
+            Immediate | ZeroPage | ZeroPageX | ZeroPageY | Relative => 1,
+            IndexedIndirect | IndirectIndexed | ZeroPageIndirect => 1,
+            Absolute | AbsoluteX | AbsoluteY | Indirect => 2,
+            AbsoluteIndexedIndirect | ZeroPageRelative => 2,
+        }
+    }
+}
+
+/// The description of an opcode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Opcode {
+    /// The instruction executed
+    pub mnemonic: Mnemonic,
+    /// How the operand is found
+    pub mode: AddressingMode,
+    /// The number of cycles the instruction takes, without the page-cross penalty. Branches
+    /// take one more cycle when the branch is taken, and the 65C02 spends one more in ADC and
+    /// SBC when the decimal flag is set.
+    pub cycles: u8,
+    /// Whether one more cycle is needed when indexing or branching crosses a page. Instructions
+    /// writing to memory always spend that cycle, so it is already part of `cycles`.
+    pub page_penalty: bool,
+}
+
+impl Opcode {
+    const fn new(mnemonic: Mnemonic, mode: AddressingMode, cycles: u8, page_penalty: bool) -> Self {
+        Self {
+            mnemonic,
+            mode,
+            cycles,
+            page_penalty,
+        }
+    }
+
+    /// Returns the size in bytes of the whole instruction, opcode included.
+    pub fn size(&self) -> u16 {
+        1 + self.mode.operand_size()
+    }
+}
+
+/// Returns the description of an opcode as executed by a variant of the 6502.
+pub fn decode(variant: Variant, opcode: u8) -> Opcode {
+    match variant {
+        Variant::Cmos65C02 => CMOS_OPCODES[opcode as usize],
+        _ => NMOS_OPCODES[opcode as usize],
+    }
+}
+
+/// Returns whether an opcode belongs to the official instruction set of a variant, as opposed
+/// to the undocumented instructions and NOPs.
+pub fn is_documented(variant: Variant, opcode: u8) -> bool {
+    match decode(variant, opcode).mnemonic {
+        Nop => opcode == 0xEA,
+        Jam | Lax | Sax | Las | Slo | Rla | Sre | Rra | Dcp | Isc | Anc | Alr | Arr | Sbx
+        | Usbc | Ane | Lxa | Sha | Shx | Shy | Tas => false,
+        _ => true,
+    }
+}
+
+/// The opcodes of the NMOS 6502, shared by the 2A03 and the 6510. Every opcode not in the
+/// official instruction set is an undocumented instruction.
+pub static NMOS_OPCODES: [Opcode; 256] = [
+    /* $00 */ Opcode::new(Brk, Implied, 7, false),
+    /* $01 */ Opcode::new(Ora, IndexedIndirect, 6, false),
+    /* $02 */ Opcode::new(Jam, Implied, 2, false),
+    /* $03 */ Opcode::new(Slo, IndexedIndirect, 8, false),
+    /* $04 */ Opcode::new(Nop, ZeroPage, 3, false),
+    /* $05 */ Opcode::new(Ora, ZeroPage, 3, false),
+    /* $06 */ Opcode::new(Asl, ZeroPage, 5, false),
+    /* $07 */ Opcode::new(Slo, ZeroPage, 5, false),
+    /* $08 */ Opcode::new(Php, Implied, 3, false),
+    /* $09 */ Opcode::new(Ora, Immediate, 2, false),
+    /* $0A */ Opcode::new(Asl, Accumulator, 2, false),
+    /* $0B */ Opcode::new(Anc, Immediate, 2, false),
+    /* $0C */ Opcode::new(Nop, Absolute, 4, false),
+    /* $0D */ Opcode::new(Ora, Absolute, 4, false),
+    /* $0E */ Opcode::new(Asl, Absolute, 6, false),
+    /* $0F */ Opcode::new(Slo, Absolute, 6, false),
+    /* $10 */ Opcode::new(Bpl, Relative, 2, true),
+    /* $11 */ Opcode::new(Ora, IndirectIndexed, 5, true),
+    /* $12 */ Opcode::new(Jam, Implied, 2, false),
+    /* $13 */ Opcode::new(Slo, IndirectIndexed, 8, false),
+    /* $14 */ Opcode::new(Nop, ZeroPageX, 4, false),
+    /* $15 */ Opcode::new(Ora, ZeroPageX, 4, false),
+    /* $16 */ Opcode::new(Asl, ZeroPageX, 6, false),
+    /* $17 */ Opcode::new(Slo, ZeroPageX, 6, false),
+    /* $18 */ Opcode::new(Clc, Implied, 2, false),
+    /* $19 */ Opcode::new(Ora, AbsoluteY, 4, true),
+    /* $1A */ Opcode::new(Nop, Implied, 2, false),
+    /* $1B */ Opcode::new(Slo, AbsoluteY, 7, false),
+    /* $1C */ Opcode::new(Nop, AbsoluteX, 4, true),
+    /* $1D */ Opcode::new(Ora, AbsoluteX, 4, true),
+    /* $1E */ Opcode::new(Asl, AbsoluteX, 7, false),
+    /* $1F */ Opcode::new(Slo, AbsoluteX, 7, false),
+    /* $20 */ Opcode::new(Jsr, Absolute, 6, false),
+    /* $21 */ Opcode::new(And, IndexedIndirect, 6, false),
+    /* $22 */ Opcode::new(Jam, Implied, 2, false),
+    /* $23 */ Opcode::new(Rla, IndexedIndirect, 8, false),
+    /* $24 */ Opcode::new(Bit, ZeroPage, 3, false),
+    /* $25 */ Opcode::new(And, ZeroPage, 3, false),
+    /* $26 */ Opcode::new(Rol, ZeroPage, 5, false),
+    /* $27 */ Opcode::new(Rla, ZeroPage, 5, false),
+    /* $28 */ Opcode::new(Plp, Implied, 4, false),
+    /* $29 */ Opcode::new(And, Immediate, 2, false),
+    /* $2A */ Opcode::new(Rol, Accumulator, 2, false),
+    /* $2B */ Opcode::new(Anc, Immediate, 2, false),
+    /* $2C */ Opcode::new(Bit, Absolute, 4, false),
+    /* $2D */ Opcode::new(And, Absolute, 4, false),
+    /* $2E */ Opcode::new(Rol, Absolute, 6, false),
+    /* $2F */ Opcode::new(Rla, Absolute, 6, false),
+    /* $30 */ Opcode::new(Bmi, Relative, 2, true),
+    /* $31 */ Opcode::new(And, IndirectIndexed, 5, true),
+    /* $32 */ Opcode::new(Jam, Implied, 2, false),
+    /* $33 */ Opcode::new(Rla, IndirectIndexed, 8, false),
+    /* $34 */ Opcode::new(Nop, ZeroPageX, 4, false),
+    /* $35 */ Opcode::new(And, ZeroPageX, 4, false),
+    /* $36 */ Opcode::new(Rol, ZeroPageX, 6, false),
+    /* $37 */ Opcode::new(Rla, ZeroPageX, 6, false),
+    /* $38 */ Opcode::new(Sec, Implied, 2, false),
+    /* $39 */ Opcode::new(And, AbsoluteY, 4, true),
+    /* $3A */ Opcode::new(Nop, Implied, 2, false),
+    /* $3B */ Opcode::new(Rla, AbsoluteY, 7, false),
+    /* $3C */ Opcode::new(Nop, AbsoluteX, 4, true),
+    /* $3D */ Opcode::new(And, AbsoluteX, 4, true),
+    /* $3E */ Opcode::new(Rol, AbsoluteX, 7, false),
+    /* $3F */ Opcode::new(Rla, AbsoluteX, 7, false),
+    /* $40 */ Opcode::new(Rti, Implied, 6, false),
+    /* $41 */ Opcode::new(Eor, IndexedIndirect, 6, false),
+    /* $42 */ Opcode::new(Jam, Implied, 2, false),
+    /* $43 */ Opcode::new(Sre, IndexedIndirect, 8, false),
+    /* $44 */ Opcode::new(Nop, ZeroPage, 3, false),
+    /* $45 */ Opcode::new(Eor, ZeroPage, 3, false),
+    /* $46 */ Opcode::new(Lsr, ZeroPage, 5, false),
+    /* $47 */ Opcode::new(Sre, ZeroPage, 5, false),
+    /* $48 */ Opcode::new(Pha, Implied, 3, false),
+    /* $49 */ Opcode::new(Eor, Immediate, 2, false),
+    /* $4A */ Opcode::new(Lsr, Accumulator, 2, false),
+    /* $4B */ Opcode::new(Alr, Immediate, 2, false),
+    /* $4C */ Opcode::new(Jmp, Absolute, 3, false),
+    /* $4D */ Opcode::new(Eor, Absolute, 4, false),
+    /* $4E */ Opcode::new(Lsr, Absolute, 6, false),
+    /* $4F */ Opcode::new(Sre, Absolute, 6, false),
+    /* $50 */ Opcode::new(Bvc, Relative, 2, true),
+    /* $51 */ Opcode::new(Eor, IndirectIndexed, 5, true),
+    /* $52 */ Opcode::new(Jam, Implied, 2, false),
+    /* $53 */ Opcode::new(Sre, IndirectIndexed, 8, false),
+    /* $54 */ Opcode::new(Nop, ZeroPageX, 4, false),
+    /* $55 */ Opcode::new(Eor, ZeroPageX, 4, false),
+    /* $56 */ Opcode::new(Lsr, ZeroPageX, 6, false),
+    /* $57 */ Opcode::new(Sre, ZeroPageX, 6, false),
+    /* $58 */ Opcode::new(Cli, Implied, 2, false),
+    /* $59 */ Opcode::new(Eor, AbsoluteY, 4, true),
+    /* $5A */ Opcode::new(Nop, Implied, 2, false),
+    /* $5B */ Opcode::new(Sre, AbsoluteY, 7, false),
+    /* $5C */ Opcode::new(Nop, AbsoluteX, 4, true),
+    /* $5D */ Opcode::new(Eor, AbsoluteX, 4, true),
+    /* $5E */ Opcode::new(Lsr, AbsoluteX, 7, false),
+    /* $5F */ Opcode::new(Sre, AbsoluteX, 7, false),
+    /* $60 */ Opcode::new(Rts, Implied, 6, false),
+    /* $61 */ Opcode::new(Adc, IndexedIndirect, 6, false),
+    /* $62 */ Opcode::new(Jam, Implied, 2, false),
+    /* $63 */ Opcode::new(Rra, IndexedIndirect, 8, false),
+    /* $64 */ Opcode::new(Nop, ZeroPage, 3, false),
+    /* $65 */ Opcode::new(Adc, ZeroPage, 3, false),
+    /* $66 */ Opcode::new(Ror, ZeroPage, 5, false),
+    /* $67 */ Opcode::new(Rra, ZeroPage, 5, false),
+    /* $68 */ Opcode::new(Pla, Implied, 4, false),
+    /* $69 */ Opcode::new(Adc, Immediate, 2, false),
+    /* $6A */ Opcode::new(Ror, Accumulator, 2, false),
+    /* $6B */ Opcode::new(Arr, Immediate, 2, false),
+    /* $6C */ Opcode::new(Jmp, Indirect, 5, false),
+    /* $6D */ Opcode::new(Adc, Absolute, 4, false),
+    /* $6E */ Opcode::new(Ror, Absolute, 6, false),
+    /* $6F */ Opcode::new(Rra, Absolute, 6, false),
+    /* $70 */ Opcode::new(Bvs, Relative, 2, true),
+    /* $71 */ Opcode::new(Adc, IndirectIndexed, 5, true),
+    /* $72 */ Opcode::new(Jam, Implied, 2, false),
+    /* $73 */ Opcode::new(Rra, IndirectIndexed, 8, false),
+    /* $74 */ Opcode::new(Nop, ZeroPageX, 4, false),
+    /* $75 */ Opcode::new(Adc, ZeroPageX, 4, false),
+    /* $76 */ Opcode::new(Ror, ZeroPageX, 6, false),
+    /* $77 */ Opcode::new(Rra, ZeroPageX, 6, false),
+    /* $78 */ Opcode::new(Sei, Implied, 2, false),
+    /* $79 */ Opcode::new(Adc, AbsoluteY, 4, true),
+    /* $7A */ Opcode::new(Nop, Implied, 2, false),
+    /* $7B */ Opcode::new(Rra, AbsoluteY, 7, false),
+    /* $7C */ Opcode::new(Nop, AbsoluteX, 4, true),
+    /* $7D */ Opcode::new(Adc, AbsoluteX, 4, true),
+    /* $7E */ Opcode::new(Ror, AbsoluteX, 7, false),
+    /* $7F */ Opcode::new(Rra, AbsoluteX, 7, false),
+    /* $80 */ Opcode::new(Nop, Immediate, 2, false),
+    /* $81 */ Opcode::new(Sta, IndexedIndirect, 6, false),
+    /* $82 */ Opcode::new(Nop, Immediate, 2, false),
+    /* $83 */ Opcode::new(Sax, IndexedIndirect, 6, false),
+    /* $84 */ Opcode::new(Sty, ZeroPage, 3, false),
+    /* $85 */ Opcode::new(Sta, ZeroPage, 3, false),
+    /* $86 */ Opcode::new(Stx, ZeroPage, 3, false),
+    /* $87 */ Opcode::new(Sax, ZeroPage, 3, false),
+    /* $88 */ Opcode::new(Dey, Implied, 2, false),
+    /* $89 */ Opcode::new(Nop, Immediate, 2, false),
+    /* $8A */ Opcode::new(Txa, Implied, 2, false),
+    /* $8B */ Opcode::new(Ane, Immediate, 2, false),
+    /* $8C */ Opcode::new(Sty, Absolute, 4, false),
+    /* $8D */ Opcode::new(Sta, Absolute, 4, false),
+    /* $8E */ Opcode::new(Stx, Absolute, 4, false),
+    /* $8F */ Opcode::new(Sax, Absolute, 4, false),
+    /* $90 */ Opcode::new(Bcc, Relative, 2, true),
+    /* $91 */ Opcode::new(Sta, IndirectIndexed, 6, false),
+    /* $92 */ Opcode::new(Jam, Implied, 2, false),
+    /* $93 */ Opcode::new(Sha, IndirectIndexed, 6, false),
+    /* $94 */ Opcode::new(Sty, ZeroPageX, 4, false),
+    /* $95 */ Opcode::new(Sta, ZeroPageX, 4, false),
+    /* $96 */ Opcode::new(Stx, ZeroPageY, 4, false),
+    /* $97 */ Opcode::new(Sax, ZeroPageY, 4, false),
+    /* $98 */ Opcode::new(Tya, Implied, 2, false),
+    /* $99 */ Opcode::new(Sta, AbsoluteY, 5, false),
+    /* $9A */ Opcode::new(Txs, Implied, 2, false),
+    /* $9B */ Opcode::new(Tas, AbsoluteY, 5, false),
+    /* $9C */ Opcode::new(Shy, AbsoluteX, 5, false),
+    /* $9D */ Opcode::new(Sta, AbsoluteX, 5, false),
+    /* $9E */ Opcode::new(Shx, AbsoluteY, 5, false),
+    /* $9F */ Opcode::new(Sha, AbsoluteY, 5, false),
+    /* $A0 */ Opcode::new(Ldy, Immediate, 2, false),
+    /* $A1 */ Opcode::new(Lda, IndexedIndirect, 6, false),
+    /* $A2 */ Opcode::new(Ldx, Immediate, 2, false),
+    /* $A3 */ Opcode::new(Lax, IndexedIndirect, 6, false),
+    /* $A4 */ Opcode::new(Ldy, ZeroPage, 3, false),
+    /* $A5 */ Opcode::new(Lda, ZeroPage, 3, false),
+    /* $A6 */ Opcode::new(Ldx, ZeroPage, 3, false),
+    /* $A7 */ Opcode::new(Lax, ZeroPage, 3, false),
+    /* $A8 */ Opcode::new(Tay, Implied, 2, false),
+    /* $A9 */ Opcode::new(Lda, Immediate, 2, false),
+    /* $AA */ Opcode::new(Tax, Implied, 2, false),
+    /* $AB */ Opcode::new(Lxa, Immediate, 2, false),
+    /* $AC */ Opcode::new(Ldy, Absolute, 4, false),
+    /* $AD */ Opcode::new(Lda, Absolute, 4, false),
+    /* $AE */ Opcode::new(Ldx, Absolute, 4, false),
+    /* $AF */ Opcode::new(Lax, Absolute, 4, false),
+    /* $B0 */ Opcode::new(Bcs, Relative, 2, true),
+    /* $B1 */ Opcode::new(Lda, IndirectIndexed, 5, true),
+    /* $B2 */ Opcode::new(Jam, Implied, 2, false),
+    /* $B3 */ Opcode::new(Lax, IndirectIndexed, 5, true),
+    /* $B4 */ Opcode::new(Ldy, ZeroPageX, 4, false),
+    /* $B5 */ Opcode::new(Lda, ZeroPageX, 4, false),
+    /* $B6 */ Opcode::new(Ldx, ZeroPageY, 4, false),
+    /* $B7 */ Opcode::new(Lax, ZeroPageY, 4, false),
+    /* $B8 */ Opcode::new(Clv, Implied, 2, false),
+    /* $B9 */ Opcode::new(Lda, AbsoluteY, 4, true),
+    /* $BA */ Opcode::new(Tsx, Implied, 2, false),
+    /* $BB */ Opcode::new(Las, AbsoluteY, 4, true),
+    /* $BC */ Opcode::new(Ldy, AbsoluteX, 4, true),
+    /* $BD */ Opcode::new(Lda, AbsoluteX, 4, true),
+    /* $BE */ Opcode::new(Ldx, AbsoluteY, 4, true),
+    /* $BF */ Opcode::new(Lax, AbsoluteY, 4, true),
+    /* $C0 */ Opcode::new(Cpy, Immediate, 2, false),
+    /* $C1 */ Opcode::new(Cmp, IndexedIndirect, 6, false),
+    /* $C2 */ Opcode::new(Nop, Immediate, 2, false),
+    /* $C3 */ Opcode::new(Dcp, IndexedIndirect, 8, false),
+    /* $C4 */ Opcode::new(Cpy, ZeroPage, 3, false),
+    /* $C5 */ Opcode::new(Cmp, ZeroPage, 3, false),
+    /* $C6 */ Opcode::new(Dec, ZeroPage, 5, false),
+    /* $C7 */ Opcode::new(Dcp, ZeroPage, 5, false),
+    /* $C8 */ Opcode::new(Iny, Implied, 2, false),
+    /* $C9 */ Opcode::new(Cmp, Immediate, 2, false),
+    /* $CA */ Opcode::new(Dex, Implied, 2, false),
+    /* $CB */ Opcode::new(Sbx, Immediate, 2, false),
+    /* $CC */ Opcode::new(Cpy, Absolute, 4, false),
+    /* $CD */ Opcode::new(Cmp, Absolute, 4, false),
+    /* $CE */ Opcode::new(Dec, Absolute, 6, false),
+    /* $CF */ Opcode::new(Dcp, Absolute, 6, false),
+    /* $D0 */ Opcode::new(Bne, Relative, 2, true),
+    /* $D1 */ Opcode::new(Cmp, IndirectIndexed, 5, true),
+    /* $D2 */ Opcode::new(Jam, Implied, 2, false),
+    /* $D3 */ Opcode::new(Dcp, IndirectIndexed, 8, false),
+    /* $D4 */ Opcode::new(Nop, ZeroPageX, 4, false),
+    /* $D5 */ Opcode::new(Cmp, ZeroPageX, 4, false),
+    /* $D6 */ Opcode::new(Dec, ZeroPageX, 6, false),
+    /* $D7 */ Opcode::new(Dcp, ZeroPageX, 6, false),
+    /* $D8 */ Opcode::new(Cld, Implied, 2, false),
+    /* $D9 */ Opcode::new(Cmp, AbsoluteY, 4, true),
+    /* $DA */ Opcode::new(Nop, Implied, 2, false),
+    /* $DB */ Opcode::new(Dcp, AbsoluteY, 7, false),
+    /* $DC */ Opcode::new(Nop, AbsoluteX, 4, true),
+    /* $DD */ Opcode::new(Cmp, AbsoluteX, 4, true),
+    /* $DE */ Opcode::new(Dec, AbsoluteX, 7, false),
+    /* $DF */ Opcode::new(Dcp, AbsoluteX, 7, false),
+    /* $E0 */ Opcode::new(Cpx, Immediate, 2, false),
+    /* $E1 */ Opcode::new(Sbc, IndexedIndirect, 6, false),
+    /* $E2 */ Opcode::new(Nop, Immediate, 2, false),
+    /* $E3 */ Opcode::new(Isc, IndexedIndirect, 8, false),
+    /* $E4 */ Opcode::new(Cpx, ZeroPage, 3, false),
+    /* $E5 */ Opcode::new(Sbc, ZeroPage, 3, false),
+    /* $E6 */ Opcode::new(Inc, ZeroPage, 5, false),
+    /* $E7 */ Opcode::new(Isc, ZeroPage, 5, false),
+    /* $E8 */ Opcode::new(Inx, Implied, 2, false),
+    /* $E9 */ Opcode::new(Sbc, Immediate, 2, false),
+    /* $EA */ Opcode::new(Nop, Implied, 2, false),
+    /* $EB */ Opcode::new(Usbc, Immediate, 2, false),
+    /* $EC */ Opcode::new(Cpx, Absolute, 4, false),
+    /* $ED */ Opcode::new(Sbc, Absolute, 4, false),
+    /* $EE */ Opcode::new(Inc, Absolute, 6, false),
+    /* $EF */ Opcode::new(Isc, Absolute, 6, false),
+    /* $F0 */ Opcode::new(Beq, Relative, 2, true),
+    /* $F1 */ Opcode::new(Sbc, IndirectIndexed, 5, true),
+    /* $F2 */ Opcode::new(Jam, Implied, 2, false),
+    /* $F3 */ Opcode::new(Isc, IndirectIndexed, 8, false),
+    /* $F4 */ Opcode::new(Nop, ZeroPageX, 4, false),
+    /* $F5 */ Opcode::new(Sbc, ZeroPageX, 4, false),
+    /* $F6 */ Opcode::new(Inc, ZeroPageX, 6, false),
+    /* $F7 */ Opcode::new(Isc, ZeroPageX, 6, false),
+    /* $F8 */ Opcode::new(Sed, Implied, 2, false),
+    /* $F9 */ Opcode::new(Sbc, AbsoluteY, 4, true),
+    /* $FA */ Opcode::new(Nop, Implied, 2, false),
+    /* $FB */ Opcode::new(Isc, AbsoluteY, 7, false),
+    /* $FC */ Opcode::new(Nop, AbsoluteX, 4, true),
+    /* $FD */ Opcode::new(Sbc, AbsoluteX, 4, true),
+    /* $FE */ Opcode::new(Inc, AbsoluteX, 7, false),
+    /* $FF */ Opcode::new(Isc, AbsoluteX, 7, false),
+];
+
+/// The opcodes of the WDC 65C02. The undocumented opcodes of the NMOS 6502 are either new
+/// instructions or NOPs.
+pub static CMOS_OPCODES: [Opcode; 256] = [
+    /* $00 */ Opcode::new(Brk, Implied, 7, false),
+    /* $01 */ Opcode::new(Ora, IndexedIndirect, 6, false),
+    /* $02 */ Opcode::new(Nop, Immediate, 2, false),
+    /* $03 */ Opcode::new(Nop, Implied, 1, false),
+    /* $04 */ Opcode::new(Tsb, ZeroPage, 5, false),
+    /* $05 */ Opcode::new(Ora, ZeroPage, 3, false),
+    /* $06 */ Opcode::new(Asl, ZeroPage, 5, false),
+    /* $07 */ Opcode::new(Rmb(0), ZeroPage, 5, false),
+    /* $08 */ Opcode::new(Php, Implied, 3, false),
+    /* $09 */ Opcode::new(Ora, Immediate, 2, false),
+    /* $0A */ Opcode::new(Asl, Accumulator, 2, false),
+    /* $0B */ Opcode::new(Nop, Implied, 1, false),
+    /* $0C */ Opcode::new(Tsb, Absolute, 6, false),
+    /* $0D */ Opcode::new(Ora, Absolute, 4, false),
+    /* $0E */ Opcode::new(Asl, Absolute, 6, false),
+    /* $0F */ Opcode::new(Bbr(0), ZeroPageRelative, 5, true),
+    /* $10 */ Opcode::new(Bpl, Relative, 2, true),
+    /* $11 */ Opcode::new(Ora, IndirectIndexed, 5, true),
+    /* $12 */ Opcode::new(Ora, ZeroPageIndirect, 5, false),
+    /* $13 */ Opcode::new(Nop, Implied, 1, false),
+    /* $14 */ Opcode::new(Trb, ZeroPage, 5, false),
+    /* $15 */ Opcode::new(Ora, ZeroPageX, 4, false),
+    /* $16 */ Opcode::new(Asl, ZeroPageX, 6, false),
+    /* $17 */ Opcode::new(Rmb(1), ZeroPage, 5, false),
+    /* $18 */ Opcode::new(Clc, Implied, 2, false),
+    /* $19 */ Opcode::new(Ora, AbsoluteY, 4, true),
+    /* $1A */ Opcode::new(Inc, Accumulator, 2, false),
+    /* $1B */ Opcode::new(Nop, Implied, 1, false),
+    /* $1C */ Opcode::new(Trb, Absolute, 6, false),
+    /* $1D */ Opcode::new(Ora, AbsoluteX, 4, true),
+    /* $1E */ Opcode::new(Asl, AbsoluteX, 6, true),
+    /* $1F */ Opcode::new(Bbr(1), ZeroPageRelative, 5, true),
+    /* $20 */ Opcode::new(Jsr, Absolute, 6, false),
+    /* $21 */ Opcode::new(And, IndexedIndirect, 6, false),
+    /* $22 */ Opcode::new(Nop, Immediate, 2, false),
+    /* $23 */ Opcode::new(Nop, Implied, 1, false),
+    /* $24 */ Opcode::new(Bit, ZeroPage, 3, false),
+    /* $25 */ Opcode::new(And, ZeroPage, 3, false),
+    /* $26 */ Opcode::new(Rol, ZeroPage, 5, false),
+    /* $27 */ Opcode::new(Rmb(2), ZeroPage, 5, false),
+    /* $28 */ Opcode::new(Plp, Implied, 4, false),
+    /* $29 */ Opcode::new(And, Immediate, 2, false),
+    /* $2A */ Opcode::new(Rol, Accumulator, 2, false),
+    /* $2B */ Opcode::new(Nop, Implied, 1, false),
+    /* $2C */ Opcode::new(Bit, Absolute, 4, false),
+    /* $2D */ Opcode::new(And, Absolute, 4, false),
+    /* $2E */ Opcode::new(Rol, Absolute, 6, false),
+    /* $2F */ Opcode::new(Bbr(2), ZeroPageRelative, 5, true),
+    /* $30 */ Opcode::new(Bmi, Relative, 2, true),
+    /* $31 */ Opcode::new(And, IndirectIndexed, 5, true),
+    /* $32 */ Opcode::new(And, ZeroPageIndirect, 5, false),
+    /* $33 */ Opcode::new(Nop, Implied, 1, false),
+    /* $34 */ Opcode::new(Bit, ZeroPageX, 4, false),
+    /* $35 */ Opcode::new(And, ZeroPageX, 4, false),
+    /* $36 */ Opcode::new(Rol, ZeroPageX, 6, false),
+    /* $37 */ Opcode::new(Rmb(3), ZeroPage, 5, false),
+    /* $38 */ Opcode::new(Sec, Implied, 2, false),
+    /* $39 */ Opcode::new(And, AbsoluteY, 4, true),
+    /* $3A */ Opcode::new(Dec, Accumulator, 2, false),
+    /* $3B */ Opcode::new(Nop, Implied, 1, false),
+    /* $3C */ Opcode::new(Bit, AbsoluteX, 4, true),
+    /* $3D */ Opcode::new(And, AbsoluteX, 4, true),
+    /* $3E */ Opcode::new(Rol, AbsoluteX, 6, true),
+    /* $3F */ Opcode::new(Bbr(3), ZeroPageRelative, 5, true),
+    /* $40 */ Opcode::new(Rti, Implied, 6, false),
+    /* $41 */ Opcode::new(Eor, IndexedIndirect, 6, false),
+    /* $42 */ Opcode::new(Nop, Immediate, 2, false),
+    /* $43 */ Opcode::new(Nop, Implied, 1, false),
+    /* $44 */ Opcode::new(Nop, ZeroPage, 3, false),
+    /* $45 */ Opcode::new(Eor, ZeroPage, 3, false),
+    /* $46 */ Opcode::new(Lsr, ZeroPage, 5, false),
+    /* $47 */ Opcode::new(Rmb(4), ZeroPage, 5, false),
+    /* $48 */ Opcode::new(Pha, Implied, 3, false),
+    /* $49 */ Opcode::new(Eor, Immediate, 2, false),
+    /* $4A */ Opcode::new(Lsr, Accumulator, 2, false),
+    /* $4B */ Opcode::new(Nop, Implied, 1, false),
+    /* $4C */ Opcode::new(Jmp, Absolute, 3, false),
+    /* $4D */ Opcode::new(Eor, Absolute, 4, false),
+    /* $4E */ Opcode::new(Lsr, Absolute, 6, false),
+    /* $4F */ Opcode::new(Bbr(4), ZeroPageRelative, 5, true),
+    /* $50 */ Opcode::new(Bvc, Relative, 2, true),
+    /* $51 */ Opcode::new(Eor, IndirectIndexed, 5, true),
+    /* $52 */ Opcode::new(Eor, ZeroPageIndirect, 5, false),
+    /* $53 */ Opcode::new(Nop, Implied, 1, false),
+    /* $54 */ Opcode::new(Nop, ZeroPageX, 4, false),
+    /* $55 */ Opcode::new(Eor, ZeroPageX, 4, false),
+    /* $56 */ Opcode::new(Lsr, ZeroPageX, 6, false),
+    /* $57 */ Opcode::new(Rmb(5), ZeroPage, 5, false),
+    /* $58 */ Opcode::new(Cli, Implied, 2, false),
+    /* $59 */ Opcode::new(Eor, AbsoluteY, 4, true),
+    /* $5A */ Opcode::new(Phy, Implied, 3, false),
+    /* $5B */ Opcode::new(Nop, Implied, 1, false),
+    /* $5C */ Opcode::new(Nop, Absolute, 8, false),
+    /* $5D */ Opcode::new(Eor, AbsoluteX, 4, true),
+    /* $5E */ Opcode::new(Lsr, AbsoluteX, 6, true),
+    /* $5F */ Opcode::new(Bbr(5), ZeroPageRelative, 5, true),
+    /* $60 */ Opcode::new(Rts, Implied, 6, false),
+    /* $61 */ Opcode::new(Adc, IndexedIndirect, 6, false),
+    /* $62 */ Opcode::new(Nop, Immediate, 2, false),
+    /* $63 */ Opcode::new(Nop, Implied, 1, false),
+    /* $64 */ Opcode::new(Stz, ZeroPage, 3, false),
+    /* $65 */ Opcode::new(Adc, ZeroPage, 3, false),
+    /* $66 */ Opcode::new(Ror, ZeroPage, 5, false),
+    /* $67 */ Opcode::new(Rmb(6), ZeroPage, 5, false),
+    /* $68 */ Opcode::new(Pla, Implied, 4, false),
+    /* $69 */ Opcode::new(Adc, Immediate, 2, false),
+    /* $6A */ Opcode::new(Ror, Accumulator, 2, false),
+    /* $6B */ Opcode::new(Nop, Implied, 1, false),
+    /* $6C */ Opcode::new(Jmp, Indirect, 6, false),
+    /* $6D */ Opcode::new(Adc, Absolute, 4, false),
+    /* $6E */ Opcode::new(Ror, Absolute, 6, false),
+    /* $6F */ Opcode::new(Bbr(6), ZeroPageRelative, 5, true),
+    /* $70 */ Opcode::new(Bvs, Relative, 2, true),
+    /* $71 */ Opcode::new(Adc, IndirectIndexed, 5, true),
+    /* $72 */ Opcode::new(Adc, ZeroPageIndirect, 5, false),
+    /* $73 */ Opcode::new(Nop, Implied, 1, false),
+    /* $74 */ Opcode::new(Stz, ZeroPageX, 4, false),
+    /* $75 */ Opcode::new(Adc, ZeroPageX, 4, false),
+    /* $76 */ Opcode::new(Ror, ZeroPageX, 6, false),
+    /* $77 */ Opcode::new(Rmb(7), ZeroPage, 5, false),
+    /* $78 */ Opcode::new(Sei, Implied, 2, false),
+    /* $79 */ Opcode::new(Adc, AbsoluteY, 4, true),
+    /* $7A */ Opcode::new(Ply, Implied, 4, false),
+    /* $7B */ Opcode::new(Nop, Implied, 1, false),
+    /* $7C */ Opcode::new(Jmp, AbsoluteIndexedIndirect, 6, false),
+    /* $7D */ Opcode::new(Adc, AbsoluteX, 4, true),
+    /* $7E */ Opcode::new(Ror, AbsoluteX, 6, true),
+    /* $7F */ Opcode::new(Bbr(7), ZeroPageRelative, 5, true),
+    /* $80 */ Opcode::new(Bra, Relative, 3, true),
+    /* $81 */ Opcode::new(Sta, IndexedIndirect, 6, false),
+    /* $82 */ Opcode::new(Nop, Immediate, 2, false),
+    /* $83 */ Opcode::new(Nop, Implied, 1, false),
+    /* $84 */ Opcode::new(Sty, ZeroPage, 3, false),
+    /* $85 */ Opcode::new(Sta, ZeroPage, 3, false),
+    /* $86 */ Opcode::new(Stx, ZeroPage, 3, false),
+    /* $87 */ Opcode::new(Smb(0), ZeroPage, 5, false),
+    /* $88 */ Opcode::new(Dey, Implied, 2, false),
+    /* $89 */ Opcode::new(Bit, Immediate, 2, false),
+    /* $8A */ Opcode::new(Txa, Implied, 2, false),
+    /* $8B */ Opcode::new(Nop, Implied, 1, false),
+    /* $8C */ Opcode::new(Sty, Absolute, 4, false),
+    /* $8D */ Opcode::new(Sta, Absolute, 4, false),
+    /* $8E */ Opcode::new(Stx, Absolute, 4, false),
+    /* $8F */ Opcode::new(Bbs(0), ZeroPageRelative, 5, true),
+    /* $90 */ Opcode::new(Bcc, Relative, 2, true),
+    /* $91 */ Opcode::new(Sta, IndirectIndexed, 6, false),
+    /* $92 */ Opcode::new(Sta, ZeroPageIndirect, 5, false),
+    /* $93 */ Opcode::new(Nop, Implied, 1, false),
+    /* $94 */ Opcode::new(Sty, ZeroPageX, 4, false),
+    /* $95 */ Opcode::new(Sta, ZeroPageX, 4, false),
+    /* $96 */ Opcode::new(Stx, ZeroPageY, 4, false),
+    /* $97 */ Opcode::new(Smb(1), ZeroPage, 5, false),
+    /* $98 */ Opcode::new(Tya, Implied, 2, false),
+    /* $99 */ Opcode::new(Sta, AbsoluteY, 5, false),
+    /* $9A */ Opcode::new(Txs, Implied, 2, false),
+    /* $9B */ Opcode::new(Nop, Implied, 1, false),
+    /* $9C */ Opcode::new(Stz, Absolute, 4, false),
+    /* $9D */ Opcode::new(Sta, AbsoluteX, 5, false),
+    /* $9E */ Opcode::new(Stz, AbsoluteX, 5, false),
+    /* $9F */ Opcode::new(Bbs(1), ZeroPageRelative, 5, true),
+    /* $A0 */ Opcode::new(Ldy, Immediate, 2, false),
+    /* $A1 */ Opcode::new(Lda, IndexedIndirect, 6, false),
+    /* $A2 */ Opcode::new(Ldx, Immediate, 2, false),
+    /* $A3 */ Opcode::new(Nop, Implied, 1, false),
+    /* $A4 */ Opcode::new(Ldy, ZeroPage, 3, false),
+    /* $A5 */ Opcode::new(Lda, ZeroPage, 3, false),
+    /* $A6 */ Opcode::new(Ldx, ZeroPage, 3, false),
+    /* $A7 */ Opcode::new(Smb(2), ZeroPage, 5, false),
+    /* $A8 */ Opcode::new(Tay, Implied, 2, false),
+    /* $A9 */ Opcode::new(Lda, Immediate, 2, false),
+    /* $AA */ Opcode::new(Tax, Implied, 2, false),
+    /* $AB */ Opcode::new(Nop, Implied, 1, false),
+    /* $AC */ Opcode::new(Ldy, Absolute, 4, false),
+    /* $AD */ Opcode::new(Lda, Absolute, 4, false),
+    /* $AE */ Opcode::new(Ldx, Absolute, 4, false),
+    /* $AF */ Opcode::new(Bbs(2), ZeroPageRelative, 5, true),
+    /* $B0 */ Opcode::new(Bcs, Relative, 2, true),
+    /* $B1 */ Opcode::new(Lda, IndirectIndexed, 5, true),
+    /* $B2 */ Opcode::new(Lda, ZeroPageIndirect, 5, false),
+    /* $B3 */ Opcode::new(Nop, Implied, 1, false),
+    /* $B4 */ Opcode::new(Ldy, ZeroPageX, 4, false),
+    /* $B5 */ Opcode::new(Lda, ZeroPageX, 4, false),
+    /* $B6 */ Opcode::new(Ldx, ZeroPageY, 4, false),
+    /* $B7 */ Opcode::new(Smb(3), ZeroPage, 5, false),
+    /* $B8 */ Opcode::new(Clv, Implied, 2, false),
+    /* $B9 */ Opcode::new(Lda, AbsoluteY, 4, true),
+    /* $BA */ Opcode::new(Tsx, Implied, 2, false),
+    /* $BB */ Opcode::new(Nop, Implied, 1, false),
+    /* $BC */ Opcode::new(Ldy, AbsoluteX, 4, true),
+    /* $BD */ Opcode::new(Lda, AbsoluteX, 4, true),
+    /* $BE */ Opcode::new(Ldx, AbsoluteY, 4, true),
+    /* $BF */ Opcode::new(Bbs(3), ZeroPageRelative, 5, true),
+    /* $C0 */ Opcode::new(Cpy, Immediate, 2, false),
+    /* $C1 */ Opcode::new(Cmp, IndexedIndirect, 6, false),
+    /* $C2 */ Opcode::new(Nop, Immediate, 2, false),
+    /* $C3 */ Opcode::new(Nop, Implied, 1, false),
+    /* $C4 */ Opcode::new(Cpy, ZeroPage, 3, false),
+    /* $C5 */ Opcode::new(Cmp, ZeroPage, 3, false),
+    /* $C6 */ Opcode::new(Dec, ZeroPage, 5, false),
+    /* $C7 */ Opcode::new(Smb(4), ZeroPage, 5, false),
+    /* $C8 */ Opcode::new(Iny, Implied, 2, false),
+    /* $C9 */ Opcode::new(Cmp, Immediate, 2, false),
+    /* $CA */ Opcode::new(Dex, Implied, 2, false),
+    /* $CB */ Opcode::new(Wai, Implied, 3, false),
+    /* $CC */ Opcode::new(Cpy, Absolute, 4, false),
+    /* $CD */ Opcode::new(Cmp, Absolute, 4, false),
+    /* $CE */ Opcode::new(Dec, Absolute, 6, false),
+    /* $CF */ Opcode::new(Bbs(4), ZeroPageRelative, 5, true),
+    /* $D0 */ Opcode::new(Bne, Relative, 2, true),
+    /* $D1 */ Opcode::new(Cmp, IndirectIndexed, 5, true),
+    /* $D2 */ Opcode::new(Cmp, ZeroPageIndirect, 5, false),
+    /* $D3 */ Opcode::new(Nop, Implied, 1, false),
+    /* $D4 */ Opcode::new(Nop, ZeroPageX, 4, false),
+    /* $D5 */ Opcode::new(Cmp, ZeroPageX, 4, false),
+    /* $D6 */ Opcode::new(Dec, ZeroPageX, 6, false),
+    /* $D7 */ Opcode::new(Smb(5), ZeroPage, 5, false),
+    /* $D8 */ Opcode::new(Cld, Implied, 2, false),
+    /* $D9 */ Opcode::new(Cmp, AbsoluteY, 4, true),
+    /* $DA */ Opcode::new(Phx, Implied, 3, false),
+    /* $DB */ Opcode::new(Stp, Implied, 3, false),
+    /* $DC */ Opcode::new(Nop, Absolute, 4, false),
+    /* $DD */ Opcode::new(Cmp, AbsoluteX, 4, true),
+    /* $DE */ Opcode::new(Dec, AbsoluteX, 7, false),
+    /* $DF */ Opcode::new(Bbs(5), ZeroPageRelative, 5, true),
+    /* $E0 */ Opcode::new(Cpx, Immediate, 2, false),
+    /* $E1 */ Opcode::new(Sbc, IndexedIndirect, 6, false),
+    /* $E2 */ Opcode::new(Nop, Immediate, 2, false),
+    /* $E3 */ Opcode::new(Nop, Implied, 1, false),
+    /* $E4 */ Opcode::new(Cpx, ZeroPage, 3, false),
+    /* $E5 */ Opcode::new(Sbc, ZeroPage, 3, false),
+    /* $E6 */ Opcode::new(Inc, ZeroPage, 5, false),
+    /* $E7 */ Opcode::new(Smb(6), ZeroPage, 5, false),
+    /* $E8 */ Opcode::new(Inx, Implied, 2, false),
+    /* $E9 */ Opcode::new(Sbc, Immediate, 2, false),
+    /* $EA */ Opcode::new(Nop, Implied, 2, false),
+    /* $EB */ Opcode::new(Nop, Implied, 1, false),
+    /* $EC */ Opcode::new(Cpx, Absolute, 4, false),
+    /* $ED */ Opcode::new(Sbc, Absolute, 4, false),
+    /* $EE */ Opcode::new(Inc, Absolute, 6, false),
+    /* $EF */ Opcode::new(Bbs(6), ZeroPageRelative, 5, true),
+    /* $F0 */ Opcode::new(Beq, Relative, 2, true),
+    /* $F1 */ Opcode::new(Sbc, IndirectIndexed, 5, true),
+    /* $F2 */ Opcode::new(Sbc, ZeroPageIndirect, 5, false),
+    /* $F3 */ Opcode::new(Nop, Implied, 1, false),
+    /* $F4 */ Opcode::new(Nop, ZeroPageX, 4, false),
+    /* $F5 */ Opcode::new(Sbc, ZeroPageX, 4, false),
+    /* $F6 */ Opcode::new(Inc, ZeroPageX, 6, false),
+    /* $F7 */ Opcode::new(Smb(7), ZeroPage, 5, false),
+    /* $F8 */ Opcode::new(Sed, Implied, 2, false),
+    /* $F9 */ Opcode::new(Sbc, AbsoluteY, 4, true),
+    /* $FA */ Opcode::new(Plx, Implied, 4, false),
+    /* $FB */ Opcode::new(Nop, Implied, 1, false),
+    /* $FC */ Opcode::new(Nop, Absolute, 4, false),
+    /* $FD */ Opcode::new(Sbc, AbsoluteX, 4, true),
+    /* $FE */ Opcode::new(Inc, AbsoluteX, 7, false),
+    /* $FF */ Opcode::new(Bbs(7), ZeroPageRelative, 5, true),
+];
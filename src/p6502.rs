@@ -1,7 +1,13 @@
-use copperline::Copperline;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
 
-#[derive(Default)]
-pub struct P6502 {
+pub use crate::bus::{Bus, BusAccess, Memory};
+use crate::error::{Error, Result};
+use crate::opcode::{self, AddressingMode, Mnemonic, Opcode};
+use crate::tracer;
+
+pub struct P6502<B: Bus = Memory> {
     /// Program counter
     pub pc: u16,
     // Stack pointer
@@ -14,13 +20,266 @@ pub struct P6502 {
     pub y: u8,
     /// Procesor flags
     pub flags: P6502Flags,
-    /// The memory associated to the CPU
-    pub memory: Memory,
-    /// The number of cycles remaining for the CPU
-    cycles: usize,
+    /// The memory bus associated to the CPU
+    pub memory: B,
+    /// The number of clock cycles elapsed since the CPU was created or powered on
+    pub(crate) cycles: usize,
+    /// What to do when a BRK instruction is executed
+    pub break_policy: BreakPolicy,
+    /// What to do when an undocumented opcode is reached
+    pub illegal_policy: IllegalPolicy,
+    /// The "magic constant" of the unstable ANE and LXA instructions, which depends on the
+    /// chip and even its temperature. Defaults to $EE.
+    pub magic_constant: u8,
+    /// Whether a JAM or STP instruction has locked up the CPU. Only a reset unlocks it.
+    pub(crate) jammed: bool,
+    /// The level of the IRQ line as driven from outside the bus (active when `true`)
+    pub(crate) irq_line: bool,
+    /// Whether a falling edge on the NMI line is waiting to be serviced
+    pub(crate) nmi_pending: bool,
+    /// The level of the bus' NMI line during the last poll, used to detect its edges
+    pub(crate) nmi_previous: bool,
+    /// Whether a WAI instruction is waiting for an interrupt (65C02 only)
+    pub(crate) waiting: bool,
+    /// The member of the 6502 family being emulated
+    variant: Variant,
+    /// The I/O port mapped at $00-$01 (6510 only)
+    pub io_port: IoPort,
+    /// The instruction being executed one cycle at a time by `tick`, if any
+    cycle_step: Option<CycleStep>,
+    /// What the last instruction executed has done
+    trace: Trace,
+    /// The error which stopped the last instruction, if any
+    pub(crate) error: Option<Error>,
+    /// Receives the trace line of every instruction, if any
+    pub(crate) tracer: Option<tracer::Tracer>,
 }
-/// Represents the 6502 seven status flags
+
+impl<B: Bus + Default> Default for P6502<B> {
+    fn default() -> Self {
+        Self::new(B::default(), Variant::default())
+    }
+}
+
+impl<B: Bus + Default> P6502<B> {
+    /// Creates a CPU of the specified variant attached to an empty bus.
+    pub fn with_variant(variant: Variant) -> Self {
+        Self::new(B::default(), variant)
+    }
+}
+
+impl<B: Bus> P6502<B> {
+    /// Creates a CPU of the specified variant attached to a bus. The variant cannot be changed
+    /// afterwards.
+    pub fn new(memory: B, variant: Variant) -> Self {
+        Self {
+            pc: 0,
+            sp: 0,
+            a: 0,
+            x: 0,
+            y: 0,
+            flags: P6502Flags::default(),
+            memory,
+            cycles: 0,
+            break_policy: BreakPolicy::default(),
+            illegal_policy: IllegalPolicy::default(),
+            magic_constant: 0xEE,
+            jammed: false,
+            irq_line: false,
+            nmi_pending: false,
+            nmi_previous: false,
+            waiting: false,
+            variant,
+            io_port: IoPort::default(),
+            cycle_step: None,
+            trace: Trace::default(),
+            error: None,
+            tracer: None,
+        }
+    }
+
+    /// Returns the member of the 6502 family being emulated.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+}
+
+/// The members of the 6502 family the CPU can behave as.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Variant {
+    /// The original NMOS 6502, with its undocumented opcodes and the JMP ($xxFF) bug.
+    #[default]
+    Nmos6502,
+    /// The WDC 65C02, including the Rockwell bit instructions (RMB, SMB, BBR and BBS). It
+    /// fixes the JMP ($xxFF) bug, clears the decimal flag on interrupts and reset, sets the N and
+    /// Z flags properly in decimal mode and turns the undocumented opcodes into NOPs.
+    Cmos65C02,
+    /// The Ricoh 2A03 of the NES: an NMOS 6502 whose decimal mode has been removed. The
+    /// decimal flag can still be set, but ADC and SBC ignore it.
+    Ricoh2A03,
+    /// The 6510 of the Commodore 64: an NMOS 6502 with an I/O port at $00-$01.
+    Mos6510,
+}
+
+impl Variant {
+    /// Returns whether ADC and SBC honour the decimal flag.
+    fn has_decimal_mode(self) -> bool {
+        self != Variant::Ricoh2A03
+    }
+}
+
+/// The 6510's built-in I/O port. Its data direction register is mapped at $00 and its data
+/// register at $01, hiding whatever is on the bus at those addresses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IoPort {
+    /// Data direction register: the lines whose bit is set are outputs
+    pub direction: u8,
+    /// Data register: the value driven on the output lines
+    pub data: u8,
+    /// The level driven from outside on the input lines. Defaults to $FF (pulled up).
+    pub input: u8,
+}
+
+impl Default for IoPort {
+    fn default() -> Self {
+        Self {
+            direction: 0,
+            data: 0,
+            input: 0xFF,
+        }
+    }
+}
+
+/// The registers and internal state of the CPU which an instruction may modify.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Snapshot {
+    pc: u16,
+    sp: u8,
+    a: u8,
+    x: u8,
+    y: u8,
+    flags: P6502Flags,
+    io_port: IoPort,
+    jammed: bool,
+    waiting: bool,
+    nmi_pending: bool,
+    nmi_previous: bool,
+}
+
+/// An instruction being executed one cycle at a time. On every cycle the instruction is run
+/// again from the snapshot: the bus accesses already performed are replayed from `accesses`,
+/// one new access reaches the bus and the ones after it are skipped.
+struct CycleStep {
+    /// The state of the CPU before the instruction
+    snapshot: Snapshot,
+    /// The data read or written by the bus accesses already performed, in order
+    accesses: Vec<u8>,
+    /// The number of accesses done by the current run of the instruction
+    position: usize,
+    /// Whether the current run has already performed its new bus access
+    new_access_done: bool,
+    /// Whether the current run has skipped any access, so the instruction is not finished
+    incomplete: bool,
+    /// The levels of the NMI and IRQ lines on the first cycle of the instruction
+    nmi_level: bool,
+    irq_level: bool,
+}
+
+/// What is recorded while an instruction is executed. The bus accesses are only recorded
+/// for `step`, and the values overwritten in memory for `step_undoable`.
 #[derive(Default)]
+struct Trace {
+    address: u16,
+    opcode: u8,
+    interrupt: Option<Interrupt>,
+    accesses: Option<Vec<BusAccess>>,
+    overwritten: Option<Vec<(u16, u8)>>,
+}
+
+/// The interrupts a CPU can service.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interrupt {
+    /// Non-maskable interrupt
+    Nmi,
+    /// Interrupt request
+    Irq,
+}
+
+/// The state of the CPU after an instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepStatus {
+    /// The CPU can keep running.
+    Running,
+    /// A BRK instruction stopped the execution, as per `BreakPolicy::Halt`.
+    Halted,
+    /// A STP instruction has stopped the CPU until the next reset.
+    Stopped,
+    /// A WAI instruction is waiting for an interrupt.
+    Waiting,
+}
+
+/// What a call to `P6502::step` did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StepResult {
+    /// The address of the instruction
+    pub address: u16,
+    /// The opcode of the instruction
+    pub opcode: u8,
+    /// The description of the opcode
+    pub instruction: Opcode,
+    /// The interrupt serviced before the instruction, if any
+    pub interrupt: Option<Interrupt>,
+    /// The number of cycles consumed, including those of the interrupt sequence
+    pub cycles: usize,
+    /// The bus accesses performed, in order
+    pub accesses: Vec<BusAccess>,
+    /// The state of the CPU afterwards
+    pub status: StepStatus,
+}
+
+/// What a bus access does while an instruction is executed one cycle at a time.
+enum BusCycle {
+    /// The access reaches the bus.
+    Real,
+    /// The access was performed on a previous cycle, with this data.
+    Replayed(u8),
+    /// The access belongs to a future cycle and is skipped.
+    Skipped,
+}
+
+impl IoPort {
+    /// Returns the level of every line of the port: the data register for the outputs and the
+    /// external level for the inputs. This is what reading $01 returns.
+    pub fn lines(&self) -> u8 {
+        (self.data & self.direction) | (self.input & !self.direction)
+    }
+}
+
+/// The behavior of the BRK instruction ($00).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BreakPolicy {
+    /// BRK behaves as in the real hardware: it pushes PC+2 and the status flags (with the
+    /// B-flag set) and jumps to the address stored in the IRQ vector ($FFFE).
+    #[default]
+    Interrupt,
+    /// BRK stops the execution and leaves the Program Counter pointing at it. Since $00 is
+    /// the value of an empty memory location, this is useful to run plain hex programs.
+    Halt,
+}
+
+/// The behavior of the undocumented opcodes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IllegalPolicy {
+    /// The opcodes behave as in the real hardware.
+    #[default]
+    Execute,
+    /// The execution stops with `Error::IllegalOpcode` before fetching the opcode, leaving the
+    /// Program Counter pointing at it.
+    Error,
+}
+
+/// Represents the 6502 seven status flags
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct P6502Flags {
     /// Carry flag
     pub c: bool,
@@ -71,9 +330,15 @@ impl P6502Flags {
 impl P6502 {
     /// Indicates the begining of the stack page. As is the 6502, it corresponds to the second page: $0100 - $01ff
     const STACK_PAGE: u16 = 0x0100;
-    /// The default memory location where the program counter is set when starting
-    /// or after a CPU reset.
+    /// The default memory location where programs are loaded. `Memory::from_program_vec`
+    /// points the reset vector to it.
     pub const PROGRAM_START: u16 = 0x0600;
+    /// The location of the address to jump to after a CPU reset.
+    pub const RESET_VECTOR: u16 = 0xFFFC;
+    /// The location of the address to jump to when a NMI occurs.
+    pub const NMI_VECTOR: u16 = 0xFFFA;
+    /// The location of the address to jump to when an IRQ occurs.
+    pub const IRQ_VECTOR: u16 = 0xFFFE;
 
     /* LIST OF OPCODES */
 
@@ -250,1265 +515,1512 @@ impl P6502 {
     pub const INS_BIT_ZP0: u8 = 0x24;
     pub const INS_BIT_ABS: u8 = 0x2C;
 
+    /* UNDOCUMENTED OPCODES. When several opcodes share the same instruction and addressing
+    mode, the opcode is appended to the name. */
+
+    /* Halting operations */
+    pub const INS_JAM_02: u8 = 0x02;
+    pub const INS_JAM_12: u8 = 0x12;
+    pub const INS_JAM_22: u8 = 0x22;
+    pub const INS_JAM_32: u8 = 0x32;
+    pub const INS_JAM_42: u8 = 0x42;
+    pub const INS_JAM_52: u8 = 0x52;
+    pub const INS_JAM_62: u8 = 0x62;
+    pub const INS_JAM_72: u8 = 0x72;
+    pub const INS_JAM_92: u8 = 0x92;
+    pub const INS_JAM_B2: u8 = 0xB2;
+    pub const INS_JAM_D2: u8 = 0xD2;
+    pub const INS_JAM_F2: u8 = 0xF2;
+
+    /* No operations */
+    pub const INS_NOP_1A: u8 = 0x1A;
+    pub const INS_NOP_3A: u8 = 0x3A;
+    pub const INS_NOP_5A: u8 = 0x5A;
+    pub const INS_NOP_7A: u8 = 0x7A;
+    pub const INS_NOP_DA: u8 = 0xDA;
+    pub const INS_NOP_FA: u8 = 0xFA;
+    pub const INS_NOP_IMM_80: u8 = 0x80;
+    pub const INS_NOP_IMM_82: u8 = 0x82;
+    pub const INS_NOP_IMM_89: u8 = 0x89;
+    pub const INS_NOP_IMM_C2: u8 = 0xC2;
+    pub const INS_NOP_IMM_E2: u8 = 0xE2;
+    pub const INS_NOP_ZP0_04: u8 = 0x04;
+    pub const INS_NOP_ZP0_44: u8 = 0x44;
+    pub const INS_NOP_ZP0_64: u8 = 0x64;
+    pub const INS_NOP_ZPX_14: u8 = 0x14;
+    pub const INS_NOP_ZPX_34: u8 = 0x34;
+    pub const INS_NOP_ZPX_54: u8 = 0x54;
+    pub const INS_NOP_ZPX_74: u8 = 0x74;
+    pub const INS_NOP_ZPX_D4: u8 = 0xD4;
+    pub const INS_NOP_ZPX_F4: u8 = 0xF4;
+    pub const INS_NOP_ABS: u8 = 0x0C;
+    pub const INS_NOP_ABX_1C: u8 = 0x1C;
+    pub const INS_NOP_ABX_3C: u8 = 0x3C;
+    pub const INS_NOP_ABX_5C: u8 = 0x5C;
+    pub const INS_NOP_ABX_7C: u8 = 0x7C;
+    pub const INS_NOP_ABX_DC: u8 = 0xDC;
+    pub const INS_NOP_ABX_FC: u8 = 0xFC;
+
+    /* Combined load and store operations */
+    pub const INS_LAX_ZP0: u8 = 0xA7;
+    pub const INS_LAX_ZPY: u8 = 0xB7;
+    pub const INS_LAX_ABS: u8 = 0xAF;
+    pub const INS_LAX_ABY: u8 = 0xBF;
+    pub const INS_LAX_IDX: u8 = 0xA3;
+    pub const INS_LAX_IDY: u8 = 0xB3;
+    pub const INS_SAX_ZP0: u8 = 0x87;
+    pub const INS_SAX_ZPY: u8 = 0x97;
+    pub const INS_SAX_ABS: u8 = 0x8F;
+    pub const INS_SAX_IDX: u8 = 0x83;
+    pub const INS_LAS_ABY: u8 = 0xBB;
+
+    /* Read-modify-write operations combined with another operation */
+    pub const INS_SLO_ZP0: u8 = 0x07;
+    pub const INS_SLO_ZPX: u8 = 0x17;
+    pub const INS_SLO_ABS: u8 = 0x0F;
+    pub const INS_SLO_ABX: u8 = 0x1F;
+    pub const INS_SLO_ABY: u8 = 0x1B;
+    pub const INS_SLO_IDX: u8 = 0x03;
+    pub const INS_SLO_IDY: u8 = 0x13;
+    pub const INS_RLA_ZP0: u8 = 0x27;
+    pub const INS_RLA_ZPX: u8 = 0x37;
+    pub const INS_RLA_ABS: u8 = 0x2F;
+    pub const INS_RLA_ABX: u8 = 0x3F;
+    pub const INS_RLA_ABY: u8 = 0x3B;
+    pub const INS_RLA_IDX: u8 = 0x23;
+    pub const INS_RLA_IDY: u8 = 0x33;
+    pub const INS_SRE_ZP0: u8 = 0x47;
+    pub const INS_SRE_ZPX: u8 = 0x57;
+    pub const INS_SRE_ABS: u8 = 0x4F;
+    pub const INS_SRE_ABX: u8 = 0x5F;
+    pub const INS_SRE_ABY: u8 = 0x5B;
+    pub const INS_SRE_IDX: u8 = 0x43;
+    pub const INS_SRE_IDY: u8 = 0x53;
+    pub const INS_RRA_ZP0: u8 = 0x67;
+    pub const INS_RRA_ZPX: u8 = 0x77;
+    pub const INS_RRA_ABS: u8 = 0x6F;
+    pub const INS_RRA_ABX: u8 = 0x7F;
+    pub const INS_RRA_ABY: u8 = 0x7B;
+    pub const INS_RRA_IDX: u8 = 0x63;
+    pub const INS_RRA_IDY: u8 = 0x73;
+    pub const INS_DCP_ZP0: u8 = 0xC7;
+    pub const INS_DCP_ZPX: u8 = 0xD7;
+    pub const INS_DCP_ABS: u8 = 0xCF;
+    pub const INS_DCP_ABX: u8 = 0xDF;
+    pub const INS_DCP_ABY: u8 = 0xDB;
+    pub const INS_DCP_IDX: u8 = 0xC3;
+    pub const INS_DCP_IDY: u8 = 0xD3;
+    pub const INS_ISC_ZP0: u8 = 0xE7;
+    pub const INS_ISC_ZPX: u8 = 0xF7;
+    pub const INS_ISC_ABS: u8 = 0xEF;
+    pub const INS_ISC_ABX: u8 = 0xFF;
+    pub const INS_ISC_ABY: u8 = 0xFB;
+    pub const INS_ISC_IDX: u8 = 0xE3;
+    pub const INS_ISC_IDY: u8 = 0xF3;
+
+    /* Immediate operations */
+    pub const INS_ANC_IMM_0B: u8 = 0x0B;
+    pub const INS_ANC_IMM_2B: u8 = 0x2B;
+    pub const INS_ALR_IMM: u8 = 0x4B;
+    pub const INS_ARR_IMM: u8 = 0x6B;
+    pub const INS_SBX_IMM: u8 = 0xCB;
+    pub const INS_USBC_IMM: u8 = 0xEB;
+
+    /* Unstable operations */
+    pub const INS_ANE_IMM: u8 = 0x8B;
+    pub const INS_LXA_IMM: u8 = 0xAB;
+    pub const INS_SHA_ABY: u8 = 0x9F;
+    pub const INS_SHA_IDY: u8 = 0x93;
+    pub const INS_SHX_ABY: u8 = 0x9E;
+    pub const INS_SHY_ABX: u8 = 0x9C;
+    pub const INS_TAS_ABY: u8 = 0x9B;
+
+    /* 65C02 OPCODES. They replace the undocumented opcodes above when emulating a 65C02. */
+
+    /* New instructions */
+    pub const INS_BRA_REL: u8 = 0x80;
+    pub const INS_PHX: u8 = 0xDA;
+    pub const INS_PHY: u8 = 0x5A;
+    pub const INS_PLX: u8 = 0xFA;
+    pub const INS_PLY: u8 = 0x7A;
+    pub const INS_STZ_ZP0: u8 = 0x64;
+    pub const INS_STZ_ZPX: u8 = 0x74;
+    pub const INS_STZ_ABS: u8 = 0x9C;
+    pub const INS_STZ_ABX: u8 = 0x9E;
+    pub const INS_TRB_ZP0: u8 = 0x14;
+    pub const INS_TRB_ABS: u8 = 0x1C;
+    pub const INS_TSB_ZP0: u8 = 0x04;
+    pub const INS_TSB_ABS: u8 = 0x0C;
+    pub const INS_INC_ACC: u8 = 0x1A;
+    pub const INS_DEC_ACC: u8 = 0x3A;
+    pub const INS_WAI: u8 = 0xCB;
+    pub const INS_STP: u8 = 0xDB;
+
+    /* New addressing modes */
+    pub const INS_ORA_IZP: u8 = 0x12;
+    pub const INS_AND_IZP: u8 = 0x32;
+    pub const INS_EOR_IZP: u8 = 0x52;
+    pub const INS_ADC_IZP: u8 = 0x72;
+    pub const INS_STA_IZP: u8 = 0x92;
+    pub const INS_LDA_IZP: u8 = 0xB2;
+    pub const INS_CMP_IZP: u8 = 0xD2;
+    pub const INS_SBC_IZP: u8 = 0xF2;
+    pub const INS_BIT_IMM: u8 = 0x89;
+    pub const INS_BIT_ZPX: u8 = 0x34;
+    pub const INS_BIT_ABX: u8 = 0x3C;
+    pub const INS_JMP_IAX: u8 = 0x7C;
+
+    /* Bit operations */
+    pub const INS_RMB0_ZP0: u8 = 0x07;
+    pub const INS_RMB1_ZP0: u8 = 0x17;
+    pub const INS_RMB2_ZP0: u8 = 0x27;
+    pub const INS_RMB3_ZP0: u8 = 0x37;
+    pub const INS_RMB4_ZP0: u8 = 0x47;
+    pub const INS_RMB5_ZP0: u8 = 0x57;
+    pub const INS_RMB6_ZP0: u8 = 0x67;
+    pub const INS_RMB7_ZP0: u8 = 0x77;
+    pub const INS_SMB0_ZP0: u8 = 0x87;
+    pub const INS_SMB1_ZP0: u8 = 0x97;
+    pub const INS_SMB2_ZP0: u8 = 0xA7;
+    pub const INS_SMB3_ZP0: u8 = 0xB7;
+    pub const INS_SMB4_ZP0: u8 = 0xC7;
+    pub const INS_SMB5_ZP0: u8 = 0xD7;
+    pub const INS_SMB6_ZP0: u8 = 0xE7;
+    pub const INS_SMB7_ZP0: u8 = 0xF7;
+    pub const INS_BBR0_ZPR: u8 = 0x0F;
+    pub const INS_BBR1_ZPR: u8 = 0x1F;
+    pub const INS_BBR2_ZPR: u8 = 0x2F;
+    pub const INS_BBR3_ZPR: u8 = 0x3F;
+    pub const INS_BBR4_ZPR: u8 = 0x4F;
+    pub const INS_BBR5_ZPR: u8 = 0x5F;
+    pub const INS_BBR6_ZPR: u8 = 0x6F;
+    pub const INS_BBR7_ZPR: u8 = 0x7F;
+    pub const INS_BBS0_ZPR: u8 = 0x8F;
+    pub const INS_BBS1_ZPR: u8 = 0x9F;
+    pub const INS_BBS2_ZPR: u8 = 0xAF;
+    pub const INS_BBS3_ZPR: u8 = 0xBF;
+    pub const INS_BBS4_ZPR: u8 = 0xCF;
+    pub const INS_BBS5_ZPR: u8 = 0xDF;
+    pub const INS_BBS6_ZPR: u8 = 0xEF;
+    pub const INS_BBS7_ZPR: u8 = 0xFF;
+
+    /* No operations which are not undocumented NOPs on the NMOS 6502 */
+    pub const INS_NOP_IMM_02: u8 = 0x02;
+    pub const INS_NOP_IMM_22: u8 = 0x22;
+    pub const INS_NOP_IMM_42: u8 = 0x42;
+    pub const INS_NOP_IMM_62: u8 = 0x62;
+}
+
+impl<B: Bus> P6502<B> {
     /// Returns a string representation of the processor's registers and the number of cycles consumed.
     pub fn status(&self) -> String {
         format!(
-            "PC=${:04x}, SP=${:02x}\nA=${:02x}, X=${:02x}, Y=${:02x}\nFlags={:08b}\n",
+            "PC=${:04x}, SP=${:02x}\nA=${:02x}, X=${:02x}, Y=${:02x}\nFlags={:08b}\nCycles={}\n",
             self.pc,
             self.sp,
             self.a,
             self.x,
             self.y,
             self.flags.as_binary(),
+            self.cycles,
         )
     }
+
+    /// Returns the number of clock cycles elapsed since the CPU was created or powered on.
+    /// Every instruction consumes exactly as many cycles as in the real hardware, including
+    /// the extra ones for page crossings and taken branches.
+    pub fn cycles(&self) -> usize {
+        self.cycles
+    }
 }
-impl P6502 {
-    /// Runs the whole code loaded into memory until a $00 opcode (BRK) is reached.
-    pub fn execute(&mut self) {
-        loop {
-            let instruction: u8 = self.next_byte();
+impl<B: Bus> P6502<B> {
+    /// Runs the whole code loaded into memory. When using `BreakPolicy::Halt`, the execution
+    /// stops once a $00 opcode (BRK) is reached. It also stops when a WAI or STP instruction is
+    /// executed, and fails if the CPU jams, an illegal opcode is reached or the bus faults.
+    pub fn execute(&mut self) -> Result<()> {
+        while self.run_instruction()? == StepStatus::Running {}
+        Ok(())
+    }
 
-            match instruction {
-                P6502::INS_NOP => {
+    /// (1 C) Advances the CPU by exactly one clock cycle, which performs one bus access. Unlike
+    /// `execute`, which runs every instruction at once, this lets the devices on the bus see
+    /// each access on the cycle it happens, dummy reads and writes included. Between calls, the
+    /// registers hold their values from before the instruction in progress.
+    ///
+    /// Returns `false` if the CPU has halted. While a WAI instruction is waiting for an
+    /// interrupt the clock keeps running (the bus is ticked) and `true` is returned.
+    pub fn tick(&mut self) -> bool {
+        if self.jammed {
+            return false;
+        }
+
+        let mut step: CycleStep = match self.cycle_step.take() {
+            Some(step) => step,
+            None => {
+                if self.waiting && !self.interrupt_requested() {
                     self.clock_tick(1);
+                    return true;
                 }
-                /*
-                 In this implementation, the BRK command (=$00) is used to quit the program, since it represents an empty memory location.
-                 A real BRK instruction would look something like this:
-
-                 P6502::INS_BRK => {
-                    self.flags.b = true;
-                    self.push_word(self.pc);
-                    self.pc = 0xFFFE;
-                    self.push_byte(self.flags.as_binary());
-                    self.clock_tick(3);
-                    break;
-                } */
-
-                P6502::INS_BRK => {
-                    self.pc = self.pc.saturating_sub(1);
-                    break;
+                CycleStep {
+                    snapshot: self.snapshot(),
+                    accesses: Vec::new(),
+                    position: 0,
+                    new_access_done: false,
+                    incomplete: false,
+                    nmi_level: self.memory.nmi(),
+                    irq_level: self.irq_line || self.memory.irq(),
                 }
+            }
+        };
+        step.position = 0;
+        step.new_access_done = false;
+        step.incomplete = false;
+        self.restore(step.snapshot);
+        self.cycle_step = Some(step);
+
+        let running: bool = self.execute_instruction();
+
+        match self.cycle_step.take() {
+            Some(step) if step.incomplete => {
+                self.restore(step.snapshot);
+                self.cycle_step = Some(step);
+                true
+            }
+            _ => running || self.waiting,
+        }
+    }
 
-                P6502::INS_RTI => {
-                    let bin_flags: u8 = self.pull_byte();
-                    let new_pc: u16 = self.pull_word();
+    /// Executes one instruction, after servicing any pending interrupt, and returns what it did.
+    /// If `tick` was in the middle of an instruction, that one is finished instead. When the
+    /// CPU is halted nothing is executed and the status tells why.
+    pub fn step(&mut self) -> Result<StepResult> {
+        let start: usize = self.cycles;
+        self.trace.accesses = Some(Vec::new());
+        let status: Result<StepStatus> = self.run_instruction();
+        let accesses: Vec<BusAccess> = self.trace.accesses.take().unwrap_or_default();
+
+        Ok(StepResult {
+            address: self.trace.address,
+            opcode: self.trace.opcode,
+            instruction: opcode::decode(self.variant, self.trace.opcode),
+            interrupt: self.trace.interrupt,
+            cycles: self.cycles - start,
+            accesses,
+            status: status?,
+        })
+    }
 
-                    self.flags = P6502Flags::from_binary(bin_flags);
-                    self.pc = new_pc;
-                    self.clock_tick(2);
-                }
+    /// Executes one instruction like `step`, also returning the addresses it wrote to with the
+    /// values they held before, in order, so that the instruction can be undone.
+    pub(crate) fn step_undoable(&mut self) -> Result<(StepResult, Vec<(u16, u8)>)> {
+        self.trace.overwritten = Some(Vec::new());
+        let result: Result<StepResult> = self.step();
+        let overwritten: Vec<(u16, u8)> = self.trace.overwritten.take().unwrap_or_default();
+        Ok((result?, overwritten))
+    }
 
-                P6502::INS_LDA_IMM => {
-                    let value: u8 = self.next_byte();
-                    self.a = value;
+    /// Executes whole instructions until at least the specified number of cycles has passed,
+    /// so it may run a few more, or until the CPU halts. While a WAI instruction waits, the
+    /// clock keeps running. Returns the status of the CPU.
+    pub fn run_for_cycles(&mut self, cycles: usize) -> Result<StepStatus> {
+        let end: usize = self.cycles + cycles;
 
-                    self.flags.z = value == 0;
-                    self.flags.n = (value & 0b10000000) > 0;
-                }
+        while self.cycles < end {
+            if self.waiting {
+                self.tick();
+                continue;
+            }
 
-                P6502::INS_LDA_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(zp_addr as u16);
-                    self.a = value;
+            let status: StepStatus = self.run_instruction()?;
+            if status != StepStatus::Running && status != StepStatus::Waiting {
+                return Ok(status);
+            }
+        }
 
-                    self.flags.z = value == 0;
-                    self.flags.n = (value & 0b10000000) > 0;
-                }
+        if self.waiting {
+            Ok(StepStatus::Waiting)
+        } else {
+            Ok(StepStatus::Running)
+        }
+    }
 
-                P6502::INS_LDA_ZPX => {
-                    let zp_addr: u8 = self.read_byte_from_addr(self.pc);
-                    let value: u8 = self.read_byte_from_addr(zp_addr.saturating_add(self.x) as u16);
-                    self.a = value;
-                    self.clock_tick(1);
-                    self.flags.z = value == 0;
-                    self.flags.n = (value & 0b10000000) > 0;
-                }
+    /// Executes instructions until the predicate, which is checked after each of them, returns
+    /// `true` or the CPU stops running. Returns the status of the CPU.
+    pub fn run_until<F>(&mut self, mut predicate: F) -> Result<StepStatus>
+    where
+        F: FnMut(&Self) -> bool,
+    {
+        loop {
+            let status: StepStatus = self.run_instruction()?;
+            if status != StepStatus::Running || predicate(self) {
+                return Ok(status);
+            }
+        }
+    }
 
-                P6502::INS_LDA_ABS => {
-                    let addr: u16 = self.next_word();
-                    self.a = self.read_byte_from_addr(addr);
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /// Executes one instruction, or finishes the one `tick` is in the middle of, and returns
+    /// the status of the CPU afterwards or the error which stopped it.
+    fn run_instruction(&mut self) -> Result<StepStatus> {
+        self.trace.address = self.pc;
+        self.trace.opcode = self.memory.peek(self.pc);
+        self.trace.interrupt = None;
 
-                P6502::INS_LDA_ABX => {
-                    let addr: u16 = self.next_word();
-                    self.a = self.read_byte_from_addr(addr + self.x as u16);
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+        let running: bool = if self.cycle_step.is_some() {
+            self.finish_tick()
+        } else {
+            self.execute_instruction()
+        };
 
-                P6502::INS_LDA_ABY => {
-                    let addr: u16 = self.next_word();
-                    self.a = self.read_byte_from_addr(addr + self.y as u16);
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        if let Some(addr) = self.memory.fault() {
+            return Err(Error::BusFault {
+                addr,
+                pc: self.trace.address,
+            });
+        }
 
-                P6502::INS_LDA_IDX => {
-                    let indirect: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indexed_indirect_addr(indirect);
-                    self.a = self.read_byte_from_addr(final_addr);
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+        if self.jammed {
+            let opcode: u8 = self.memory.peek(self.pc);
+            if opcode::decode(self.variant, opcode).mnemonic == Mnemonic::Stp {
+                Ok(StepStatus::Stopped)
+            } else {
+                Err(Error::Jam {
+                    opcode,
+                    pc: self.pc,
+                })
+            }
+        } else if self.waiting {
+            Ok(StepStatus::Waiting)
+        } else if !running {
+            Ok(StepStatus::Halted)
+        } else {
+            Ok(StepStatus::Running)
+        }
+    }
 
-                P6502::INS_LDA_IDY => {
-                    let indirect_addr: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indirect_indexing_addr(indirect_addr);
-                    self.a = self.read_byte_from_addr(final_addr);
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & (1 << 7)) != 0;
-                }
-                P6502::INS_JSR_ABS => {
-                    let jump_addr: u16 = self.next_word();
-                    self.push_word(self.pc - 1);
-                    self.pc = jump_addr;
-                    self.clock_tick(1);
-                }
+    /// Returns whether `tick` is in the middle of an instruction.
+    pub fn mid_instruction(&self) -> bool {
+        self.cycle_step.is_some()
+    }
 
-                P6502::INS_RTS => {
-                    self.pc = self.pull_word() + 1;
-                    self.clock_tick(3);
-                }
+    /// Finishes the instruction started by `tick`, if any. Returns `false` if the CPU has halted.
+    fn finish_tick(&mut self) -> bool {
+        let mut running: bool = true;
+        while self.cycle_step.is_some() {
+            running = self.tick();
+        }
+        running
+    }
 
-                P6502::INS_BCC_REL => {
-                    let jump_addr: u8 = self.next_byte();
-                    if !self.flags.c {
-                        self.relative_jump(jump_addr);
-                    }
-                }
+    /// Returns the registers and internal state which an instruction may modify.
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            pc: self.pc,
+            sp: self.sp,
+            a: self.a,
+            x: self.x,
+            y: self.y,
+            flags: self.flags,
+            io_port: self.io_port,
+            jammed: self.jammed,
+            waiting: self.waiting,
+            nmi_pending: self.nmi_pending,
+            nmi_previous: self.nmi_previous,
+        }
+    }
 
-                P6502::INS_BCS_REL => {
-                    let jump_addr: u8 = self.next_byte();
-                    if self.flags.c {
-                        self.relative_jump(jump_addr);
-                    }
-                }
+    /// Restores the registers and internal state from a snapshot.
+    pub(crate) fn restore(&mut self, snapshot: Snapshot) {
+        self.pc = snapshot.pc;
+        self.sp = snapshot.sp;
+        self.a = snapshot.a;
+        self.x = snapshot.x;
+        self.y = snapshot.y;
+        self.flags = snapshot.flags;
+        self.io_port = snapshot.io_port;
+        self.jammed = snapshot.jammed;
+        self.waiting = snapshot.waiting;
+        self.nmi_pending = snapshot.nmi_pending;
+        self.nmi_previous = snapshot.nmi_previous;
+    }
 
-                P6502::INS_BEQ_REL => {
-                    let jump_addr: u8 = self.next_byte();
-                    if self.flags.z {
-                        self.relative_jump(jump_addr);
-                    }
+    /// Decides what the next bus access does. Outside of `tick`, every access is real.
+    fn next_bus_cycle(&mut self) -> BusCycle {
+        match &mut self.cycle_step {
+            None => BusCycle::Real,
+            Some(step) => {
+                let index: usize = step.position;
+                step.position += 1;
+
+                if index < step.accesses.len() {
+                    BusCycle::Replayed(step.accesses[index])
+                } else if !step.new_access_done {
+                    step.new_access_done = true;
+                    BusCycle::Real
+                } else {
+                    step.incomplete = true;
+                    BusCycle::Skipped
                 }
+            }
+        }
+    }
 
-                P6502::INS_BMI_REL => {
-                    let jump_addr: u8 = self.next_byte();
-                    if self.flags.n {
-                        self.relative_jump(jump_addr);
-                    }
-                }
+    /// Records a real bus access, so that `tick` can replay it and `step` can report it.
+    fn record_bus_cycle(&mut self, addr: u16, data: u8, write: bool) {
+        if let Some(step) = &mut self.cycle_step {
+            step.accesses.push(data);
+        }
+        if let Some(accesses) = &mut self.trace.accesses {
+            accesses.push(BusAccess { addr, data, write });
+        }
+    }
 
-                P6502::INS_BNE_REL => {
-                    let jump_addr: u8 = self.next_byte();
-                    if !self.flags.z {
-                        self.relative_jump(jump_addr);
-                    }
-                }
+    /// Returns the level of the NMI line. Within `tick`, the one sampled on the first cycle of
+    /// the instruction.
+    fn nmi_level(&self) -> bool {
+        match &self.cycle_step {
+            Some(step) => step.nmi_level,
+            None => self.memory.nmi(),
+        }
+    }
 
-                P6502::INS_BPL_REL => {
-                    let jump_addr: u8 = self.next_byte();
-                    if !self.flags.n {
-                        self.relative_jump(jump_addr);
-                    }
-                }
+    /// Returns the level of the IRQ line, either driven through `irq` or by a device. Within
+    /// `tick`, the one sampled on the first cycle of the instruction.
+    fn irq_level(&self) -> bool {
+        match &self.cycle_step {
+            Some(step) => step.irq_level,
+            None => self.irq_line || self.memory.irq(),
+        }
+    }
 
-                P6502::INS_BVC_REL => {
-                    let jump_addr: u8 = self.next_byte();
-                    if !self.flags.v {
-                        self.relative_jump(jump_addr);
-                    }
-                }
+    /// Services any pending interrupt and then fetches, decodes and executes one instruction.
+    /// Returns `false` if the CPU has halted.
+    fn execute_instruction(&mut self) -> bool {
+        if self.jammed {
+            return false;
+        }
+        if self.waiting {
+            if !self.interrupt_requested() {
+                return false;
+            }
+            self.waiting = false;
+        }
+
+        self.poll_interrupts();
+        self.trace.address = self.pc;
+        self.trace.opcode = self.memory.peek(self.pc);
+        if self.illegal_policy == IllegalPolicy::Error
+            && !opcode::is_documented(self.variant, self.trace.opcode)
+        {
+            self.error = Some(Error::IllegalOpcode {
+                opcode: self.trace.opcode,
+                pc: self.pc,
+            });
+            return false;
+        }
+        // `tick` executes the same instruction again and again, so only whole ones are traced.
+        if self.cycle_step.is_none() {
+            if let Some(mut tracer) = self.tracer.take() {
+                tracer(&tracer::trace_line(self));
+                self.tracer = Some(tracer);
+            }
+        }
 
-                P6502::INS_BVS_REL => {
-                    let jump_addr: u8 = self.next_byte();
-                    if self.flags.v {
-                        self.relative_jump(jump_addr);
+        let instruction: u8 = self.next_byte();
+        self.trace.opcode = instruction;
+        let opcode: Opcode = opcode::decode(self.variant, instruction);
+
+        match opcode.mnemonic {
+            /* System operations */
+            Mnemonic::Nop => match opcode.cycles {
+                // The 65C02 turns some undocumented opcodes into one-cycle NOPs...
+                1 => {}
+                // ...and $5C into an eight-cycle one, which keeps reading from its operand.
+                8 => {
+                    let addr: u16 = self.operand_addr(opcode);
+                    for _ in 0..5 {
+                        self.read_byte_from_addr(addr);
                     }
                 }
-
-                P6502::INS_LDX_IMM => {
-                    let value: u8 = self.next_byte();
-                    self.x = value;
-
-                    self.flags.z = value == 0;
-                    self.flags.n = (value & 0b10000000) > 0;
+                _ => {
+                    self.read_operand(opcode);
                 }
+            },
 
-                P6502::INS_LDX_ZP0 => {
-                    let addr: u16 = self.next_byte() as u16;
-                    self.x = self.read_byte_from_addr(addr);
-                    self.flags.z = self.x == 0;
-                    self.flags.n = (self.x & 0b10000000) > 0;
+            Mnemonic::Brk => match self.break_policy {
+                BreakPolicy::Interrupt => {
+                    // The byte after the opcode is skipped, so the return address is PC+2.
+                    self.next_byte();
+                    self.push_word(self.pc);
+                    self.push_byte(self.flags.as_binary() | 0b00010000);
+                    self.flags.i = true;
+                    self.clear_decimal_on_interrupt();
+                    self.pc = self.read_word_from_addr(P6502::IRQ_VECTOR);
                 }
-
-                P6502::INS_LDX_ZPY => {
-                    let addr: u8 = self.next_byte();
-                    self.x = self.read_byte_from_addr(addr.wrapping_add(self.y) as u16);
-
-                    self.flags.z = self.x == 0;
-                    self.flags.n = (self.x & 0b10000000) > 0;
+                BreakPolicy::Halt => {
+                    self.pc = self.pc.wrapping_sub(1);
+                    return false;
                 }
+            },
 
-                P6502::INS_LDX_ABS => {
-                    let addr: u16 = self.next_word();
-                    self.x = self.read_byte_from_addr(addr);
+            Mnemonic::Rti => {
+                self.implied_cycle();
+                self.stack_cycle();
+                self.flags = P6502Flags::from_binary(self.pull_byte());
+                self.pc = self.pull_word();
+            }
 
-                    self.flags.z = self.x == 0;
-                    self.flags.n = (self.x & 0b10000000) > 0;
+            Mnemonic::Jam | Mnemonic::Stp => {
+                self.implied_cycle();
+                if opcode.mnemonic == Mnemonic::Stp {
+                    self.implied_cycle();
                 }
+                self.pc = self.pc.wrapping_sub(1);
+                self.jammed = true;
+                return false;
+            }
 
-                P6502::INS_LDX_ABY => {
-                    let addr: u16 = self.next_word() + self.y as u16;
-                    self.x = self.read_byte_from_addr(addr);
+            Mnemonic::Wai => {
+                self.implied_cycle();
+                self.implied_cycle();
+                self.waiting = true;
+                return false;
+            }
 
-                    self.flags.z = self.x == 0;
-                    self.flags.n = (self.x & 0b10000000) > 0;
-                }
+            /* Load and store operations */
+            Mnemonic::Lda => {
+                self.a = self.read_operand(opcode);
+                self.set_zn_flags(self.a);
+            }
 
-                P6502::INS_LDY_IMM => {
-                    self.y = self.next_byte();
+            Mnemonic::Ldx => {
+                self.x = self.read_operand(opcode);
+                self.set_zn_flags(self.x);
+            }
 
-                    self.flags.z = self.y == 0;
-                    self.flags.n = (self.y & 0b10000000) > 0;
-                }
+            Mnemonic::Ldy => {
+                self.y = self.read_operand(opcode);
+                self.set_zn_flags(self.y);
+            }
 
-                P6502::INS_LDY_ZP0 => {
-                    let addr: u16 = self.next_byte() as u16;
-                    self.y = self.read_byte_from_addr(addr);
-                    self.flags.z = self.y == 0;
-                    self.flags.n = (self.y & 0b10000000) > 0;
-                }
+            Mnemonic::Sta => self.write_operand(opcode, self.a),
+            Mnemonic::Stx => self.write_operand(opcode, self.x),
+            Mnemonic::Sty => self.write_operand(opcode, self.y),
+            Mnemonic::Stz => self.write_operand(opcode, 0),
 
-                P6502::INS_LDY_ZPX => {
-                    let addr: u8 = self.next_byte();
-                    self.y = self.read_byte_from_addr(addr.saturating_add(self.x) as u16);
+            /* Register transfers */
+            Mnemonic::Tax => {
+                self.implied_cycle();
+                self.x = self.a;
+                self.set_zn_flags(self.x);
+            }
 
-                    self.flags.z = self.y == 0;
-                    self.flags.n = (self.y & 0b10000000) > 0;
-                }
+            Mnemonic::Tay => {
+                self.implied_cycle();
+                self.y = self.a;
+                self.set_zn_flags(self.y);
+            }
 
-                P6502::INS_LDY_ABS => {
-                    let addr: u16 = self.next_word();
-                    self.y = self.read_byte_from_addr(addr);
+            Mnemonic::Txa => {
+                self.implied_cycle();
+                self.a = self.x;
+                self.set_zn_flags(self.a);
+            }
 
-                    self.flags.z = self.y == 0;
-                    self.flags.n = (self.y & 0b10000000) > 0;
-                }
+            Mnemonic::Tya => {
+                self.implied_cycle();
+                self.a = self.y;
+                self.set_zn_flags(self.a);
+            }
 
-                P6502::INS_LDY_ABX => {
-                    let addr: u16 = self.next_word();
-                    self.y = self.read_byte_from_addr(addr + self.x as u16);
+            Mnemonic::Tsx => {
+                self.implied_cycle();
+                self.x = self.sp;
+                self.set_zn_flags(self.x);
+            }
 
-                    self.flags.z = self.y == 0;
-                    self.flags.n = (self.y & 0b10000000) > 0;
-                }
+            Mnemonic::Txs => {
+                self.implied_cycle();
+                self.sp = self.x;
+            }
 
-                P6502::INS_PHA => {
-                    self.push_byte(self.a);
-                    self.clock_tick(1);
-                }
+            /* Stack operations */
+            Mnemonic::Pha => {
+                self.implied_cycle();
+                self.push_byte(self.a);
+            }
 
-                P6502::INS_PHP => {
-                    self.push_byte(self.flags.as_binary());
-                    self.clock_tick(1);
-                }
+            Mnemonic::Phx => {
+                self.implied_cycle();
+                self.push_byte(self.x);
+            }
 
-                P6502::INS_PLA => {
-                    self.a = self.pull_byte();
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                    self.clock_tick(2);
-                }
+            Mnemonic::Phy => {
+                self.implied_cycle();
+                self.push_byte(self.y);
+            }
 
-                P6502::INS_PLP => {
-                    self.flags = P6502Flags::from_binary(self.pull_byte());
-                    self.clock_tick(2);
-                }
+            Mnemonic::Php => {
+                self.implied_cycle();
+                self.push_byte(self.flags.as_binary() | 0b00010000);
+            }
 
-                P6502::INS_JMP_ABS => {
-                    let addr: u16 = self.next_word();
-                    self.pc = addr;
-                    self.clock_tick(1);
-                }
+            Mnemonic::Pla => {
+                self.implied_cycle();
+                self.stack_cycle();
+                self.a = self.pull_byte();
+                self.set_zn_flags(self.a);
+            }
 
-                P6502::INS_JMP_IND => {
-                    let indirect_addr: u16 = self.next_word();
-                    let jump_addr: u16 = if indirect_addr & 0x00FF == 0xFF {
-                        let lsb: u8 = self.read_byte_from_addr(indirect_addr);
-                        let msb: u8 = self.read_byte_from_addr(indirect_addr & 0xFF00);
-                        (msb as u16) << 8 | (lsb as u16)
-                    } else {
-                        self.read_word_from_addr(indirect_addr)
-                    };
-                    self.pc = jump_addr;
-                    self.clock_tick(1);
-                }
+            Mnemonic::Plx => {
+                self.implied_cycle();
+                self.stack_cycle();
+                self.x = self.pull_byte();
+                self.set_zn_flags(self.x);
+            }
 
-                P6502::INS_TAX => {
-                    self.x = self.a;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                    self.clock_tick(1);
-                }
+            Mnemonic::Ply => {
+                self.implied_cycle();
+                self.stack_cycle();
+                self.y = self.pull_byte();
+                self.set_zn_flags(self.y);
+            }
 
-                P6502::INS_TAY => {
-                    self.y = self.a;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                    self.clock_tick(1);
-                }
+            Mnemonic::Plp => {
+                self.implied_cycle();
+                self.stack_cycle();
+                self.flags = P6502Flags::from_binary(self.pull_byte());
+            }
 
-                P6502::INS_TSX => {
-                    self.x = self.sp;
-                    self.flags.z = self.x == 0;
-                    self.flags.n = (self.x & 0b10000000) > 0;
-                    self.clock_tick(1);
-                }
+            /* Bit shifts */
+            Mnemonic::Asl => {
+                self.modify_operand(opcode, Self::shift_left);
+            }
 
-                P6502::INS_TXA => {
-                    self.a = self.x;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                    self.clock_tick(1);
-                }
+            Mnemonic::Lsr => {
+                self.modify_operand(opcode, Self::shift_right);
+            }
 
-                P6502::INS_TXS => {
-                    self.sp = self.x;
-                    self.clock_tick(1);
-                }
+            Mnemonic::Rol => {
+                self.modify_operand(opcode, Self::rotate_left);
+            }
 
-                P6502::INS_TYA => {
-                    self.a = self.y;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                    self.clock_tick(1);
-                }
-                P6502::INS_INC_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.increment_memory(zp_addr as u16);
-                }
+            Mnemonic::Ror => {
+                self.modify_operand(opcode, Self::rotate_right);
+            }
 
-                P6502::INS_INC_ZPX => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.increment_memory(zp_addr.wrapping_add(self.x) as u16);
-                    self.clock_tick(1);
-                }
+            /* Status flags operations */
+            Mnemonic::Clc => {
+                self.implied_cycle();
+                self.flags.c = false;
+            }
 
-                P6502::INS_INC_ABS => {
-                    let addr: u16 = self.next_word();
-                    self.increment_memory(addr);
-                    self.clock_tick(1);
-                }
+            Mnemonic::Cld => {
+                self.implied_cycle();
+                self.flags.d = false;
+            }
 
-                P6502::INS_INC_ABX => {
-                    let addr: u16 = self.next_word();
-                    self.increment_memory(addr + self.x as u16);
-                    self.clock_tick(2);
-                }
+            Mnemonic::Cli => {
+                self.implied_cycle();
+                self.flags.i = false;
+            }
 
-                P6502::INS_INX => {
-                    self.x = self.x.wrapping_add(1);
-                    self.flags.z = self.x == 0;
-                    self.flags.n = (self.x & 0b10000000) > 0;
-                    self.clock_tick(1);
-                }
+            Mnemonic::Clv => {
+                self.implied_cycle();
+                self.flags.v = false;
+            }
 
-                P6502::INS_INY => {
-                    self.y = self.y.wrapping_add(1);
-                    self.flags.z = self.y == 0;
-                    self.flags.n = (self.y & 0b10000000) > 0;
-                    self.clock_tick(1);
-                }
+            Mnemonic::Sec => {
+                self.implied_cycle();
+                self.flags.c = true;
+            }
 
-                P6502::INS_DEC_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.decrement_memory(zp_addr as u16);
-                    self.clock_tick(1);
-                }
+            Mnemonic::Sed => {
+                self.implied_cycle();
+                self.flags.d = true;
+            }
 
-                P6502::INS_DEC_ZPX => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.decrement_memory(zp_addr.wrapping_add(self.x) as u16);
-                    self.clock_tick(1);
-                }
+            Mnemonic::Sei => {
+                self.implied_cycle();
+                self.flags.i = true;
+            }
 
-                P6502::INS_DEC_ABS => {
-                    let addr: u16 = self.next_word();
-                    self.decrement_memory(addr);
-                }
+            /* Jumps, branches and returns */
+            Mnemonic::Jmp => {
+                let addr: u16 = self.operand_addr(opcode);
+                self.pc = match opcode.mode {
+                    AddressingMode::Absolute => addr,
+                    // The NMOS 6502 does not carry into the MSB when fetching the high byte of
+                    // the vector, so it is read from the beginning of the same page.
+                    AddressingMode::Indirect if self.variant != Variant::Cmos65C02 => {
+                        let lsb: u8 = self.read_byte_from_addr(addr);
+                        let msb: u8 = self
+                            .read_byte_from_addr((addr & 0xFF00) | (addr.wrapping_add(1) & 0x00FF));
+                        (msb as u16) << 8 | lsb as u16
+                    }
+                    // The 65C02 fixes the bug at the expense of one more cycle.
+                    AddressingMode::Indirect => {
+                        self.read_byte_from_addr(self.pc.wrapping_sub(1));
+                        self.read_word_from_addr(addr)
+                    }
+                    _ => self.read_word_from_addr(addr),
+                };
+            }
 
-                P6502::INS_DEC_ABX => {
-                    let addr: u16 = self.next_word();
-                    self.decrement_memory(addr);
-                    self.clock_tick(1);
-                }
+            Mnemonic::Jsr => {
+                // The return address (minus one) is pushed before the MSB of the target is read.
+                let lsb: u8 = self.next_byte();
+                self.stack_cycle();
+                self.push_word(self.pc);
+                let msb: u8 = self.read_byte_from_addr(self.pc);
+                self.pc = (msb as u16) << 8 | lsb as u16;
+            }
 
-                P6502::INS_DEX => {
-                    self.x = self.x.wrapping_sub(1);
-                    self.flags.z = self.x == 0;
-                    self.flags.n = (self.x & 0b10000000) > 0;
-                    self.clock_tick(1);
-                }
+            Mnemonic::Rts => {
+                self.implied_cycle();
+                self.stack_cycle();
+                self.pc = self.pull_word();
+                self.read_byte_from_addr(self.pc);
+                self.pc = self.pc.wrapping_add(1);
+            }
 
-                P6502::INS_DEY => {
-                    self.y = self.y.wrapping_sub(1);
-                    self.flags.z = self.y == 0;
-                    self.flags.n = (self.y & 0b10000000) > 0;
-                    self.clock_tick(1);
+            Mnemonic::Bcc => self.branch(!self.flags.c),
+            Mnemonic::Bcs => self.branch(self.flags.c),
+            Mnemonic::Beq => self.branch(self.flags.z),
+            Mnemonic::Bmi => self.branch(self.flags.n),
+            Mnemonic::Bne => self.branch(!self.flags.z),
+            Mnemonic::Bpl => self.branch(!self.flags.n),
+            Mnemonic::Bvc => self.branch(!self.flags.v),
+            Mnemonic::Bvs => self.branch(self.flags.v),
+            Mnemonic::Bra => self.branch(true),
+
+            Mnemonic::Bbr(bit) | Mnemonic::Bbs(bit) => {
+                let addr: u16 = self.operand_addr(opcode);
+                let value: u8 = self.read_byte_from_addr(addr);
+                let offset: u8 = self.next_byte();
+                self.read_byte_from_addr(addr);
+
+                let bit_set: bool = value & (1 << bit) != 0;
+                if bit_set == matches!(opcode.mnemonic, Mnemonic::Bbs(_)) {
+                    self.relative_jump(offset);
                 }
+            }
 
-                P6502::INS_STA_ZP0 => {
-                    let addr: u8 = self.next_byte();
-                    self.write_byte_to_addr(addr as u16, self.a);
-                }
+            /* Increments and decrements */
+            Mnemonic::Inc => {
+                self.modify_operand(opcode, |cpu, value| {
+                    let result: u8 = value.wrapping_add(1);
+                    cpu.set_zn_flags(result);
+                    result
+                });
+            }
 
-                P6502::INS_STA_ZPX => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.write_byte_to_addr(zp_addr.wrapping_add(self.x) as u16, self.a);
-                    self.clock_tick(1);
-                }
+            Mnemonic::Dec => {
+                self.modify_operand(opcode, |cpu, value| {
+                    let result: u8 = value.wrapping_sub(1);
+                    cpu.set_zn_flags(result);
+                    result
+                });
+            }
 
-                P6502::INS_STA_ABS => {
-                    let addr: u16 = self.next_word();
-                    self.write_byte_to_addr(addr, self.a);
-                }
+            Mnemonic::Inx => {
+                self.implied_cycle();
+                self.x = self.x.wrapping_add(1);
+                self.set_zn_flags(self.x);
+            }
 
-                P6502::INS_STA_ABX => {
-                    let addr: u16 = self.next_word();
-                    self.write_byte_to_addr(addr + self.x as u16, self.a);
-                    self.clock_tick(1);
-                }
+            Mnemonic::Iny => {
+                self.implied_cycle();
+                self.y = self.y.wrapping_add(1);
+                self.set_zn_flags(self.y);
+            }
 
-                P6502::INS_STA_ABY => {
-                    let addr: u16 = self.next_word();
-                    self.write_byte_to_addr(addr + self.y as u16, self.a);
-                    self.clock_tick(1);
-                }
+            Mnemonic::Dex => {
+                self.implied_cycle();
+                self.x = self.x.wrapping_sub(1);
+                self.set_zn_flags(self.x);
+            }
 
-                P6502::INS_STA_IDX => {
-                    let indirect: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indexed_indirect_addr(indirect);
-                    self.write_byte_to_addr(final_addr, self.a);
-                }
+            Mnemonic::Dey => {
+                self.implied_cycle();
+                self.y = self.y.wrapping_sub(1);
+                self.set_zn_flags(self.y);
+            }
 
-                P6502::INS_STA_IDY => {
-                    let indirect_addr: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indirect_indexing_addr(indirect_addr);
-                    self.write_byte_to_addr(final_addr, self.a);
-                    self.clock_tick(1);
-                }
+            /* Arithmetic and comparisions */
+            Mnemonic::Adc => {
+                let value: u8 = self.read_operand(opcode);
+                self.add_with_carry(value);
+            }
 
-                P6502::INS_STX_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.write_byte_to_addr(zp_addr as u16, self.x);
-                }
+            Mnemonic::Sbc | Mnemonic::Usbc => {
+                let value: u8 = self.read_operand(opcode);
+                self.substract_with_carry(value);
+            }
 
-                P6502::INS_STX_ZPY => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.write_byte_to_addr(zp_addr.wrapping_add(self.y) as u16, self.x);
-                    self.clock_tick(1);
-                }
+            Mnemonic::Cmp => {
+                let value: u8 = self.read_operand(opcode);
+                self.compare(self.a, value);
+            }
 
-                P6502::INS_STX_ABS => {
-                    let addr: u16 = self.next_word();
-                    self.write_byte_to_addr(addr as u16, self.x);
-                    self.clock_tick(1);
-                }
+            Mnemonic::Cpx => {
+                let value: u8 = self.read_operand(opcode);
+                self.compare(self.x, value);
+            }
 
-                P6502::INS_STY_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.write_byte_to_addr(zp_addr as u16, self.y);
-                }
+            Mnemonic::Cpy => {
+                let value: u8 = self.read_operand(opcode);
+                self.compare(self.y, value);
+            }
 
-                P6502::INS_STY_ZPX => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.write_byte_to_addr(zp_addr.wrapping_add(self.x) as u16, self.y);
-                    self.clock_tick(1);
-                }
-
-                P6502::INS_STY_ABS => {
-                    let addr: u16 = self.next_word();
-                    self.write_byte_to_addr(addr as u16, self.y);
-                    self.clock_tick(1);
-                }
-
-                P6502::INS_CMP_IMM => {
-                    let value: u8 = self.next_byte();
-                    self.flags.c = self.a >= value;
-                    self.flags.z = self.a == value;
-                    self.flags.n = (self.a.wrapping_sub(value) & 0b10000000) > 0;
-                }
-
-                P6502::INS_CMP_ZP0 => {
-                    let zp_addr: u16 = self.next_byte() as u16;
-                    let value: u8 = self.read_byte_from_addr(zp_addr);
-                    self.flags.c = self.a >= value;
-                    self.flags.z = self.a == value;
-                    self.flags.n = (self.a.wrapping_sub(value) & 0b10000000) > 0;
-                }
-
-                P6502::INS_CMP_ZPX => {
-                    let zp_addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(zp_addr.wrapping_add(self.x) as u16);
-                    self.flags.c = self.a >= value;
-                    self.flags.z = self.a == value;
-                    self.flags.n = (self.a.wrapping_sub(value) & 0b10000000) > 0;
-                }
-
-                P6502::INS_CMP_ABS => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr);
-                    self.flags.c = self.a >= value;
-                    self.flags.z = self.a == value;
-                    self.flags.n = (self.a.wrapping_sub(value) & 0b10000000) > 0;
-                }
-
-                P6502::INS_CMP_ABX => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr + self.x as u16);
-                    self.flags.c = self.a >= value;
-                    self.flags.z = self.a == value;
-                    self.flags.n = (self.a.wrapping_sub(value) & 0b10000000) > 0;
-                }
-
-                P6502::INS_CMP_ABY => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr + self.y as u16);
-                    self.flags.c = self.a >= value;
-                    self.flags.z = self.a == value;
-                    self.flags.n = (self.a.wrapping_sub(value) & 0b10000000) > 0;
-                }
-
-                P6502::INS_CMP_IDX => {
-                    let indirect: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indexed_indirect_addr(indirect);
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.flags.c = self.a >= value;
-                    self.flags.z = self.a == value;
-                    self.flags.n = (self.a.wrapping_sub(value) & 0b10000000) > 0;
-                }
-
-                P6502::INS_CMP_IDY => {
-                    let indirect_addr: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indirect_indexing_addr(indirect_addr);
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.flags.c = self.a >= value;
-                    self.flags.z = self.a == value;
-                    self.flags.n = (self.a.wrapping_sub(value) & 0b10000000) > 0;
-                }
-
-                P6502::INS_CPX_IMM => {
-                    let value: u8 = self.next_byte();
-                    self.flags.c = self.x >= value;
-                    self.flags.z = self.x == value;
-                    self.flags.n = (self.x.wrapping_sub(value) & 0b10000000) > 0;
-                }
-
-                P6502::INS_CPX_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(zp_addr as u16);
-                    self.flags.c = self.x >= value;
-                    self.flags.z = self.x == value;
-                    self.flags.n = (self.x.wrapping_sub(value) & 0b10000000) > 0;
-                }
-
-                P6502::INS_CPX_ABS => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr as u16);
-                    self.flags.c = self.x >= value;
-                    self.flags.z = self.x == value;
-                    self.flags.n = (self.x.wrapping_sub(value) & 0b10000000) > 0;
-                }
-
-                P6502::INS_CPY_IMM => {
-                    let value: u8 = self.next_byte();
-                    self.flags.c = self.y >= value;
-                    self.flags.z = self.y == value;
-                    self.flags.n = (self.y.wrapping_sub(value) & 0b10000000) > 0;
-                }
-
-                P6502::INS_CPY_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(zp_addr as u16);
-                    self.flags.c = self.y >= value;
-                    self.flags.z = self.y == value;
-                    self.flags.n = (self.y.wrapping_sub(value) & 0b10000000) > 0;
-                }
+            /* Binary logical operations */
+            Mnemonic::And => {
+                self.a &= self.read_operand(opcode);
+                self.set_zn_flags(self.a);
+            }
 
-                P6502::INS_CPY_ABS => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr as u16);
-                    self.flags.c = self.y >= value;
-                    self.flags.z = self.y == value;
-                    self.flags.n = (self.y.wrapping_sub(value) & 0b10000000) > 0;
-                }
+            Mnemonic::Ora => {
+                self.a |= self.read_operand(opcode);
+                self.set_zn_flags(self.a);
+            }
 
-                P6502::INS_ADC_IMM => {
-                    let value: u8 = self.next_byte();
-                    self.add_with_carry(value);
-                }
+            Mnemonic::Eor => {
+                self.a ^= self.read_operand(opcode);
+                self.set_zn_flags(self.a);
+            }
 
-                P6502::INS_ADC_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(zp_addr as u16);
-                    self.add_with_carry(value);
+            Mnemonic::Bit => {
+                let value: u8 = self.read_operand(opcode);
+                if opcode.mode == AddressingMode::Immediate {
+                    // Only the zero flag is affected when the operand is immediate.
+                    self.flags.z = self.a & value == 0;
+                } else {
+                    self.test_bits(value);
                 }
+            }
 
-                P6502::INS_ADC_ZPX => {
-                    let zp_addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(zp_addr.wrapping_add(self.x) as u16);
-                    self.add_with_carry(value);
-                }
+            /* Bit operations of the 65C02 */
+            Mnemonic::Trb => {
+                self.modify_operand(opcode, |cpu, value| {
+                    cpu.flags.z = cpu.a & value == 0;
+                    value & !cpu.a
+                });
+            }
 
-                P6502::INS_ADC_ABS => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr);
-                    self.add_with_carry(value);
-                }
+            Mnemonic::Tsb => {
+                self.modify_operand(opcode, |cpu, value| {
+                    cpu.flags.z = cpu.a & value == 0;
+                    value | cpu.a
+                });
+            }
 
-                P6502::INS_ADC_ABX => {
-                    let addr: u16 = self.next_word();
-                    let final_addr: u16 = addr + self.x as u16;
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.add_with_carry(value);
-                }
+            Mnemonic::Rmb(bit) => {
+                self.modify_operand(opcode, |_, value| value & !(1 << bit));
+            }
 
-                P6502::INS_ADC_ABY => {
-                    let addr: u16 = self.next_word();
-                    let final_addr: u16 = addr + self.y as u16;
-                    if addr & 0xFF00 != final_addr & 0xFF00 {
-                        self.clock_tick(1);
-                    }
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.add_with_carry(value);
-                }
+            Mnemonic::Smb(bit) => {
+                self.modify_operand(opcode, |_, value| value | 1 << bit);
+            }
 
-                P6502::INS_ADC_IDX => {
-                    let indirect: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indexed_indirect_addr(indirect);
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.add_with_carry(value);
-                }
+            /* Undocumented combined load and store operations */
+            Mnemonic::Lax => {
+                let value: u8 = self.read_operand(opcode);
+                self.a = value;
+                self.x = value;
+                self.set_zn_flags(value);
+            }
 
-                P6502::INS_ADC_IDY => {
-                    let indirect: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indirect_indexing_addr(indirect);
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.add_with_carry(value);
-                    self.clock_tick(1);
-                }
+            Mnemonic::Sax => self.write_operand(opcode, self.a & self.x),
 
-                P6502::INS_SBC_IMM => {
-                    let value: u8 = self.next_byte();
-                    self.substract_with_carry(value);
-                }
+            Mnemonic::Las => {
+                let value: u8 = self.read_operand(opcode) & self.sp;
+                self.a = value;
+                self.x = value;
+                self.sp = value;
+                self.set_zn_flags(value);
+            }
 
-                P6502::INS_SBC_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(zp_addr as u16);
-                    self.substract_with_carry(value);
-                }
+            /* Undocumented read-modify-write operations combined with another operation */
+            Mnemonic::Slo => {
+                self.a |= self.modify_operand(opcode, Self::shift_left);
+                self.set_zn_flags(self.a);
+            }
 
-                P6502::INS_SBC_ZPX => {
-                    let zp_addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(zp_addr.wrapping_add(self.x) as u16);
-                    self.substract_with_carry(value);
-                    self.clock_tick(1);
-                }
+            Mnemonic::Rla => {
+                self.a &= self.modify_operand(opcode, Self::rotate_left);
+                self.set_zn_flags(self.a);
+            }
 
-                P6502::INS_SBC_ABS => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr);
-                    self.substract_with_carry(value);
-                }
+            Mnemonic::Sre => {
+                self.a ^= self.modify_operand(opcode, Self::shift_right);
+                self.set_zn_flags(self.a);
+            }
 
-                P6502::INS_SBC_ABX => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr + self.x as u16);
-                    self.substract_with_carry(value);
-                }
+            Mnemonic::Rra => {
+                let value: u8 = self.modify_operand(opcode, Self::rotate_right);
+                self.add_with_carry(value);
+            }
 
-                P6502::INS_SBC_ABY => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr + self.y as u16);
-                    self.substract_with_carry(value);
-                }
+            Mnemonic::Dcp => {
+                let value: u8 = self.modify_operand(opcode, |_, value| value.wrapping_sub(1));
+                self.compare(self.a, value);
+            }
 
-                P6502::INS_SBC_IDX => {
-                    let indirect: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indexed_indirect_addr(indirect);
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.substract_with_carry(value);
-                }
-                P6502::INS_SBC_IDY => {
-                    let indirect_addr: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indirect_indexing_addr(indirect_addr);
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.substract_with_carry(value);
-                }
+            Mnemonic::Isc => {
+                let value: u8 = self.modify_operand(opcode, |_, value| value.wrapping_add(1));
+                self.substract_with_carry(value);
+            }
 
-                P6502::INS_AND_IMM => {
-                    let value: u8 = self.next_byte();
-                    self.a &= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+            /* Undocumented immediate operations */
+            Mnemonic::Anc => {
+                self.a &= self.read_operand(opcode);
+                self.set_zn_flags(self.a);
+                self.flags.c = self.flags.n;
+            }
 
-                P6502::INS_AND_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(zp_addr as u16);
-                    self.a &= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+            Mnemonic::Alr => {
+                let value: u8 = self.a & self.read_operand(opcode);
+                self.a = self.shift_right(value);
+            }
 
-                P6502::INS_AND_ZPX => {
-                    let zp_addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(zp_addr.wrapping_add(self.x) as u16);
-                    self.a &= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+            Mnemonic::Arr => {
+                let value: u8 = self.a & self.read_operand(opcode);
+                self.and_rotate_right(value);
+            }
 
-                P6502::INS_AND_ABS => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr);
-                    self.a &= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+            Mnemonic::Sbx => {
+                let value: u8 = self.read_operand(opcode);
+                let and_result: u8 = self.a & self.x;
+                self.x = and_result.wrapping_sub(value);
+                self.flags.c = and_result >= value;
+                self.set_zn_flags(self.x);
+            }
 
-                P6502::INS_AND_ABX => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr + self.x as u16);
-                    self.a &= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+            /* Undocumented unstable operations */
+            Mnemonic::Ane => {
+                let value: u8 = self.read_operand(opcode);
+                self.a = (self.a | self.magic_constant) & self.x & value;
+                self.set_zn_flags(self.a);
+            }
 
-                P6502::INS_AND_ABY => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr + self.y as u16);
-                    self.a &= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+            Mnemonic::Lxa => {
+                let value: u8 = self.read_operand(opcode);
+                self.a = (self.a | self.magic_constant) & value;
+                self.x = self.a;
+                self.set_zn_flags(self.a);
+            }
 
-                P6502::INS_AND_IDX => {
-                    let indirect: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indexed_indirect_addr(indirect);
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.a &= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+            Mnemonic::Sha => self.unstable_store(opcode, self.a & self.x),
+            Mnemonic::Shx => self.unstable_store(opcode, self.x),
+            Mnemonic::Shy => self.unstable_store(opcode, self.y),
 
-                P6502::INS_AND_IDY => {
-                    let indirect_addr: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indirect_indexing_addr(indirect_addr);
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.a |= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+            Mnemonic::Tas => {
+                self.sp = self.a & self.x;
+                self.unstable_store(opcode, self.sp);
+            }
+        }
 
-                P6502::INS_ORA_IMM => {
-                    let value: u8 = self.next_byte();
-                    self.a |= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+        true
+    }
 
-                P6502::INS_ORA_ZP0 => {
-                    let addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(addr as u16);
-                    self.a |= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /* Addressing modes */
+
+    /// (0-5 C) Reads the operand of an instruction and returns the address it refers to.
+    /// - For the Immediate mode, it is the address of the operand itself.
+    /// - For the Implied and Accumulator modes, it is the address of the next byte, which the
+    ///   6502 reads anyway.
+    /// - For the Indirect modes of JMP, it is the address of the vector.
+    /// - For the Relative modes, it is the address of the offset (or of the Zero Page location
+    ///   to test, for BBR and BBS).
+    ///
+    /// When adding an index crosses a page an extra cycle is spent, unless the opcode does
+    /// not have a page-cross penalty, which means that the cycle is always spent.
+    fn operand_addr(&mut self, opcode: Opcode) -> u16 {
+        let always_fix: bool = !opcode.page_penalty;
+
+        match opcode.mode {
+            AddressingMode::Implied | AddressingMode::Accumulator => self.pc,
+            AddressingMode::Immediate | AddressingMode::Relative => {
+                let addr: u16 = self.pc;
+                self.pc = self.pc.wrapping_add(1);
+                addr
+            }
+            AddressingMode::ZeroPage | AddressingMode::ZeroPageRelative => self.zero_page_addr(),
+            AddressingMode::ZeroPageX => self.zero_page_indexed_addr(self.x),
+            AddressingMode::ZeroPageY => self.zero_page_indexed_addr(self.y),
+            AddressingMode::Absolute | AddressingMode::Indirect => self.next_word(),
+            AddressingMode::AbsoluteX => self.absolute_indexed_addr(self.x, always_fix),
+            AddressingMode::AbsoluteY => self.absolute_indexed_addr(self.y, always_fix),
+            AddressingMode::IndexedIndirect => self.indexed_indirect_addr(),
+            AddressingMode::IndirectIndexed => self.indirect_indexed_addr(always_fix),
+            AddressingMode::ZeroPageIndirect => self.zero_page_indirect_addr(),
+            AddressingMode::AbsoluteIndexedIndirect => {
+                let base: u16 = self.next_word();
+                self.read_byte_from_addr(self.pc.wrapping_sub(1));
+                base.wrapping_add(self.x as u16)
+            }
+        }
+    }
 
-                P6502::INS_ORA_ZPX => {
-                    let zp_addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(zp_addr.wrapping_add(self.x) as u16);
-                    self.a |= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /// (1-6 C) Reads the operand of an instruction and returns the value it refers to.
+    fn read_operand(&mut self, opcode: Opcode) -> u8 {
+        let addr: u16 = self.operand_addr(opcode);
+        self.read_byte_from_addr(addr)
+    }
 
-                P6502::INS_ORA_ABS => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr);
-                    self.a |= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /// (2-6 C) Reads the operand of an instruction and writes a value to the address it refers to.
+    fn write_operand(&mut self, opcode: Opcode, value: u8) {
+        let addr: u16 = self.operand_addr(opcode);
+        self.write_byte_to_addr(addr, value);
+    }
 
-                P6502::INS_ORA_ABX => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr + self.x as u16);
-                    self.a |= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /// (1-7 C) Applies an operation to the operand of a read-modify-write instruction, which is
+    /// either the accumulator or a memory location, and returns the result.
+    fn modify_operand<F>(&mut self, opcode: Opcode, operation: F) -> u8
+    where
+        F: FnOnce(&mut Self, u8) -> u8,
+    {
+        if opcode.mode == AddressingMode::Accumulator {
+            self.implied_cycle();
+            let value: u8 = self.a;
+            self.a = operation(self, value);
+            self.a
+        } else {
+            let addr: u16 = self.operand_addr(opcode);
+            self.read_modify_write(addr, operation)
+        }
+    }
 
-                P6502::INS_ORA_ABY => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr + self.y as u16);
-                    self.a |= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /// (1 C) Performs the read of the next byte that the 6502 does, and discards, while it
+    /// executes an instruction with no operand.
+    fn implied_cycle(&mut self) {
+        self.read_byte_from_addr(self.pc);
+    }
 
-                P6502::INS_ORA_IDX => {
-                    let indirect: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indexed_indirect_addr(indirect);
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.a |= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /// (1 C) Performs the read of the top of the stack that the 6502 does, and discards, before
+    /// pulling from it.
+    fn stack_cycle(&mut self) {
+        self.read_byte_from_addr(P6502::STACK_PAGE + self.sp as u16);
+    }
 
-                P6502::INS_ORA_IDY => {
-                    let indirect_addr: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indirect_indexing_addr(indirect_addr);
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.a |= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /// (1-3 C) Reads the offset of a branch and jumps if the condition is met.
+    fn branch(&mut self, condition: bool) {
+        let offset: u8 = self.next_byte();
+        if condition {
+            self.relative_jump(offset);
+        }
+    }
 
-                P6502::INS_EOR_IMM => {
-                    let value: u8 = self.next_byte();
-                    self.a ^= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /* Helpers */
 
-                P6502::INS_EOR_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(zp_addr as u16);
-                    self.a ^= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /// Sets the zero and negative flags from a value.
+    fn set_zn_flags(&mut self, value: u8) {
+        self.flags.z = value == 0;
+        self.flags.n = (value & 0b10000000) != 0;
+    }
 
-                P6502::INS_EOR_ZPX => {
-                    let zp_addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(zp_addr.wrapping_add(self.x) as u16);
-                    self.a ^= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /// Compares a register with a value, setting the carry, zero and negative flags.
+    fn compare(&mut self, register: u8, value: u8) {
+        self.flags.c = register >= value;
+        self.set_zn_flags(register.wrapping_sub(value));
+    }
 
-                P6502::INS_EOR_ABS => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr);
-                    self.a ^= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /// (1 C) Reads a Zero Page operand and returns it as an address.
+    fn zero_page_addr(&mut self) -> u16 {
+        self.next_byte() as u16
+    }
 
-                P6502::INS_EOR_ABX => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr + self.x as u16);
-                    self.a ^= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /// (2 C) Reads a Zero Page,X or Zero Page,Y operand and returns the final address, which
+    /// wraps around the Zero Page. The base address is read while the index is being added.
+    fn zero_page_indexed_addr(&mut self, index: u8) -> u16 {
+        let zp_addr: u8 = self.next_byte();
+        self.read_byte_from_addr(zp_addr as u16);
+        zp_addr.wrapping_add(index) as u16
+    }
 
-                P6502::INS_EOR_ABY => {
-                    let indirect: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indexed_indirect_addr(indirect);
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.a ^= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /// (2-3 C) Reads an Absolute,X or Absolute,Y operand and returns the final address. When
+    /// adding the index crosses a page, an extra cycle fixes the MSB (see `fix_page_crossing`).
+    /// Instructions which write to memory always take that cycle, so `always_fix` must be set
+    /// for them.
+    fn absolute_indexed_addr(&mut self, index: u8, always_fix: bool) -> u16 {
+        let base: u16 = self.next_word();
+        let addr: u16 = base.wrapping_add(index as u16);
+        if always_fix || base & 0xFF00 != addr & 0xFF00 {
+            self.fix_page_crossing(base, addr);
+        }
+        addr
+    }
 
-                P6502::INS_EOR_IDX => {
-                    let indirect: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indexed_indirect_addr(indirect);
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.a ^= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /// (1 C) Performs the dummy read of the cycle which fixes the MSB of an indexed address.
+    /// The NMOS chips read the address with the unfixed MSB, whereas the 65C02 reads the last
+    /// byte of the operand again when a page is crossed.
+    fn fix_page_crossing(&mut self, base: u16, addr: u16) {
+        if self.variant == Variant::Cmos65C02 && base & 0xFF00 != addr & 0xFF00 {
+            self.read_byte_from_addr(self.pc.wrapping_sub(1));
+        } else {
+            self.read_byte_from_addr((base & 0xFF00) | (addr & 0x00FF));
+        }
+    }
 
-                P6502::INS_EOR_IDY => {
-                    let indirect_addr: u8 = self.next_byte();
-                    let final_addr: u16 = self.parse_indirect_indexing_addr(indirect_addr);
-                    let value: u8 = self.read_byte_from_addr(final_addr);
-                    self.a ^= value;
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) > 0;
-                }
+    /// (4 C) Reads a (ADDR,X) indexed indirect operand and returns the final address.
+    fn indexed_indirect_addr(&mut self) -> u16 {
+        let indirect: u8 = self.next_byte();
+        self.read_byte_from_addr(indirect as u16);
+        self.read_zero_page_word(indirect.wrapping_add(self.x))
+    }
 
-                P6502::INS_BIT_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    let value: u8 = self.read_byte_from_addr(zp_addr as u16);
-                    self.flags.z = value & self.a == 0;
-                    self.flags.v = (value & 0b01000000) >> 6 != 0;
-                    self.flags.n = (value & 0b10000000) >> 7 != 0;
-                }
+    /// (3-4 C) Reads a (ADDR),Y indirect indexed operand and returns the final address. The
+    /// extra cycle follows the same rules as in `absolute_indexed_addr`.
+    fn indirect_indexed_addr(&mut self, always_fix: bool) -> u16 {
+        let indirect: u8 = self.next_byte();
+        let base: u16 = self.read_zero_page_word(indirect);
+        let addr: u16 = base.wrapping_add(self.y as u16);
+        if always_fix || base & 0xFF00 != addr & 0xFF00 {
+            self.fix_page_crossing(base, addr);
+        }
+        addr
+    }
 
-                P6502::INS_BIT_ABS => {
-                    let addr: u16 = self.next_word();
-                    let value: u8 = self.read_byte_from_addr(addr);
-                    self.flags.z = value & self.a == 0;
-                    self.flags.v = (value & 0b01000000) >> 6 != 0;
-                    self.flags.n = (value & 0b10000000) >> 7 != 0;
-                }
+    /// (2 C) Reads a 16-bit pointer from the Zero Page. The MSB of a pointer at $FF is read
+    /// from $00.
+    fn read_zero_page_word(&mut self, addr: u8) -> u16 {
+        let lsb: u8 = self.read_byte_from_addr(addr as u16);
+        let msb: u8 = self.read_byte_from_addr(addr.wrapping_add(1) as u16);
+        (msb as u16) << 8 | lsb as u16
+    }
 
-                P6502::INS_ASL_ACC => {
-                    let msb: u8 = (self.a & 0b10000000) >> 7;
-                    self.flags.c = msb != 0;
-                    self.a = self.a.wrapping_mul(2);
-                    self.flags.z = self.a == 0;
-                    self.flags.n = (self.a & 0b10000000) != 0;
-                }
-                P6502::INS_ASL_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.shift_left_memory(zp_addr as u16);
-                }
-                P6502::INS_ASL_ZPX => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.shift_left_memory(zp_addr.wrapping_add(self.x) as u16);
-                    self.clock_tick(1);
-                }
+    /// (3 C) Reads a (ADDR) zero page indirect operand (65C02 only) and returns the address.
+    fn zero_page_indirect_addr(&mut self) -> u16 {
+        let indirect: u8 = self.next_byte();
+        self.read_zero_page_word(indirect)
+    }
 
-                P6502::INS_ASL_ABS => {
-                    let addr: u16 = self.next_word();
-                    self.shift_left_memory(addr);
-                }
+    /// Performs the BIT instruction: the zero flag is set from the AND of the accumulator and
+    /// the value, while the negative and overflow flags are copied from its bits 7 and 6.
+    fn test_bits(&mut self, value: u8) {
+        self.flags.z = self.a & value == 0;
+        self.flags.n = (value & 0b10000000) != 0;
+        self.flags.v = (value & 0b01000000) != 0;
+    }
 
-                P6502::INS_ASL_ABX => {
-                    let addr: u16 = self.next_word();
-                    self.shift_left_memory(addr + self.x as u16);
-                    self.clock_tick(1);
-                }
+    /// (3 C) Reads a memory location, writes the value back unmodified (as the 6502 does while
+    /// it is computing the result) and then writes the result of the operation. Returns the
+    /// new value. The 65C02 reads the location again instead of writing it twice.
+    fn read_modify_write<F>(&mut self, addr: u16, operation: F) -> u8
+    where
+        F: FnOnce(&mut Self, u8) -> u8,
+    {
+        let value: u8 = self.read_byte_from_addr(addr);
+        if self.variant == Variant::Cmos65C02 {
+            self.read_byte_from_addr(addr);
+        } else {
+            self.write_byte_to_addr(addr, value);
+        }
+        let new_value: u8 = operation(self, value);
+        self.write_byte_to_addr(addr, new_value);
+        new_value
+    }
 
-                P6502::INS_LSR_ACC => {
-                    self.flags.c = self.a & 1 != 0;
-                    self.a >>= 1;
-                    self.a &= !(1 << 7);
-                    self.flags.z = self.a == 0;
-                    self.flags.n = self.a & (1 << 7) != 0;
-                    self.clock_tick(1);
-                }
+    /// Shifts a value to the left and sets the carry, zero and negative flags.
+    fn shift_left(&mut self, value: u8) -> u8 {
+        self.flags.c = (value & 0b10000000) != 0;
+        let result: u8 = value << 1;
+        self.set_zn_flags(result);
+        result
+    }
 
-                P6502::INS_LSR_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.logical_shift_right_memory(zp_addr as u16);
-                }
+    /// Shifts a value to the right and sets the carry, zero and negative flags.
+    fn shift_right(&mut self, value: u8) -> u8 {
+        self.flags.c = (value & 1) != 0;
+        let result: u8 = value >> 1;
+        self.set_zn_flags(result);
+        result
+    }
 
-                P6502::INS_LSR_ZPX => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.logical_shift_right_memory(zp_addr.wrapping_add(self.x) as u16);
-                    self.clock_tick(1);
-                }
+    /// Rotates a value to the left through the carry and sets the carry, zero and negative flags.
+    fn rotate_left(&mut self, value: u8) -> u8 {
+        let result: u8 = value << 1 | self.flags.c as u8;
+        self.flags.c = (value & 0b10000000) != 0;
+        self.set_zn_flags(result);
+        result
+    }
 
-                P6502::INS_LSR_ABS => {
-                    let addr: u16 = self.next_word();
-                    self.logical_shift_right_memory(addr);
-                }
+    /// Rotates a value to the right through the carry and sets the carry, zero and negative flags.
+    fn rotate_right(&mut self, value: u8) -> u8 {
+        let result: u8 = value >> 1 | (self.flags.c as u8) << 7;
+        self.flags.c = (value & 1) != 0;
+        self.set_zn_flags(result);
+        result
+    }
 
-                P6502::INS_LSR_ABX => {
-                    let addr: u16 = self.next_word();
-                    self.logical_shift_right_memory(addr + self.x as u16);
-                    self.clock_tick(1);
-                }
+    /// Performs the ROR part of the ARR instruction on the result of the AND. Its flags are
+    /// unlike those of any other instruction, even more so in decimal mode.
+    fn and_rotate_right(&mut self, value: u8) {
+        let mut result: u8 = value >> 1 | (self.flags.c as u8) << 7;
 
-                P6502::INS_ROL_ACC => {
-                    let old_carry: bool = self.flags.c;
-                    let new_carry: bool = (self.a & 0b10000000) >> 7 != 0;
-                    self.a = self.a.rotate_left(1);
-                    if old_carry {
-                        self.a |= 1;
-                    } else {
-                        self.a &= 0b11111110;
-                    }
-                    self.flags.c = new_carry;
-                    self.flags.z = self.a == 0;
-                    self.clock_tick(1);
-                }
+        if self.flags.d && self.variant.has_decimal_mode() {
+            self.flags.n = self.flags.c;
+            self.flags.z = result == 0;
+            self.flags.v = (value ^ result) & 0b01000000 != 0;
 
-                P6502::INS_ROL_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.rotate_left_memory(zp_addr as u16);
-                }
+            if (value & 0x0F) + (value & 0x01) > 0x05 {
+                result = (result & 0xF0) | (result.wrapping_add(0x06) & 0x0F);
+            }
+            self.flags.c = (value & 0xF0) as u16 + (value & 0x10) as u16 > 0x50;
+            if self.flags.c {
+                result = result.wrapping_add(0x60);
+            }
+        } else {
+            self.set_zn_flags(result);
+            self.flags.c = result & 0b01000000 != 0;
+            self.flags.v = ((result >> 6) ^ (result >> 5)) & 1 != 0;
+        }
 
-                P6502::INS_ROL_ZPX => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.rotate_left_memory(zp_addr.wrapping_add(self.x) as u16);
-                }
+        self.a = result;
+    }
 
-                P6502::INS_ROL_ABS => {
-                    let addr: u16 = self.next_word();
-                    self.rotate_left_memory(addr);
-                }
+    /// (4-5 C) Performs the store of the unstable SHA, SHX, SHY and TAS instructions: the value
+    /// stored is ANDed with the MSB of the base address plus one. When adding the index crosses
+    /// a page, that value also replaces the MSB of the final address.
+    fn unstable_store(&mut self, opcode: Opcode, value: u8) {
+        let (base, index): (u16, u8) = match opcode.mode {
+            AddressingMode::IndirectIndexed => {
+                let indirect: u8 = self.next_byte();
+                (self.read_zero_page_word(indirect), self.y)
+            }
+            AddressingMode::AbsoluteX => (self.next_word(), self.x),
+            _ => (self.next_word(), self.y),
+        };
+        let addr: u16 = base.wrapping_add(index as u16);
+        self.read_byte_from_addr((base & 0xFF00) | (addr & 0x00FF));
+
+        let stored_value: u8 = value & ((base >> 8) as u8).wrapping_add(1);
+        let final_addr: u16 = if base & 0xFF00 != addr & 0xFF00 {
+            (stored_value as u16) << 8 | (addr & 0x00FF)
+        } else {
+            addr
+        };
+        self.write_byte_to_addr(final_addr, stored_value);
+    }
+    /// (0 C) Performs the addition with carry in the accumulator with another value and
+    /// sets all flags appropriately. When the decimal flag is set, both numbers are treated as
+    /// BCD (Binary Coded Decimal) numbers.
+    fn add_with_carry(&mut self, value: u8) {
+        if self.flags.d && self.variant.has_decimal_mode() {
+            self.decimal_add_with_carry(value);
+            self.fix_decimal_flags();
+        } else {
+            self.a = self.binary_add_with_carry(value);
+        }
+    }
 
-                P6502::INS_ROL_ABX => {
-                    let addr: u16 = self.next_word();
-                    self.rotate_left_memory(addr + self.x as u16);
-                }
+    /// (0 C) Performs the substraction with carry in the accumulator with another value and
+    /// sets all flags appropriately. When the decimal flag is set, both numbers are treated as
+    /// BCD (Binary Coded Decimal) numbers.
+    fn substract_with_carry(&mut self, value: u8) {
+        let decimal_result: u8 = self.decimal_substract_result(value);
+        // The NMOS 6502 sets the flags as in binary mode even when the decimal flag is set.
+        let binary_result: u8 = self.binary_add_with_carry(!value);
+        if self.flags.d && self.variant.has_decimal_mode() {
+            self.a = decimal_result;
+            self.fix_decimal_flags();
+        } else {
+            self.a = binary_result;
+        }
+    }
 
-                P6502::INS_ROR_ACC => {
-                    let old_carry: bool = self.flags.c;
-                    let new_carry: bool = self.a & 1 != 0;
-                    self.a = self.a.rotate_right(1);
-                    if old_carry {
-                        self.a |= 1 << 7;
-                    } else {
-                        self.a &= !(1 << 7);
-                    }
-                    self.flags.z = self.a == 0;
-                    self.flags.c = new_carry;
-                    self.flags.n = self.a & 0b10000000 != 0;
-                }
+    /// (0-1 C) Sets the zero and negative flags from the result of a decimal ADC or SBC, which
+    /// the 65C02 does at the expense of one extra cycle. Does nothing on the NMOS variants.
+    fn fix_decimal_flags(&mut self) {
+        if self.variant == Variant::Cmos65C02 {
+            self.set_zn_flags(self.a);
+            self.read_byte_from_addr(self.pc);
+        }
+    }
 
-                P6502::INS_ROR_ZP0 => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.rotate_right_memory(zp_addr as u16);
-                }
+    /// (0 C) Adds a value and the carry to the accumulator in binary mode, sets all flags and
+    /// returns the result without storing it.
+    fn binary_add_with_carry(&mut self, value: u8) -> u8 {
+        let total_addition: u16 = self.a as u16 + value as u16 + self.flags.c as u16;
+        let result: u8 = total_addition as u8;
 
-                P6502::INS_ROR_ZPX => {
-                    let zp_addr: u8 = self.next_byte();
-                    self.rotate_right_memory(zp_addr.wrapping_add(self.x) as u16);
-                    self.clock_tick(1);
-                }
+        self.flags.c = total_addition > 0xFF;
+        self.flags.z = result == 0;
+        self.flags.n = (result & 0b10000000) > 0;
+        // There is an overflow when both operands have the same sign and the result does not.
+        self.flags.v = (self.a ^ result) & (value ^ result) & 0b10000000 != 0;
 
-                P6502::INS_ROR_ABS => {
-                    let addr: u16 = self.next_word();
-                    self.rotate_right_memory(addr);
-                }
+        result
+    }
 
-                P6502::INS_ROR_ABX => {
-                    let addr: u16 = self.next_word();
-                    self.rotate_right_memory(addr + self.x as u16);
-                    self.clock_tick(1);
-                }
+    /// (0 C) Adds a value and the carry to the accumulator in decimal mode. As in the NMOS
+    /// 6502, the Z flag is set from the binary result, and the N and V flags are taken from the
+    /// intermediate result, before the high nibble is adjusted.
+    fn decimal_add_with_carry(&mut self, value: u8) {
+        let carry: u16 = self.flags.c as u16;
+        let binary_result: u16 = self.a as u16 + value as u16 + carry;
 
-                P6502::INS_SEC => {
-                    self.flags.c = true;
-                    self.clock_tick(1);
-                }
+        let mut low_nibble: u16 = (self.a & 0x0F) as u16 + (value & 0x0F) as u16 + carry;
+        if low_nibble >= 0x0A {
+            low_nibble = ((low_nibble + 0x06) & 0x0F) + 0x10;
+        }
+        let mut result: u16 = (self.a & 0xF0) as u16 + (value & 0xF0) as u16 + low_nibble;
+        let signed_result: i16 =
+            (self.a & 0xF0) as i8 as i16 + (value & 0xF0) as i8 as i16 + low_nibble as i16;
 
-                P6502::INS_SED => {
-                    self.flags.d = true;
-                    self.clock_tick(1);
-                }
+        self.flags.z = binary_result & 0xFF == 0;
+        self.flags.n = result & 0b10000000 != 0;
+        self.flags.v = !(-128..=127).contains(&signed_result);
 
-                P6502::INS_SEI => {
-                    self.flags.i = true;
-                    self.clock_tick(1);
-                }
+        if result >= 0xA0 {
+            result += 0x60;
+        }
+        self.flags.c = result > 0xFF;
+        self.a = result as u8;
+    }
 
-                P6502::INS_CLC => {
-                    self.flags.c = false;
-                    self.clock_tick(1);
-                }
+    /// (0 C) Returns the result of substracting a value and the borrow (the inverted carry)
+    /// from the accumulator in decimal mode. It neither modifies the accumulator nor the flags.
+    fn decimal_substract_result(&self, value: u8) -> u8 {
+        let borrow: i16 = !self.flags.c as i16;
+
+        // The 65C02 adjusts the result differently, which only matters for invalid BCD numbers.
+        if self.variant == Variant::Cmos65C02 {
+            let low_nibble: i16 = (self.a & 0x0F) as i16 - (value & 0x0F) as i16 - borrow;
+            let mut result: i16 = self.a as i16 - value as i16 - borrow;
+            if result < 0 {
+                result -= 0x60;
+            }
+            if low_nibble < 0 {
+                result -= 0x06;
+            }
+            return result as u8;
+        }
 
-                P6502::INS_CLD => {
-                    self.flags.d = false;
-                    self.clock_tick(1);
-                }
+        let mut low_nibble: i16 = (self.a & 0x0F) as i16 - (value & 0x0F) as i16 - borrow;
+        if low_nibble < 0 {
+            low_nibble = ((low_nibble - 0x06) & 0x0F) - 0x10;
+        }
+        let mut result: i16 = (self.a & 0xF0) as i16 - (value & 0xF0) as i16 + low_nibble;
+        if result < 0 {
+            result -= 0x60;
+        }
 
-                P6502::INS_CLI => {
-                    self.flags.i = false;
-                    self.clock_tick(1);
-                }
+        result as u8
+    }
 
-                P6502::INS_CLV => {
-                    self.flags.v = false;
-                    self.clock_tick(1);
-                }
+    /// (1-2 C) Jumps to the address at a signed 8-bit offset from the Program Counter. The
+    /// next opcode is read while the offset is added, and the address with the unfixed MSB is
+    /// read when the jump crosses a page.
+    fn relative_jump(&mut self, offset: u8) {
+        self.read_byte_from_addr(self.pc);
+        let old_pc: u16 = self.pc;
+        // The offset is a signed byte, so it is sign-extended before being added.
+        self.pc = self.pc.wrapping_add(offset as i8 as u16);
 
-                _ => {
-                    panic!("Unhandled opcode detected ${:02x}", instruction);
-                }
-            }
+        if old_pc & 0xFF00 != self.pc & 0xFF00 {
+            self.read_byte_from_addr((old_pc & 0xFF00) | (self.pc & 0x00FF));
         }
     }
-    /// (3 C) Increments the value of a specified memory location. Wrapps when necesary.
-    fn increment_memory(&mut self, addr: u16) {
-        let current_value: u8 = self.read_byte_from_addr(addr as u16);
-        let new_value: u8 = current_value.wrapping_add(1);
-        self.write_byte_to_addr(addr as u16, new_value);
-        self.flags.z = new_value == 0;
-        self.flags.n = (new_value & 0b10000000) > 0;
-        self.clock_tick(1);
-    }
 
-    /// (3 C) Decrements the value of a specified memory location. Wrapps when necesary.
-    fn decrement_memory(&mut self, addr: u16) {
-        let current_value: u8 = self.read_byte_from_addr(addr as u16);
-        let new_value: u8 = current_value.wrapping_sub(1);
-        self.write_byte_to_addr(addr as u16, new_value);
-        self.flags.z = new_value == 0;
-        self.flags.n = (new_value & 0b10000000) > 0;
-        self.clock_tick(1);
+    /// Returns whether a JAM instruction (or STP, on the 65C02) has locked up the CPU.
+    pub fn is_jammed(&self) -> bool {
+        self.jammed
     }
 
-    /// (0 C) Performs the addition with carry in the accumulator with another value and
-    /// sets all flags appropriately.
-    fn add_with_carry(&mut self, value: u8) {
-        let acc_sign: u8 = self.a & 0b10000000;
-        let value_sign: u8 = value & 0b10000000;
-        let mut total_addition: u16 = self.a as u16 + value as u16 + self.flags.c as u16;
-
-        if total_addition < 256 {
-            self.a = total_addition as u8;
-        } else {
-            self.flags.c = true;
-            total_addition -= 256;
-            self.a = total_addition as u8;
-        }
-
-        self.flags.z = self.a == 0;
-        self.flags.n = (self.a & 0b10000000) > 0;
+    /// Sets the level of the IRQ line (`true` means the interrupt is being requested). As in
+    /// the real hardware, the line is level-triggered: the interrupt is serviced before every
+    /// instruction for as long as the line is held and the interrupt mask (`flags.i`) is clear.
+    pub fn irq(&mut self, level: bool) {
+        self.irq_line = level;
+    }
 
-        if acc_sign == value_sign && acc_sign != total_addition as u8 & 0b10000000 {
-            self.flags.v = true;
-        }
+    /// Signals a non-maskable interrupt. The NMI line is edge-triggered, so each call is
+    /// serviced exactly once, before the next instruction and regardless of `flags.i`.
+    pub fn nmi(&mut self) {
+        self.nmi_pending = true;
     }
 
-    /// (0 C) Performs the substraction with carry in the accumulator with another value and
-    /// sets all flags appropriately.
-    fn substract_with_carry(&mut self, value: u8) {
-        let c2value: u8 = !value;
-        self.add_with_carry(c2value);
-    }
-
-    /// (3 C) Rotates to the left by a factor of one the contents of a memory location.
-    fn rotate_left_memory(&mut self, addr: u16) {
-        let mut value: u8 = self.read_byte_from_addr(addr);
-        let old_carry: bool = self.flags.c;
-        let new_carry: bool = (value & 0b10000000) >> 7 != 0;
-        value = value.rotate_left(1);
-        if old_carry {
-            self.a |= 1;
-        } else {
-            self.a &= !1;
+    /// Checks the interrupt lines, both the ones driven through `irq` and `nmi` and the ones
+    /// driven by the devices on the bus, and services the pending interrupt with the highest
+    /// priority, if any.
+    fn poll_interrupts(&mut self) {
+        let nmi_level: bool = self.nmi_level();
+        if nmi_level && !self.nmi_previous {
+            self.nmi_pending = true;
         }
-        self.write_byte_to_addr(addr, value);
-        self.flags.z = value == 0;
-        self.flags.c = new_carry;
-        self.flags.n = value & 0b10000000 != 0;
-        self.clock_tick(1);
-    }
-
-    /// (3 C) Rotates to the right by a factor of one the contents of a memory location.
-    fn rotate_right_memory(&mut self, addr: u16) {
-        let mut value: u8 = self.read_byte_from_addr(addr);
-        let old_carry: bool = self.flags.c;
-        let new_carry: bool = value & 1 != 0;
-        value = value.rotate_right(1);
-        if old_carry {
-            value |= 1 << 7;
+        self.nmi_previous = nmi_level;
+
+        let interrupt: Option<Interrupt> = if self.nmi_pending {
+            self.nmi_pending = false;
+            self.interrupt(P6502::NMI_VECTOR);
+            Some(Interrupt::Nmi)
+        } else if self.irq_level() && !self.flags.i {
+            self.interrupt(P6502::IRQ_VECTOR);
+            Some(Interrupt::Irq)
         } else {
-            value &= !(1 << 7);
-        }
-        self.write_byte_to_addr(addr, value);
-        self.flags.z = value == 0;
-        self.flags.c = new_carry;
-        self.flags.n = value & 0b10000000 != 0;
-        self.clock_tick(1);
-    }
+            None
+        };
 
-    /// (3 C) Shifts left by a factor of two the contents of a specified memory location.
-    fn shift_left_memory(&mut self, addr: u16) {
-        let current_value: u8 = self.read_byte_from_addr(addr);
-        let msb: u8 = (current_value & 0b10000000) >> 7;
-        self.flags.c = msb != 0;
-        let new_value: u8 = current_value.wrapping_mul(2);
-        self.write_byte_to_addr(addr, new_value);
-        self.flags.z = new_value == 0;
-        self.flags.n = (new_value & 0b10000000) != 0;
-        self.clock_tick(1);
+        self.trace.interrupt = interrupt;
     }
 
-    /// (3 C) Performs the LSR on a memory location.
-    fn logical_shift_right_memory(&mut self, addr: u16) {
-        let mut value: u8 = self.read_byte_from_addr(addr);
-        self.flags.c = value & 1 != 0;
-        value >>= 1;
-        value &= !(1 << 7);
-        self.flags.z = value == 0;
-        self.flags.n = value & (1 << 7) != 0;
-        self.write_byte_to_addr(addr, value);
-        self.clock_tick(1);
+    /// (7 C) Performs the interrupt sequence: pushes the Program Counter and the status flags
+    /// (with the B-flag clear), sets the interrupt mask and jumps to the address stored in the
+    /// specified vector.
+    fn interrupt(&mut self, vector: u16) {
+        // The CPU fetches the next opcode twice before discarding it.
+        self.read_byte_from_addr(self.pc);
+        self.read_byte_from_addr(self.pc);
+        self.push_word(self.pc);
+        self.push_byte(self.flags.as_binary() & !0b00010000);
+        self.flags.i = true;
+        self.clear_decimal_on_interrupt();
+        self.pc = self.read_word_from_addr(vector);
     }
 
-    /// Performs a relative jump from a signed 8-bit address.
-    fn relative_jump(&mut self, jump_addr: u8) {
-        let positive_sign: bool = jump_addr & 0b10000000 == 0; // & 1 << 7
-        let old_pc: u16 = self.pc;
-
-        if positive_sign {
-            self.pc += (jump_addr & 0b0111111) as u16;
-        } else {
-            let mut new_addr: u8 = jump_addr & 0b011111111; // !(1 << 7)
-            new_addr = !new_addr + 1;
-            self.pc -= new_addr as u16;
+    /// Clears the decimal flag when entering an interrupt handler, as the 65C02 does. The
+    /// NMOS variants leave it untouched.
+    fn clear_decimal_on_interrupt(&mut self) {
+        if self.variant == Variant::Cmos65C02 {
+            self.flags.d = false;
         }
-
-        // Check if the old page and the new one match.
-        if old_pc & 0xFF00 != self.pc & 0xFF00 {
-            self.clock_tick(2);
-        } else {
-            self.clock_tick(1);
-        }
-    }
-    /// (2 C) Parses a (ADDR),Y indirect indexing operand.
-    fn parse_indirect_indexing_addr(&mut self, indirect: u8) -> u16 {
-        let indirect_value: u8 = self.read_byte_from_addr(indirect as u16);
-        let lsb: u8 = self.y.wrapping_add(indirect_value);
-        let carry: bool = self.y as u16 + indirect_value as u16 > 255;
-        let msb: u8 = self
-            .read_byte_from_addr((indirect as u16).wrapping_add(1))
-            .wrapping_add(carry as u8);
-        (msb as u16) << 8 | lsb as u16
     }
 
-    /// (3 C) Parses a (ADDR,X) indexed indirect operand.
-    fn parse_indexed_indirect_addr(&mut self, indirect_addr: u8) -> u16 {
-        self.clock_tick(1);
-        let addr: u16 = self.x.wrapping_add(indirect_addr) as u16;
-        self.read_word_from_addr(addr)
+    /// Returns whether any interrupt line is active, regardless of the interrupt mask. Used to
+    /// wake the CPU up from a WAI instruction.
+    fn interrupt_requested(&self) -> bool {
+        self.nmi_pending || (self.nmi_level() && !self.nmi_previous) || self.irq_level()
     }
 
     /// Keeps track of the CPU's clock ticks.
     fn clock_tick(&mut self, ticks: usize) {
         self.cycles += ticks;
+        self.memory.tick(ticks);
     }
 
     /// Sets the memory to another
-    pub fn set_memory(&mut self, memory: Memory) {
+    pub fn set_memory(&mut self, memory: B) {
         self.memory = memory;
     }
 
     /* Stack operations */
 
-    /// Pushes one byte onto the stack and decrements the Stack Pointer.
+    /// (1 C) Pushes one byte onto the stack and decrements the Stack Pointer.
     fn push_byte(&mut self, data: u8) {
         self.write_byte_to_addr(P6502::STACK_PAGE + self.sp as u16, data);
-        self.clock_tick(1);
-        self.sp = self.sp.saturating_sub(1);
+        self.sp = self.sp.wrapping_sub(1);
     }
 
-    /// Pulls one byte from the stack and increments the Stack Pointer.
+    /// (1 C) Increments the Stack Pointer and pulls one byte from the stack.
     fn pull_byte(&mut self) -> u8 {
-        self.sp = self.sp.saturating_add(1);
-        let value: u8 = self.read_byte_from_addr(P6502::STACK_PAGE + self.sp as u16);
-        self.clock_tick(1);
-        value
+        self.sp = self.sp.wrapping_add(1);
+        self.read_byte_from_addr(P6502::STACK_PAGE + self.sp as u16)
     }
 
-    /// Pushes two bytes onto the stack (the MSB first, then the LSB) and
+    /// (2 C) Pushes two bytes onto the stack (the MSB first, then the LSB) and
     /// decrements two times the Stack Pointer.
     fn push_word(&mut self, data: u16) {
         let msb: u8 = ((data & 0xFF00) >> 8) as u8;
@@ -1518,7 +2030,7 @@ impl P6502 {
         self.push_byte(lsb);
     }
 
-    /// Pulls two bytes from the stack (the LSB first, then the MSB) and
+    /// (2 C) Pulls two bytes from the stack (the LSB first, then the MSB) and
     /// increments two times the Stack Pointer.
     fn pull_word(&mut self) -> u16 {
         let lsb: u8 = self.pull_byte();
@@ -1529,16 +2041,16 @@ impl P6502 {
 
     /* Instruction fetching */
 
-    /// Reads the next byte from memory and increments one time the Program Counter.
+    /// (1 C) Reads the next byte from memory and increments one time the Program Counter.
     fn next_byte(&mut self) -> u8 {
         let data: u8 = self.read_byte_from_addr(self.pc);
-        self.pc = self.pc.saturating_add(1);
+        self.pc = self.pc.wrapping_add(1);
         data
     }
-    /// Reads the next two bytes from memory and increments two times the Program Counter.
+    /// (2 C) Reads the next two bytes from memory and increments two times the Program Counter.
     fn next_word(&mut self) -> u16 {
         let data: u16 = self.read_word_from_addr(self.pc);
-        self.pc = self.pc.saturating_add(2);
+        self.pc = self.pc.wrapping_add(2);
         data
     }
 
@@ -1546,154 +2058,94 @@ impl P6502 {
 
     /// (1 C) Reads and returns the content of the specified memory location.
     fn read_byte_from_addr(&mut self, addr: u16) -> u8 {
-        let data: u8 = self.memory.read(addr as usize);
-        self.clock_tick(1);
-        data
+        match self.next_bus_cycle() {
+            BusCycle::Replayed(data) => data,
+            BusCycle::Skipped => 0,
+            BusCycle::Real => {
+                let data: u8 = match addr {
+                    0x0000 if self.variant == Variant::Mos6510 => self.io_port.direction,
+                    0x0001 if self.variant == Variant::Mos6510 => self.io_port.lines(),
+                    _ => self.memory.read(addr),
+                };
+                self.record_bus_cycle(addr, data, false);
+                self.clock_tick(1);
+                data
+            }
+        }
     }
 
     /// (2 C) Reads and returns as one 16-bit number the contents of two
     /// contiguous memory locations
     fn read_word_from_addr(&mut self, addr: u16) -> u16 {
-        let mut data: u16 = self.memory.read(addr as usize) as u16;
-        //self.pc = self.pc.saturating_add(1);
-        data |= (self.memory.read((addr + 1) as usize) as u16) << 8;
-
-        self.clock_tick(2);
-        data
+        let lsb: u8 = self.read_byte_from_addr(addr);
+        let msb: u8 = self.read_byte_from_addr(addr.wrapping_add(1));
+        (msb as u16) << 8 | lsb as u16
     }
 
     /// (1 C) Writes one byte to the specified memory location.
     fn write_byte_to_addr(&mut self, addr: u16, data: u8) {
-        self.memory.write(addr as usize, data);
-        self.clock_tick(1);
-    }
-    /// Runs an interactive prompt once the program is finished.
-    pub fn interactive(&self) {
-        let mut copper: Copperline = Copperline::new();
-
-        loop {
-            let line = copper.read_line_utf8("]] ");
-            match line {
-                Ok(contents) => {
-                    let line_as_vec: Vec<&str> = contents.split_whitespace().collect();
-
-                    match *line_as_vec.get(0).unwrap_or(&"") {
-                        "" => {}
-                        "mem" | "memory" => {
-                            let start: usize =
-                                usize::from_str_radix(line_as_vec.get(1).unwrap_or(&"0"), 16)
-                                    .unwrap();
-                            let end: usize =
-                                usize::from_str_radix(line_as_vec.get(2).unwrap_or(&"0"), 16)
-                                    .unwrap();
-                            println!("Listing memory from ${:x} to ${:x}:", start, end);
-
-                            self.memory.monitor(start, end);
-                        }
-
-                        "clear" => copper.clear_screen().unwrap(),
-                        "help" => {
-                            println!("Refer to the 'Using the interactive prompt' in the README file for more help. Basic commands are:");
-                            println!("\tmonitor START [END]: lists the memory contents of the specified addresses.");
-                            println!("\tstatus: outputs the values stored in the processor's registers, the status flags and the program counter.");
-                            println!("\tclear: clears the screen.");
-                            println!("\texit | quit: terminates this utility.");
-                        }
-                        "status" | "stat" => println!("{}", self.status()),
-                        "exit" | "quit" => break,
-                        _ => {}
-                    }
-                }
-
-                Err(err) => match err {
-                    copperline::Error::Cancel | copperline::Error::EndOfFile => {
-                        println!("Type \"exit\" or \"quit\" to exit.");
+        let cycle: BusCycle = self.next_bus_cycle();
+
+        // The I/O port is inside the CPU, so it is written again whenever `tick` replays the
+        // access.
+        match addr {
+            0x0000 if self.variant == Variant::Mos6510 => self.io_port.direction = data,
+            0x0001 if self.variant == Variant::Mos6510 => self.io_port.data = data,
+            _ => {
+                if let BusCycle::Real = cycle {
+                    if let Some(overwritten) = &mut self.trace.overwritten {
+                        overwritten.push((addr, self.memory.peek(addr)));
                     }
-                    _ => {}
+                    self.memory.write(addr, data);
                 }
             }
         }
-    }
-
-    /// (0 C) Resets the CPU by:
-    /// - Erasing the memory (all memory is set to `0_u8`).
-    /// - Setting the Program Counter to the default program start address.
-    /// - Setting the Stack Pointer to $FF (initial position).
-    /// - Setting all flags to zero (boolean false) except for the B-flag which is set to `true`.
-    pub fn reset(&mut self) {
-        self.pc = Self::PROGRAM_START;
-        self.flags = P6502Flags::default();
-        self.flags.b = true;
-        self.sp = 0xFF;
-        self.memory.clear();
-    }
-}
-
-/// Represents the computer's memory. Consistists only of an `u8` slice of fixed length
-/// and equal to 64 K (= 65536 = $10000).
-pub struct Memory {
-    pub data: [u8; Memory::MAX_SIZE],
-}
-
-impl Default for Memory {
-    fn default() -> Self {
-        Self {
-            data: [0_u8; Self::MAX_SIZE],
-        }
-    }
-}
-
-impl Memory {
-    const MAX_SIZE: usize = 64 * 1024;
 
-    /// Clears the memory by setting all the elements of the slice to `0_u8`.
-    pub fn clear(&mut self) {
-        self.data = [0_u8; Memory::MAX_SIZE];
-    }
-
-    /// Writes a byte to the specified memory address.
-    pub fn write(&mut self, addr: usize, data: u8) {
-        if addr < Memory::MAX_SIZE {
-            self.data[addr] = data;
-        }
-    }
-
-    /// Reads a byte from the specified memory address and returns the value.
-    pub fn read(&self, addr: usize) -> u8 {
-        if addr < Memory::MAX_SIZE {
-            self.data[addr]
-        } else {
-            0
+        if let BusCycle::Real = cycle {
+            self.record_bus_cycle(addr, data, true);
+            self.clock_tick(1);
         }
     }
 
-    pub fn from_program_vec(program: Vec<u8>) -> Self {
-        let mut memory: Self = Self::default();
-
-        for (index, value) in program.iter().enumerate() {
-            memory.write(P6502::PROGRAM_START as usize + index, *value);
+    /// (7 C) Resets the CPU as the RES line does. The memory is left intact. The reset:
+    /// - Performs two dummy reads of the next instruction and three dummy reads of the stack,
+    ///   which leave the Stack Pointer at $FD.
+    /// - Sets the interrupt mask and the B-flag, and clears the decimal flag on the 65C02. The
+    ///   other flags keep their values.
+    /// - Loads the Program Counter from the reset vector ($FFFC - $FFFD).
+    /// - Turns every line of the 6510's I/O port into an input.
+    pub fn reset(&mut self) {
+        self.cycle_step = None;
+        self.error = None;
+        self.nmi_pending = false;
+        self.jammed = false;
+        self.waiting = false;
+        self.io_port.direction = 0;
+        self.read_byte_from_addr(self.pc);
+        self.read_byte_from_addr(self.pc);
+
+        self.sp = 0x00;
+        for _ in 0..3 {
+            self.read_byte_from_addr(P6502::STACK_PAGE + self.sp as u16);
+            self.sp = self.sp.wrapping_sub(1);
         }
 
-        memory
+        self.flags.b = true;
+        self.flags.i = true;
+        self.clear_decimal_on_interrupt();
+        self.pc = self.read_word_from_addr(P6502::RESET_VECTOR);
     }
 
-    /// Prints to stdout the contents of an specified memory area in hexadecimal format.
-    pub fn monitor(&self, start: usize, end: usize) {
-        if end == 0 {
-            print!("${:04x}: ", start);
-            for j in 0..16 as usize {
-                print!("{:02x} ", self.data[start + j]);
-            }
-        } else {
-            for i in (start..end).step_by(16) {
-                print!("${:04x}: ", i);
-                for j in 0..16 as usize {
-                    print!("{:02x} ", self.data[i + j]);
-                }
-                println!("");
-            }
-        }
-
-        println!("");
+    /// Simulates turning the computer on: erases the RAM (all of it is set to `0_u8`), clears
+    /// the registers and the flags and resets the CPU.
+    pub fn power_on(&mut self) {
+        self.memory.clear();
+        self.a = 0;
+        self.x = 0;
+        self.y = 0;
+        self.pc = 0;
+        self.flags = P6502Flags::default();
+        self.cycles = 0;
+        self.reset();
     }
 }
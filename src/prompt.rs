@@ -0,0 +1,448 @@
+//! The interactive prompt of the command line tool. Requires the `std` feature.
+
+use copperline::Copperline;
+
+use crate::bus::Bus;
+use crate::debugger::{Condition, Debugger, Register, Stop, Watch};
+use crate::disassembler::{self, Instruction, Symbols};
+use crate::error::Result;
+use crate::gdb;
+use crate::p6502::{StepStatus, P6502};
+use crate::savestate::SaveState;
+
+/// How many instructions `disasm` shows when no end address is given.
+const DISASM_LINES: usize = 16;
+
+/// Where `gdb` waits for GDB when no address is given.
+pub const GDB_ADDRESS: &str = "127.0.0.1:6502";
+
+impl<B: Bus> P6502<B> {
+    /// Runs an interactive prompt to inspect the CPU and the memory and to debug the program,
+    /// either before it runs or once it is finished. The disassembler and the debugger name the
+    /// addresses found in `symbols`.
+    pub fn interactive(&mut self, symbols: &Symbols) {
+        let mut copper: Copperline = Copperline::new();
+        let mut debugger: Debugger = Debugger::new();
+
+        loop {
+            let line = copper.read_line_utf8("]] ");
+            match line {
+                Ok(contents) => {
+                    let line_as_vec: Vec<&str> = contents.split_whitespace().collect();
+
+                    match *line_as_vec.first().unwrap_or(&"") {
+                        "" => {}
+                        "mem" | "memory" => {
+                            let start: usize =
+                                usize::from_str_radix(line_as_vec.get(1).unwrap_or(&"0"), 16)
+                                    .unwrap();
+                            let end: usize =
+                                usize::from_str_radix(line_as_vec.get(2).unwrap_or(&"0"), 16)
+                                    .unwrap();
+                            println!("Listing memory from ${:x} to ${:x}:", start, end);
+
+                            monitor(&self.memory, start, end);
+                        }
+
+                        "disasm" | "d" => {
+                            match (
+                                line_as_vec.get(1).map(|text| parse_address(text)),
+                                line_as_vec.get(2).map(|text| parse_address(text)),
+                            ) {
+                                (Some(Some(start)), None) => {
+                                    let mut address: u16 = start;
+                                    for _ in 0..DISASM_LINES {
+                                        let instruction: Instruction =
+                                            disassembler::disassemble_instruction(
+                                                &self.memory,
+                                                self.variant(),
+                                                address,
+                                                symbols,
+                                            );
+                                        address = instruction.next_address();
+                                        print_instruction(&instruction);
+                                    }
+                                }
+                                (Some(Some(start)), Some(Some(end))) => {
+                                    for instruction in disassembler::disassemble(
+                                        &self.memory,
+                                        self.variant(),
+                                        start,
+                                        end,
+                                        symbols,
+                                    ) {
+                                        print_instruction(&instruction);
+                                    }
+                                }
+                                _ => println!("Usage: disasm START [END], in hexadecimal."),
+                            }
+                        }
+
+                        "step" | "s" => {
+                            let count: usize = line_as_vec
+                                .get(1)
+                                .and_then(|text| text.parse().ok())
+                                .unwrap_or(1);
+                            let mut result: Result<Stop> = Ok(Stop::Done);
+                            for _ in 0..count {
+                                result = debugger.step(self);
+                                if result.as_ref().ok() != Some(&Stop::Done) {
+                                    break;
+                                }
+                            }
+                            self.report(result, symbols);
+                        }
+                        "next" | "n" => {
+                            let result: Result<Stop> = debugger.next(self);
+                            self.report(result, symbols);
+                        }
+                        "finish" | "f" => {
+                            let result: Result<Stop> = debugger.finish(self);
+                            self.report(result, symbols);
+                        }
+                        "continue" | "c" => {
+                            let result: Result<Stop> = debugger.resume(self);
+                            self.report(result, symbols);
+                        }
+                        "step-back" | "sb" => {
+                            let count: usize = line_as_vec
+                                .get(1)
+                                .and_then(|text| text.parse().ok())
+                                .unwrap_or(1);
+                            let mut stop: Stop = Stop::Done;
+                            for _ in 0..count {
+                                stop = debugger.step_back(self);
+                                if stop != Stop::Done {
+                                    break;
+                                }
+                            }
+                            self.report(Ok(stop), symbols);
+                        }
+                        "reverse-continue" | "rc" => {
+                            let stop: Stop = debugger.reverse_resume(self);
+                            self.report(Ok(stop), symbols);
+                        }
+
+                        "break" | "b" => {
+                            let address: Option<u16> = line_as_vec
+                                .get(1)
+                                .and_then(|text| parse_location(text, symbols));
+                            let condition: Option<&str> =
+                                contents.split_once(" if ").map(|(_, condition)| condition);
+
+                            match (
+                                address,
+                                condition.map(|text| Condition::parse(text, symbols)),
+                            ) {
+                                (Some(address), None) => {
+                                    let id: usize = debugger.add_breakpoint(address, None);
+                                    println!("Breakpoint {} at ${:04X}.", id, address);
+                                }
+                                (Some(address), Some(Ok(condition))) => {
+                                    let id: usize =
+                                        debugger.add_breakpoint(address, Some(condition));
+                                    println!(
+                                        "Breakpoint {} at ${:04X}, when the condition holds.",
+                                        id, address
+                                    );
+                                }
+                                (Some(_), Some(Err(error))) => {
+                                    println!("Invalid condition: {}.", error)
+                                }
+                                (None, _) => println!("Usage: break ADDRESS [if CONDITION]."),
+                            }
+                        }
+                        "watch" | "w" => {
+                            let watch: Option<Watch> = match line_as_vec.get(1) {
+                                Some(&"read") | Some(&"r") => Some(Watch::Read),
+                                Some(&"write") | Some(&"w") => Some(Watch::Write),
+                                Some(&"access") | Some(&"a") => Some(Watch::Access),
+                                Some(&"exec") | Some(&"x") => Some(Watch::Execute),
+                                _ => None,
+                            };
+                            let range: Option<(u16, u16)> =
+                                line_as_vec
+                                    .get(2)
+                                    .and_then(|text| match text.split_once('-') {
+                                        Some((start, end)) => parse_location(start, symbols)
+                                            .zip(parse_location(end, symbols)),
+                                        None => {
+                                            parse_location(text, symbols).map(|addr| (addr, addr))
+                                        }
+                                    });
+                            let value: Option<Option<u8>> = match line_as_vec.get(3) {
+                                Some(text) => parse_byte(text).map(Some),
+                                None => Some(None),
+                            };
+
+                            match (watch, range, value) {
+                                (Some(watch), Some((start, end)), Some(value)) if start <= end => {
+                                    let id: usize =
+                                        debugger.add_watchpoint(start..=end, watch, value);
+                                    println!("Watchpoint {} on ${:04X}-${:04X}.", id, start, end);
+                                }
+                                _ => println!(
+                                    "Usage: watch read|write|access|exec START[-END] [VALUE]."
+                                ),
+                            }
+                        }
+                        "delete" => match line_as_vec.get(1).and_then(|text| text.parse().ok()) {
+                            Some(id) if debugger.remove_breakpoint(id) => {
+                                println!("Breakpoint {} deleted.", id)
+                            }
+                            Some(id) if debugger.remove_watchpoint(id) => {
+                                println!("Watchpoint {} deleted.", id)
+                            }
+                            Some(id) => println!("There is no breakpoint or watchpoint {}.", id),
+                            None => println!("Usage: delete ID."),
+                        },
+                        "enable" | "disable" => {
+                            let enabled: bool = line_as_vec[0] == "enable";
+                            match line_as_vec.get(1).and_then(|text| text.parse().ok()) {
+                                Some(id)
+                                    if debugger.enable_breakpoint(id, enabled)
+                                        || debugger.enable_watchpoint(id, enabled) => {}
+                                Some(id) => {
+                                    println!("There is no breakpoint or watchpoint {}.", id)
+                                }
+                                None => println!("Usage: {} ID.", line_as_vec[0]),
+                            }
+                        }
+                        "breaks" => {
+                            if debugger.breakpoints().is_empty()
+                                && debugger.watchpoints().is_empty()
+                            {
+                                println!("There are no breakpoints.");
+                            }
+                            for breakpoint in debugger.breakpoints() {
+                                print!("{}: ${:04X}", breakpoint.id, breakpoint.address);
+                                if let Some(condition) = &breakpoint.condition {
+                                    print!(" if {}", condition.text());
+                                }
+                                if !breakpoint.enabled {
+                                    print!(" (disabled)");
+                                }
+                                println!();
+                            }
+                            for watchpoint in debugger.watchpoints() {
+                                print!(
+                                    "{}: {:?} ${:04X}-${:04X}",
+                                    watchpoint.id,
+                                    watchpoint.watch,
+                                    watchpoint.range.start(),
+                                    watchpoint.range.end()
+                                );
+                                if let Some(value) = watchpoint.value {
+                                    print!(" = ${:02X}", value);
+                                }
+                                if !watchpoint.enabled {
+                                    print!(" (disabled)");
+                                }
+                                println!();
+                            }
+                        }
+
+                        "set" => match (
+                            line_as_vec.get(1).and_then(|text| Register::parse(text)),
+                            line_as_vec.get(2).and_then(|text| parse_address(text)),
+                        ) {
+                            (Some(register), Some(value)) => {
+                                register.set(self, value);
+                                debugger.clear_history();
+                                println!("{}", self.status());
+                            }
+                            _ => println!(
+                                "Usage: set REGISTER VALUE, with the value in hexadecimal."
+                            ),
+                        },
+                        "poke" => {
+                            let address: Option<u16> = line_as_vec
+                                .get(1)
+                                .and_then(|text| parse_location(text, symbols));
+                            let values: Option<Vec<u8>> = line_as_vec
+                                .iter()
+                                .skip(2)
+                                .map(|text| {
+                                    u8::from_str_radix(text.trim_start_matches('$'), 16).ok()
+                                })
+                                .collect();
+
+                            match (address, values) {
+                                (Some(address), Some(values)) if !values.is_empty() => {
+                                    for (offset, value) in values.iter().enumerate() {
+                                        self.memory
+                                            .poke(address.wrapping_add(offset as u16), *value);
+                                    }
+                                    debugger.clear_history();
+                                }
+                                _ => println!("Usage: poke ADDRESS VALUE..., in hexadecimal."),
+                            }
+                        }
+                        "gdb" => {
+                            let address: &str = line_as_vec.get(1).unwrap_or(&GDB_ADDRESS);
+                            println!("Waiting for GDB to connect to {}...", address);
+                            match gdb::listen(self, &mut debugger, address) {
+                                Ok(()) => println!("GDB detached."),
+                                Err(error) => println!("The GDB session failed: {}.", error),
+                            }
+                            self.report(Ok(Stop::Done), symbols);
+                        }
+                        "save" => match line_as_vec.get(1) {
+                            Some(path) => {
+                                match self.save_state().and_then(|state| state.write_file(path)) {
+                                    Ok(()) => println!("State saved to '{}'.", path),
+                                    Err(error) => println!("Unable to save the state: {}.", error),
+                                }
+                            }
+                            None => println!("Usage: save FILE."),
+                        },
+                        "load" => match line_as_vec.get(1) {
+                            Some(path) => match SaveState::read_file(path)
+                                .and_then(|state| self.load_state(&state))
+                            {
+                                Ok(()) => {
+                                    debugger.clear_history();
+                                    self.report(Ok(Stop::Done), symbols);
+                                }
+                                Err(error) => println!("Unable to load the state: {}.", error),
+                            },
+                            None => println!("Usage: load FILE."),
+                        },
+                        "reset" => {
+                            self.reset();
+                            debugger.clear_history();
+                            self.report(Ok(Stop::Done), symbols);
+                        }
+
+                        "clear" => copper.clear_screen().unwrap(),
+                        "help" => {
+                            println!("Refer to the 'Using the interactive prompt' in the README file for more help. Basic commands are:");
+                            println!("\tmonitor START [END]: lists the memory contents of the specified addresses.");
+                            println!("\tdisasm START [END]: disassembles the code between the specified addresses.");
+                            println!("\tstep [N] | next | finish | continue: executes one or N instructions, a whole subroutine, the rest of the subroutine or the program until a breakpoint.");
+                            println!("\tstep-back [N] | reverse-continue: undoes the last one or N instructions, or goes back to the previous breakpoint or watchpoint.");
+                            println!("\tbreak ADDRESS [if CONDITION] | delete ID | enable ID | disable ID | breaks: manages the breakpoints and the watchpoints.");
+                            println!("\twatch read|write|access|exec START[-END] [VALUE]: stops the execution when the memory is accessed.");
+                            println!("\tset REGISTER VALUE | poke ADDRESS VALUE...: changes a register or the memory.");
+                            println!("\tgdb [ADDRESS]: waits for GDB to connect (by default, to {}) and lets it debug the program.", GDB_ADDRESS);
+                            println!("\tsave FILE | load FILE: saves the state of the processor and the memory to a file, or restores it.");
+                            println!("\treset: resets the processor.");
+                            println!("\tstatus: outputs the values stored in the processor's registers, the status flags and the program counter.");
+                            println!("\tclear: clears the screen.");
+                            println!("\texit | quit: terminates this utility.");
+                        }
+                        "status" | "stat" => println!("{}", self.status()),
+                        "exit" | "quit" => break,
+                        _ => {}
+                    }
+                }
+
+                Err(err) => match err {
+                    copperline::Error::Cancel | copperline::Error::EndOfFile => {
+                        println!("Type \"exit\" or \"quit\" to exit.");
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+}
+
+impl<B: Bus> P6502<B> {
+    /// Tells why the debugger stopped and shows the next instruction.
+    fn report(&self, result: Result<Stop>, symbols: &Symbols) {
+        match result {
+            Ok(Stop::Done) => {}
+            Ok(Stop::Breakpoint(id)) => println!("Breakpoint {} reached.", id),
+            Ok(Stop::Watchpoint { id, pc, access }) if pc == self.pc && access.addr == pc => {
+                println!("Watchpoint {}: about to execute ${:04X}.", id, pc)
+            }
+            Ok(Stop::Watchpoint { id, pc, access }) if pc == self.pc => {
+                // Going backwards, the instruction has been undone.
+                println!(
+                    "Watchpoint {}: the next instruction {} ${:02X} {} ${:04X}.",
+                    id,
+                    if access.write { "writes" } else { "reads" },
+                    access.data,
+                    if access.write { "to" } else { "from" },
+                    access.addr
+                )
+            }
+            Ok(Stop::Watchpoint { id, pc, access }) => {
+                println!(
+                    "Watchpoint {}: the instruction at ${:04X} {} ${:02X} {} ${:04X}:",
+                    id,
+                    pc,
+                    if access.write { "wrote" } else { "read" },
+                    access.data,
+                    if access.write { "to" } else { "from" },
+                    access.addr
+                );
+                print_instruction(&disassembler::disassemble_instruction(
+                    &self.memory,
+                    self.variant(),
+                    pc,
+                    symbols,
+                ));
+                println!("The next instruction is:");
+            }
+            Ok(Stop::Halted(StepStatus::Stopped)) => println!("The processor executed a STP."),
+            Ok(Stop::Halted(StepStatus::Waiting)) => {
+                println!("The processor is waiting for an interrupt.")
+            }
+            Ok(Stop::Halted(_)) => println!("The program finished."),
+            Ok(Stop::HistoryStart) => println!("There is no earlier instruction in the history."),
+            Err(error) => println!("The execution stopped: {}.", error),
+        }
+
+        print_instruction(&disassembler::disassemble_instruction(
+            &self.memory,
+            self.variant(),
+            self.pc,
+            symbols,
+        ));
+    }
+}
+
+/// Prints a disassembled instruction, preceded by its label if it has one.
+fn print_instruction(instruction: &Instruction) {
+    if let Some(label) = &instruction.label {
+        println!("{}:", label);
+    }
+    println!("{}", instruction);
+}
+
+/// Parses an address in hexadecimal, optionally prefixed by `$`.
+fn parse_address(text: &str) -> Option<u16> {
+    u16::from_str_radix(text.trim_start_matches('$'), 16).ok()
+}
+
+/// Parses a byte in hexadecimal, optionally prefixed by `$`.
+fn parse_byte(text: &str) -> Option<u8> {
+    u8::from_str_radix(text.trim_start_matches('$'), 16).ok()
+}
+
+/// Parses the name of a symbol or an address in hexadecimal.
+fn parse_location(text: &str, symbols: &Symbols) -> Option<u16> {
+    symbols.find(text).or_else(|| parse_address(text))
+}
+
+/// Prints to stdout the contents of an specified memory area in hexadecimal format.
+pub fn monitor<B: Bus + ?Sized>(bus: &B, start: usize, end: usize) {
+    if end == 0 {
+        print!("${:04x}: ", start);
+        for j in 0..16 {
+            print!("{:02x} ", bus.peek((start + j) as u16));
+        }
+    } else {
+        for i in (start..end).step_by(16) {
+            print!("${:04x}: ", i);
+            for j in 0..16 {
+                print!("{:02x} ", bus.peek((i + j) as u16));
+            }
+            println!();
+        }
+    }
+
+    println!();
+}
@@ -0,0 +1,255 @@
+//! Save states: the whole state of a CPU and its bus (the registers, the flags, the cycles
+//! elapsed, the memory and the devices), which can be kept in memory or written to a file and
+//! restored later.
+//!
+//! The format is a compact binary one, in little-endian order:
+//!
+//! | Size | Contents                                                                  |
+//! |------|---------------------------------------------------------------------------|
+//! | 4    | `KW65`                                                                    |
+//! | 1    | The version of the format, currently 1                                    |
+//! | 1    | The variant: 0 (6502), 1 (65C02), 2 (2A03) or 3 (6510)                    |
+//! | 2    | PC                                                                        |
+//! | 5    | SP, A, X, Y and the flags                                                 |
+//! | 8    | The cycles elapsed                                                        |
+//! | 5    | Whether the CPU is jammed or waiting, the IRQ line and the NMI edge state |
+//! | 3    | The I/O port: direction, data and input                                   |
+//! | 4    | The size of the state of the bus                                          |
+//! | ...  | The state of the bus, as saved by `Bus::save_state`                       |
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use crate::bus::Bus;
+use crate::error::{Error, Result};
+use crate::p6502::{IoPort, P6502Flags, Variant, P6502};
+
+/// The first bytes of every save state.
+const MAGIC: &[u8; 4] = b"KW65";
+
+/// The version of the format written.
+pub const VERSION: u8 = 1;
+
+/// A snapshot of a CPU and its bus.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SaveState {
+    data: Vec<u8>,
+}
+
+impl SaveState {
+    /// Checks the header of a save state read from elsewhere.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self> {
+        let mut reader: Reader = Reader::new(&data);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(Error::SaveState("not a kw6502 save state".into()));
+        }
+        let version: u8 = reader.byte()?;
+        if version != VERSION {
+            return Err(Error::SaveState(format!(
+                "version {} is not supported",
+                version
+            )));
+        }
+
+        Ok(Self { data })
+    }
+
+    /// Returns the save state in its binary format.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Writes the save state to a file. Requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn write_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, &self.data)?;
+        Ok(())
+    }
+
+    /// Reads a save state from a file. Requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn read_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        Self::from_bytes(std::fs::read(path)?)
+    }
+}
+
+impl<B: Bus> P6502<B> {
+    /// Takes a snapshot of the CPU and its bus. Fails in the middle of an instruction executed
+    /// by `tick`.
+    pub fn save_state(&self) -> Result<SaveState> {
+        if self.mid_instruction() {
+            return Err(Error::SaveState(
+                "the CPU is in the middle of an instruction".into(),
+            ));
+        }
+
+        let mut data: Vec<u8> = MAGIC.to_vec();
+        data.push(VERSION);
+        data.push(variant_number(self.variant()));
+        data.extend_from_slice(&self.pc.to_le_bytes());
+        data.extend_from_slice(&[self.sp, self.a, self.x, self.y, self.flags.as_binary()]);
+        data.extend_from_slice(&(self.cycles as u64).to_le_bytes());
+        data.extend_from_slice(&[
+            self.jammed as u8,
+            self.waiting as u8,
+            self.irq_line as u8,
+            self.nmi_pending as u8,
+            self.nmi_previous as u8,
+        ]);
+        data.extend_from_slice(&[
+            self.io_port.direction,
+            self.io_port.data,
+            self.io_port.input,
+        ]);
+
+        let mut bus_state: Vec<u8> = Vec::new();
+        self.memory.save_state(&mut bus_state);
+        data.extend_from_slice(&(bus_state.len() as u32).to_le_bytes());
+        data.extend_from_slice(&bus_state);
+
+        Ok(SaveState { data })
+    }
+
+    /// Restores a snapshot taken from a CPU of the same variant with a similar bus. If the bus
+    /// rejects its part, the CPU is left untouched but the bus may be partially restored.
+    pub fn load_state(&mut self, state: &SaveState) -> Result<()> {
+        let mut reader: Reader = Reader::new(&state.data);
+        reader.take(MAGIC.len() + 1)?;
+
+        let variant: u8 = reader.byte()?;
+        if variant != variant_number(self.variant()) {
+            return Err(Error::SaveState(format!(
+                "it was saved from a {:?} CPU, not a {:?} one",
+                variant_from_number(variant)?,
+                self.variant()
+            )));
+        }
+
+        let pc: u16 = reader.word()?;
+        let registers: &[u8] = reader.take(5)?;
+        let (sp, a, x, y, flags): (u8, u8, u8, u8, u8) = (
+            registers[0],
+            registers[1],
+            registers[2],
+            registers[3],
+            registers[4],
+        );
+        let cycles: u64 = reader.u64()?;
+        let lines: &[u8] = reader.take(5)?;
+        let (jammed, waiting, irq_line, nmi_pending, nmi_previous): (bool, bool, bool, bool, bool) = (
+            lines[0] != 0,
+            lines[1] != 0,
+            lines[2] != 0,
+            lines[3] != 0,
+            lines[4] != 0,
+        );
+        let port: &[u8] = reader.take(3)?;
+        let io_port: IoPort = IoPort {
+            direction: port[0],
+            data: port[1],
+            input: port[2],
+        };
+        let bus_size: usize = reader.u32()? as usize;
+        let bus_state: &[u8] = reader.take(bus_size)?;
+        reader.finish()?;
+
+        self.memory.load_state(bus_state)?;
+        self.pc = pc;
+        self.sp = sp;
+        self.a = a;
+        self.x = x;
+        self.y = y;
+        self.flags = P6502Flags::from_binary(flags);
+        self.cycles = cycles as usize;
+        self.jammed = jammed;
+        self.waiting = waiting;
+        self.irq_line = irq_line;
+        self.nmi_pending = nmi_pending;
+        self.nmi_previous = nmi_previous;
+        self.io_port = io_port;
+        self.error = None;
+
+        Ok(())
+    }
+}
+
+/// Returns the number of a variant in the save states.
+fn variant_number(variant: Variant) -> u8 {
+    match variant {
+        Variant::Nmos6502 => 0,
+        Variant::Cmos65C02 => 1,
+        Variant::Ricoh2A03 => 2,
+        Variant::Mos6510 => 3,
+    }
+}
+
+/// Returns the variant of a number in the save states.
+fn variant_from_number(number: u8) -> Result<Variant> {
+    match number {
+        0 => Ok(Variant::Nmos6502),
+        1 => Ok(Variant::Cmos65C02),
+        2 => Ok(Variant::Ricoh2A03),
+        3 => Ok(Variant::Mos6510),
+        _ => Err(Error::SaveState(format!("unknown variant {}", number))),
+    }
+}
+
+/// Reads the fields of a save state, failing when it is too short. Buses can use it to read
+/// their part.
+pub struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Starts reading at the beginning of `data`.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// Reads the next `size` bytes.
+    pub fn take(&mut self, size: usize) -> Result<&'a [u8]> {
+        let end: usize = self.position.saturating_add(size);
+        if end > self.data.len() {
+            return Err(Error::SaveState("it is truncated".into()));
+        }
+
+        let bytes: &'a [u8] = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    /// Reads a byte.
+    pub fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Reads a 16-bit number.
+    pub fn word(&mut self) -> Result<u16> {
+        let bytes: &[u8] = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Reads a 32-bit number.
+    pub fn u32(&mut self) -> Result<u32> {
+        let mut bytes: [u8; 4] = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    /// Reads a 64-bit number.
+    pub fn u64(&mut self) -> Result<u64> {
+        let mut bytes: [u8; 8] = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Checks that everything has been read.
+    pub fn finish(&self) -> Result<()> {
+        if self.position == self.data.len() {
+            Ok(())
+        } else {
+            Err(Error::SaveState("it is longer than expected".into()))
+        }
+    }
+}
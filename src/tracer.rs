@@ -0,0 +1,163 @@
+//! An execution tracer which logs every instruction in the format of the `nestest.log` reference
+//! log, as written by Nintendulator, so that runs can be diffed against other emulators:
+//!
+//! ```text
+//! C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD CYC:7
+//! C5F7  86 00     STX $00 = 00                    A:00 X:00 Y:00 P:26 SP:FD CYC:12
+//! ```
+//!
+//! Every line shows the instruction about to be executed and the registers and cycles before
+//! it runs. Undocumented opcodes are marked with `*` and the operands in memory are followed by
+//! the addresses they resolve to and the values found there.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::bus::Bus;
+use crate::disassembler::{self, Instruction, Symbols};
+use crate::opcode::{self, AddressingMode, Mnemonic};
+use crate::p6502::{Variant, P6502};
+
+/// What receives the trace lines of a CPU.
+pub(crate) type Tracer = Box<dyn FnMut(&str)>;
+
+/// Returns the trace line of the instruction at the Program Counter. The memory is only peeked.
+pub fn trace_line<B: Bus>(cpu: &P6502<B>) -> String {
+    let instruction: Instruction =
+        disassembler::disassemble_instruction(&cpu.memory, cpu.variant(), cpu.pc, &Symbols::new());
+    let bytes: Vec<String> = instruction
+        .bytes
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect();
+
+    // Nintendulator's names for two of the undocumented instructions.
+    let mnemonic: String = match instruction.opcode.mnemonic {
+        Mnemonic::Isc => "ISB".to_string(),
+        Mnemonic::Usbc => "SBC".to_string(),
+        mnemonic => mnemonic.to_string(),
+    };
+    let mut text: String = if instruction.operand.is_empty() {
+        mnemonic
+    } else {
+        format!("{} {}", mnemonic, instruction.operand)
+    };
+    text.push_str(&operand_details(cpu, &instruction));
+
+    let documented: bool = opcode::is_documented(cpu.variant(), instruction.bytes[0]);
+    // The B flag only exists on the stack, so it is never shown.
+    let flags: u8 = cpu.flags.as_binary() & !0x10;
+
+    format!(
+        "{:04X}  {:<8} {}{:<31} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
+        cpu.pc,
+        bytes.join(" "),
+        if documented { ' ' } else { '*' },
+        text,
+        cpu.a,
+        cpu.x,
+        cpu.y,
+        flags,
+        cpu.sp,
+        cpu.cycles()
+    )
+}
+
+/// Returns what `nestest.log` shows after the operand: the effective address, if it is not the
+/// operand itself, and the value stored there.
+fn operand_details<B: Bus>(cpu: &P6502<B>, instruction: &Instruction) -> String {
+    let memory: &B = &cpu.memory;
+    let byte: u8 = instruction.bytes.get(1).copied().unwrap_or(0);
+    let word: u16 = ((instruction.bytes.get(2).copied().unwrap_or(0) as u16) << 8) | byte as u16;
+    let zero_page_word = |addr: u8| -> u16 {
+        ((memory.peek(addr.wrapping_add(1) as u16) as u16) << 8) | memory.peek(addr as u16) as u16
+    };
+
+    match instruction.opcode.mode {
+        AddressingMode::ZeroPage => format!(" = {:02X}", memory.peek(byte as u16)),
+        AddressingMode::ZeroPageX | AddressingMode::ZeroPageY => {
+            let index: u8 = if instruction.opcode.mode == AddressingMode::ZeroPageX {
+                cpu.x
+            } else {
+                cpu.y
+            };
+            let addr: u8 = byte.wrapping_add(index);
+            format!(" @ {:02X} = {:02X}", addr, memory.peek(addr as u16))
+        }
+        AddressingMode::Absolute => match instruction.opcode.mnemonic {
+            Mnemonic::Jmp | Mnemonic::Jsr => String::new(),
+            _ => format!(" = {:02X}", memory.peek(word)),
+        },
+        AddressingMode::AbsoluteX | AddressingMode::AbsoluteY => {
+            let index: u8 = if instruction.opcode.mode == AddressingMode::AbsoluteX {
+                cpu.x
+            } else {
+                cpu.y
+            };
+            let addr: u16 = word.wrapping_add(index as u16);
+            format!(" @ {:04X} = {:02X}", addr, memory.peek(addr))
+        }
+        AddressingMode::Indirect => {
+            // The NMOS chips don't carry into the MSB of the pointer.
+            let msb_addr: u16 = if cpu.variant() == Variant::Cmos65C02 {
+                word.wrapping_add(1)
+            } else {
+                (word & 0xff00) | (word.wrapping_add(1) & 0x00ff)
+            };
+            let target: u16 = ((memory.peek(msb_addr) as u16) << 8) | memory.peek(word) as u16;
+            format!(" = {:04X}", target)
+        }
+        AddressingMode::IndexedIndirect => {
+            let pointer: u8 = byte.wrapping_add(cpu.x);
+            let addr: u16 = zero_page_word(pointer);
+            format!(
+                " @ {:02X} = {:04X} = {:02X}",
+                pointer,
+                addr,
+                memory.peek(addr)
+            )
+        }
+        AddressingMode::IndirectIndexed => {
+            let base: u16 = zero_page_word(byte);
+            let addr: u16 = base.wrapping_add(cpu.y as u16);
+            format!(" = {:04X} @ {:04X} = {:02X}", base, addr, memory.peek(addr))
+        }
+        AddressingMode::ZeroPageIndirect => {
+            let addr: u16 = zero_page_word(byte);
+            format!(" = {:04X} = {:02X}", addr, memory.peek(addr))
+        }
+        AddressingMode::AbsoluteIndexedIndirect => {
+            let pointer: u16 = word.wrapping_add(cpu.x as u16);
+            let target: u16 =
+                ((memory.peek(pointer.wrapping_add(1)) as u16) << 8) | memory.peek(pointer) as u16;
+            format!(" = {:04X}", target)
+        }
+        _ => String::new(),
+    }
+}
+
+impl<B: Bus> P6502<B> {
+    /// Calls `tracer` with the trace line of every instruction before executing it, until
+    /// `clear_tracer` is called. Instructions executed a cycle at a time by `tick` are not
+    /// traced.
+    pub fn set_tracer<F: FnMut(&str) + 'static>(&mut self, tracer: F) {
+        self.tracer = Some(Box::new(tracer));
+    }
+
+    /// Stops tracing the execution.
+    pub fn clear_tracer(&mut self) {
+        self.tracer = None;
+    }
+
+    /// Writes the trace line of every instruction to `writer`, such as a file or the standard
+    /// output. Write errors are ignored, so that they don't stop the execution. Requires the
+    /// `std` feature.
+    #[cfg(feature = "std")]
+    pub fn trace_to<W: std::io::Write + 'static>(&mut self, mut writer: W) {
+        self.set_tracer(move |line| {
+            let _ = writeln!(writer, "{}", line);
+        });
+    }
+}
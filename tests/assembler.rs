@@ -0,0 +1,221 @@
+use kw6502::assembler::{self, Assembly, Chunk};
+use kw6502::error::Error;
+use kw6502::opcode::{self, AddressingMode, Opcode};
+use kw6502::p6502::{self, Bus};
+
+/// Returns the bytes assembled at the start of a program.
+fn bytes(source: &str, variant: p6502::Variant) -> Vec<u8> {
+    let assembly: Assembly = assembler::assemble(source, variant).unwrap();
+    assembly.chunks[0].bytes.clone()
+}
+
+/// Returns the line and message of an assembly error.
+fn error(source: &str) -> (usize, String) {
+    match assembler::assemble(source, p6502::Variant::Nmos6502) {
+        Err(Error::Assemble { line, message, .. }) => (line, message),
+        other => panic!("expected an assembly error, got {:?}", other),
+    }
+}
+
+#[test]
+fn every_documented_opcode() {
+    for variant in [p6502::Variant::Nmos6502, p6502::Variant::Cmos65C02].iter() {
+        for byte in 0..=255_u8 {
+            if !opcode::is_documented(*variant, byte) {
+                continue;
+            }
+
+            let decoded: Opcode = opcode::decode(*variant, byte);
+            let (operand, encoded): (&str, Vec<u8>) = match decoded.mode {
+                AddressingMode::Implied => ("", vec![]),
+                AddressingMode::Accumulator => ("A", vec![]),
+                AddressingMode::Immediate => ("#$12", vec![0x12]),
+                AddressingMode::ZeroPage => ("$12", vec![0x12]),
+                AddressingMode::ZeroPageX => ("$12,X", vec![0x12]),
+                AddressingMode::ZeroPageY => ("$12,y", vec![0x12]),
+                AddressingMode::Relative => ("* + 4", vec![0x02]),
+                AddressingMode::Absolute => ("$1234", vec![0x34, 0x12]),
+                AddressingMode::AbsoluteX => ("$1234,x", vec![0x34, 0x12]),
+                AddressingMode::AbsoluteY => ("$1234,Y", vec![0x34, 0x12]),
+                AddressingMode::Indirect => ("($1234)", vec![0x34, 0x12]),
+                AddressingMode::IndexedIndirect => ("($12,X)", vec![0x12]),
+                AddressingMode::IndirectIndexed => ("($12),Y", vec![0x12]),
+                AddressingMode::ZeroPageIndirect => ("($12)", vec![0x12]),
+                AddressingMode::AbsoluteIndexedIndirect => ("($1234,X)", vec![0x34, 0x12]),
+                AddressingMode::ZeroPageRelative => ("$12, * + 5", vec![0x12, 0x02]),
+            };
+
+            let source: String = format!("  {} {}", decoded.mnemonic, operand);
+            let mut expected: Vec<u8> = vec![byte];
+            expected.extend(encoded);
+            assert_eq!(bytes(&source, *variant), expected, "{}", source);
+        }
+    }
+}
+
+#[test]
+fn undocumented_opcodes() {
+    let source: &str = "LAX $10\nDCP ($20),Y\nNOP #$01\nNOP\nJAM";
+    assert_eq!(
+        bytes(source, p6502::Variant::Nmos6502),
+        vec![0xa7, 0x10, 0xd3, 0x20, 0x80, 0x01, 0xea, 0x02]
+    );
+
+    // The 65C02 doesn't have them.
+    assert!(assembler::assemble("LAX $10", p6502::Variant::Cmos65C02).is_err());
+}
+
+#[test]
+fn labels_and_expressions() {
+    let source: &str = r#"
+        ; Copies a string to $0200 and counts its characters.
+        dest = $0200
+        count = $10
+
+        .org $0600
+start:  ldx #0
+.loop:  lda text,x          ; forward reference: absolute mode
+        beq .done
+        sta dest,x
+        inx
+        bne .loop
+.done:  stx count
+        lda #<text
+        ldy #>text
+        brk
+
+text:   .text "HI!"
+        .byte 0, 'a' - 'A', %101 | 8, -1
+        .word start, text + 2 * (1 + 1)
+next:
+.loop:  .byte .loop - next
+"#;
+    let assembly: Assembly = assembler::assemble(source, p6502::Variant::Nmos6502).unwrap();
+
+    assert_eq!(assembly.symbols["start"], 0x0600);
+    assert_eq!(assembly.symbols["start.loop"], 0x0602);
+    assert_eq!(assembly.symbols["start.done"], 0x060d);
+    assert_eq!(assembly.symbols["text"], 0x0614);
+    assert_eq!(assembly.symbols["next.loop"], 0x061f);
+    assert_eq!(assembly.symbols["count"], 0x0010);
+    assert_eq!(
+        assembly.chunks,
+        vec![Chunk {
+            start: 0x0600,
+            bytes: vec![
+                0xa2, 0x00, 0xbd, 0x14, 0x06, 0xf0, 0x06, 0x9d, 0x00, 0x02, 0xe8, 0xd0, 0xf5, 0x86,
+                0x10, 0xa9, 0x14, 0xa0, 0x06, 0x00, b'H', b'I', b'!', 0x00, 0x20, 0x0d, 0xff, 0x00,
+                0x06, 0x18, 0x06, 0x00,
+            ],
+        }]
+    );
+
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+    cpu.set_memory(assembly.to_memory());
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.pc, 0x0613);
+    assert_eq!(cpu.memory.read(0x0010), 3);
+    assert_eq!(cpu.memory.read(0x0200), b'H');
+    assert_eq!(cpu.memory.read(0x0202), b'!');
+    assert_eq!((cpu.a, cpu.y), (0x14, 0x06));
+
+    // The arithmetic wraps around instead of overflowing.
+    let source: &str = "quotient = (1 << 63) / -1\nremainder = (1 << 63) % -1";
+    let assembly: Assembly = assembler::assemble(source, p6502::Variant::Nmos6502).unwrap();
+    assert_eq!(assembly.symbols["remainder"], 0);
+}
+
+#[test]
+fn org_and_reset_vector() {
+    let source: &str = ".org $c000\nreset: jmp reset\n.org $fffc\n.word reset";
+    let assembly: Assembly = assembler::assemble(source, p6502::Variant::Nmos6502).unwrap();
+    assert_eq!(assembly.start(), 0xc000);
+    assert_eq!(assembly.chunks.len(), 2);
+
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.set_memory(assembly.to_memory());
+    cpu.reset();
+    assert_eq!(cpu.pc, 0xc000);
+
+    // Without an .org, programs start at $0600 like the hex dumps.
+    let assembly: Assembly = assembler::assemble("nop", p6502::Variant::Nmos6502).unwrap();
+    assert_eq!(assembly.start(), 0x0600);
+    assert_eq!(assembly.to_memory().read(0xfffd), 0x06);
+}
+
+#[test]
+fn include() {
+    let source: &str = "  .include \"macros.s\"\n  lda #value";
+    let assembly: Assembly =
+        assembler::assemble_with(source, p6502::Variant::Nmos6502, |name| match name {
+            "macros.s" => Some(String::from("value = 42 ; the answer")),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(assembly.chunks[0].bytes, vec![0xa9, 42]);
+
+    match assembler::assemble_with("nop\n.include \"a.s\"", p6502::Variant::Nmos6502, |_| {
+        Some(String::from("nop\n  bogus"))
+    }) {
+        Err(Error::Assemble {
+            file: Some(file), ..
+        }) if file == "a.s" => {}
+        other => panic!("expected a nesting error, got {:?}", other),
+    }
+
+    match assembler::assemble_with(
+        "nop\n.include \"missing.s\"",
+        p6502::Variant::Nmos6502,
+        |_| None,
+    ) {
+        Err(Error::Assemble {
+            file: None,
+            line: 2,
+            ..
+        }) => {}
+        other => panic!("expected an include error, got {:?}", other),
+    }
+}
+
+#[test]
+fn zero_page_selection() {
+    // Known values which fit in a byte get the zero page modes, forward references don't.
+    let source: &str = "zp = $80\nlda zp\nlda later\nldx zp,y\nstx zp,y\nlater = $81";
+    assert_eq!(
+        bytes(source, p6502::Variant::Nmos6502),
+        vec![0xa5, 0x80, 0xad, 0x81, 0x00, 0xb6, 0x80, 0x96, 0x80]
+    );
+
+    // LDA has no zero page,Y mode.
+    assert_eq!(
+        bytes("lda $10,y", p6502::Variant::Nmos6502),
+        vec![0xb9, 0x10, 0x00]
+    );
+}
+
+#[test]
+fn assembly_errors() {
+    assert_eq!(
+        error("nop\nfoo #1"),
+        (2, "unknown instruction 'foo'".to_string())
+    );
+    assert_eq!(error("a: nop\na: nop").1, "'a' is already defined");
+    assert_eq!(error("lda missing").1, "'missing' is not defined");
+    assert_eq!(error("lda #256").1, "256 doesn't fit in a byte");
+    assert_eq!(error("jmp #1").1, "'jmp' can't take the operand '#1'");
+    assert_eq!(error("stx $1234,y").1, "4660 doesn't fit in a byte");
+    assert_eq!(
+        error(".org $0600\nbne $0700").1,
+        "branch target $0700 is out of range"
+    );
+    assert_eq!(error(".bogus 1").1, "unknown directive '.bogus'");
+    assert_eq!(
+        error(".org $ffff\nnop\nnop").1,
+        "the program goes past $FFFF"
+    );
+    assert_eq!(error("lda #1/0").1, "division by zero");
+}
@@ -0,0 +1,2117 @@
+use kw6502::bus::{MemoryMap, Region, RomWrites};
+use kw6502::error::Error;
+use kw6502::loader;
+use kw6502::opcode::{self, AddressingMode, Mnemonic};
+use kw6502::p6502::{self, Bus};
+
+#[test]
+fn adc_idx() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0xfc, 0x85, 0xb4, 0xa9, 0x1c, 0x85, 0xb5, 0xa9, 0xab, 0x8d, 0xfc, 0x1c, 0xa9, 0xba,
+        0xa2, 0x04, 0x61, 0xb0,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+
+    assert_eq!(cpu.a, 0x65);
+    assert_eq!(cpu.flags.as_binary(), 0b01110101);
+}
+
+#[test]
+fn sbc_idx() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0xfc, 0x85, 0xb4, 0xa9, 0x1c, 0x85, 0xb5, 0xa9, 0xab, 0x8d, 0xfc, 0x1c, 0xa9, 0xba,
+        0xa2, 0x04, 0xe1, 0xb0,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x0e);
+    assert_eq!(cpu.flags.as_binary(), 0b00110101);
+}
+
+#[test]
+fn sbc_imm2() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa9, 0xab, 0xe9, 0x43];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x67);
+    assert_eq!(cpu.flags.as_binary(), 0b01110101);
+}
+
+#[test]
+fn sbc_imm3() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa9, 0x43, 0xe9, 0xab];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x97);
+    assert_eq!(cpu.flags.as_binary(), 0b11110100);
+}
+
+#[test]
+fn sbc_imm() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa9, 0xab, 0xe9, 0x0f];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x9b);
+    assert_eq!(cpu.flags.as_binary(), 0b10110101);
+}
+#[test]
+fn lsr_imm() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa9, 0xab, 0x4a];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x55);
+    assert_eq!(cpu.flags.as_binary(), 0b00110101);
+}
+
+#[test]
+fn bit_zp() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa9, 0xab, 0x85, 0x00, 0x24, 0x00];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0xab);
+    assert_eq!(cpu.flags.as_binary(), 0b10110100);
+}
+
+#[test]
+fn dex() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xca];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.x, 0xff);
+    assert_eq!(cpu.flags.as_binary(), 0b10110100);
+}
+
+#[test]
+fn cpx_abs_positive() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa2, 0xbc, 0xa9, 0xaa, 0x8d, 0x32, 0x54, 0xec, 0x32, 0x54, 0x10, 0x02, 0x30, 0x05, 0xa9,
+        0xcc, 0x4c, 0x18, 0x06, 0xa9, 0xbb, 0x4c, 0x18, 0x06,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0xcc);
+    assert_eq!(cpu.flags.as_binary(), 0b10110101);
+}
+#[test]
+fn cpx_abs_negative() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa2, 0xbc, 0xa9, 0xcb, 0x8d, 0x32, 0x54, 0xec, 0x32, 0x54, 0x10, 0x02, 0x30, 0x05, 0xa9,
+        0xff, 0x4c, 0x18, 0x06, 0xa9, 0xbb, 0x4c, 0x18, 0x06,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0xbb);
+    assert_eq!(cpu.flags.as_binary(), 0b10110100);
+}
+
+#[test]
+fn ror_acc() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa9, 0x43, 0xe9, 0xab, 0x6a];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x4b);
+    assert_eq!(cpu.flags.as_binary(), 0b01110101);
+}
+
+#[test]
+fn asl_zp0() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa9, 0x43, 0xe9, 0xab, 0x6a, 0x85, 0x06, 0x06, 0x06];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.memory.read(6), 0x96);
+    assert_eq!(cpu.a, 0x4b);
+    assert_eq!(cpu.flags.as_binary(), 0b11110100);
+}
+
+#[test]
+fn ldx_aby() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x43, 0xe9, 0xab, 0x6a, 0x85, 0x06, 0x06, 0x06, 0xa0, 0x06, 0xbe, 0x00, 0x00,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.memory.read(6), 0x96);
+    assert_eq!(cpu.a, 0x4b);
+    assert_eq!(cpu.y, 0x06);
+    assert_eq!(cpu.x, 0x96);
+    assert_eq!(cpu.flags.as_binary(), 0b11110100);
+}
+
+#[test]
+fn iny() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x43, 0xe9, 0xab, 0x6a, 0x85, 0x06, 0x06, 0x06, 0xa0, 0x06, 0xbe, 0x00, 0x00, 0xc8,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.memory.read(6), 0x96);
+    assert_eq!(cpu.a, 0x4b);
+    assert_eq!(cpu.y, 0x07);
+    assert_eq!(cpu.x, 0x96);
+    assert_eq!(cpu.flags.as_binary(), 0b01110100);
+}
+
+#[test]
+fn inc_zp0() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x43, 0xe9, 0xab, 0x6a, 0x85, 0x06, 0x06, 0x06, 0xa0, 0x06, 0xbe, 0x00, 0x00, 0xe6,
+        0x06,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.memory.read(6), 0x97);
+    assert_eq!(cpu.a, 0x4b);
+    assert_eq!(cpu.y, 0x06);
+    assert_eq!(cpu.x, 0x96);
+    assert_eq!(cpu.flags.as_binary(), 0b11110100);
+}
+
+#[test]
+fn eor_zpx() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x43, 0xe9, 0xab, 0x6a, 0x85, 0x06, 0x06, 0x06, 0xa0, 0x06, 0xbe, 0x00, 0x00, 0xe6,
+        0x06, 0xa2, 0x06, 0x55, 0x00,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0xdc);
+    assert_eq!(cpu.y, 0x06);
+    assert_eq!(cpu.x, 0x06);
+    assert_eq!(cpu.flags.as_binary(), 0b11110100);
+}
+
+#[test]
+fn and_abx() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x43, 0xe9, 0xab, 0x6a, 0x85, 0x06, 0x06, 0x06, 0xa0, 0x06, 0xbe, 0x00, 0x00, 0xe6,
+        0x06, 0xa2, 0x06, 0x55, 0x00, 0x39, 0x00, 0x00,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x94);
+    assert_eq!(cpu.y, 0x06);
+    assert_eq!(cpu.x, 0x06);
+    assert_eq!(cpu.flags.as_binary(), 0b11110100);
+}
+
+#[test]
+fn jmp_ind() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x01, 0xaa, 0x86, 0x05, 0x69, 0x01, 0x69, 0x01, 0x69, 0x01, 0xaa, 0x86, 0x06, 0x6c,
+        0x05, 0x00,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x04);
+    assert_eq!(cpu.a, cpu.x);
+    assert_eq!(cpu.pc, 0x0401);
+}
+
+#[test]
+fn cpy_negative() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa0, 0x45, 0xc0, 0xab];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.y, 0x45);
+    assert_eq!(cpu.flags.as_binary(), 0b10110100);
+}
+
+#[test]
+fn cpy_positive() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa0, 0x45, 0xc0, 0x35];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.y, 0x45);
+    assert_eq!(cpu.flags.as_binary(), 0b00110101);
+}
+
+#[test]
+fn cpy_equal() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa0, 0x45, 0xc0, 0x45];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.y, 0x45);
+    assert_eq!(cpu.flags.as_binary(), 0b00110111);
+}
+
+#[test]
+fn clear_flags() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0x18, 0xd8, 0x58, 0xb8];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.flags.as_binary(), 0b00110000);
+}
+
+#[test]
+fn set_flags() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xf8, 0x38, 0x78];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.flags.as_binary(), 0b00111101);
+}
+
+#[test]
+fn pha_plp_tsx() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa9, 0xe6, 0x48, 0xba, 0x28];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+    assert_eq!(cpu.a, 0xe6);
+    assert_eq!(cpu.flags.as_binary(), 0b11100110);
+    assert_eq!(cpu.x, 0xfc);
+}
+
+#[test]
+fn jmp_ind_bug() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x40, 0x8d, 0x00, 0x30, 0xa9, 0x80, 0x8d, 0xff, 0x30, 0x6c, 0xff, 0x30,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+    assert_eq!(cpu.pc, 0x4080);
+}
+
+#[test]
+fn branch_mul82() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x05, 0x85, 0x00, 0xa9, 0x0a, 0x85, 0x01, 0xa9, 0x00, 0x18, 0x65, 0x00, 0xc6, 0x01,
+        0xa6, 0x01, 0xe0, 0x00, 0xd0, 0xf5,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+    assert_eq!(cpu.a, 0x32);
+    assert_eq!(cpu.flags.as_binary(), 0b00110111);
+}
+
+#[test]
+fn jsr_rts_sum16() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x8a, 0x48, 0xa9, 0x3f, 0x48, 0xa9, 0x24, 0x48, 0xa9, 0xb3, 0x48, 0x20, 0x12, 0x06,
+        0x4c, 0x63, 0x06, 0x68, 0x85, 0x00, 0x68, 0x85, 0x01, 0x68, 0x85, 0x02, 0x68, 0x85, 0x03,
+        0x68, 0x85, 0x04, 0x68, 0x85, 0x05, 0x18, 0xa5, 0x03, 0x65, 0x05, 0x48, 0xa5, 0x02, 0x65,
+        0x04, 0x48, 0xa5, 0x01, 0x48, 0xa5, 0x00, 0x48, 0x60, 0xa9, 0x0f, 0x48, 0xa9, 0x0f, 0x48,
+        0x20, 0x44, 0x06, 0xa2, 0x60, 0x4c, 0x63, 0x06, 0x68, 0x85, 0x00, 0x68, 0x85, 0x01, 0x68,
+        0xaa, 0x68, 0x85, 0x02, 0xa9, 0x00, 0x18, 0x65, 0x02, 0xca, 0xc8, 0xe0, 0x00, 0xd0, 0xf7,
+        0xaa, 0xa5, 0x01, 0x48, 0xa5, 0x00, 0x48, 0x8a, 0x40, 0xa2, 0x50,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+    assert_eq!(cpu.memory.read(0x01fc), 0xf2);
+    assert_eq!(cpu.memory.read(0x01fd), 0xae);
+    assert_eq!(cpu.x, 0x50);
+    assert_eq!(cpu.flags.as_binary(), 0b00110100);
+}
+
+#[test]
+fn txy_txs() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa2, 0x50, 0x8a, 0x9a, 0xa8];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, cpu.x);
+    assert_eq!(cpu.x, cpu.y);
+    assert_eq!(cpu.a, cpu.sp);
+    assert_eq!(cpu.y, 0x50);
+}
+
+#[test]
+fn tsx_txy() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xba, 0x8a, 0xa8];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, cpu.x);
+    assert_eq!(cpu.x, cpu.y);
+    assert_eq!(cpu.a, cpu.sp);
+    assert_eq!(cpu.y, 0xfd);
+}
+
+#[test]
+fn cmp_grater() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x50, 0xc9, 0x40, 0x10, 0x04, 0x30, 0x07, 0xf0, 0x0a, 0xa2, 0xaa, 0x4c, 0x19, 0x06,
+        0xa2, 0xbb, 0x4c, 0x19, 0x06, 0xa2, 0xcc, 0x4c, 0x19, 0x06, 0xa0, 0x99,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x50);
+    assert_eq!(cpu.x, 0xaa);
+    assert_eq!(cpu.y, 0x99);
+}
+
+#[test]
+fn cmp_smaller() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x50, 0xc9, 0x60, 0x10, 0x04, 0x30, 0x07, 0xf0, 0x0a, 0xa2, 0xaa, 0x4c, 0x19, 0x06,
+        0xa2, 0xbb, 0x4c, 0x19, 0x06, 0xa2, 0xcc, 0x4c, 0x19, 0x06, 0xa0, 0x99,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x50);
+    assert_eq!(cpu.x, 0xbb);
+    assert_eq!(cpu.y, 0x99);
+}
+
+#[test]
+fn cmp_equal() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x50, 0xc9, 0x50, 0xf0, 0x0e, 0x30, 0x07, 0x10, 0x00, 0xa2, 0xaa, 0x4c, 0x19, 0x06,
+        0xa2, 0xbb, 0x4c, 0x19, 0x06, 0xa2, 0xcc, 0x4c, 0x19, 0x06, 0xa0, 0x99,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x50);
+    assert_eq!(cpu.x, 0xcc);
+    assert_eq!(cpu.y, 0x99);
+}
+
+/// A bus which behaves like plain memory but exposes $D000 as an output port.
+#[derive(Default)]
+struct PortBus {
+    memory: p6502::Memory,
+    port: Vec<u8>,
+    ticks: usize,
+    irq: bool,
+}
+
+impl Bus for PortBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.memory.read(addr)
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        if addr == 0xd000 {
+            self.port.push(data);
+            self.irq = false;
+        } else {
+            self.memory.write(addr, data);
+        }
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.memory.peek(addr)
+    }
+
+    fn tick(&mut self, cycles: usize) {
+        self.ticks += cycles;
+    }
+
+    fn irq(&self) -> bool {
+        self.irq
+    }
+}
+
+#[test]
+fn custom_bus() {
+    let mut cpu: p6502::P6502<PortBus> = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa9, 0x41, 0x8d, 0x00, 0xd0, 0xa9, 0x42, 0x8d, 0x00, 0xd0];
+
+    let bus: PortBus = PortBus {
+        memory: p6502::Memory::from_program_vec(program),
+        ..PortBus::default()
+    };
+    cpu.set_memory(bus);
+    cpu.reset();
+    cpu.execute().unwrap();
+
+    assert_eq!(cpu.memory.port, vec![0x41, 0x42]);
+    assert_eq!(cpu.memory.peek(0xd000), 0x00);
+    assert!(cpu.memory.ticks > 0);
+}
+
+#[test]
+fn memory_map_mirror() {
+    let mut map: MemoryMap = MemoryMap::new()
+        .ram(0x0000..=0x07ff)
+        .mirror(0x0800..=0x1fff, 0x0000..=0x07ff);
+
+    map.write(0x0801, 0xab);
+    map.write(0x1ffe, 0xcd);
+
+    assert_eq!(map.read(0x0001), 0xab);
+    assert_eq!(map.read(0x1001), 0xab);
+    assert_eq!(map.read(0x17fe), 0xcd);
+    assert_eq!(map.peek(0x07fe), 0xcd);
+}
+
+#[test]
+fn memory_map_rom_and_open_bus() {
+    let mut map: MemoryMap = MemoryMap::new()
+        .ram(0x0000..=0x0fff)
+        .rom(0xf000..=0xffff, vec![0x11, 0x22, 0x33])
+        .unmapped(0x0800..=0x08ff)
+        .rom_writes(RomWrites::Report);
+
+    map.write(0xf001, 0xff);
+    assert_eq!(map.read(0xf001), 0x22);
+    assert_eq!(map.rom_writes, vec![(0xf001, 0xff)]);
+
+    // Unmapped addresses return the last value seen on the data bus.
+    assert_eq!(map.read(0x0800), 0x22);
+    map.write(0x0800, 0x44);
+    assert_eq!(map.read(0x8000), 0x44);
+    assert_eq!(map.read(0x0800), 0x44);
+}
+
+#[test]
+fn memory_map_region_sizes() {
+    let mut map: MemoryMap = MemoryMap::new()
+        .map(0x0000..=0x07ff, Region::Ram(vec![]))
+        .map(0xfffe..=0xffff, Region::Rom(vec![0x11, 0x22, 0x33]));
+
+    // The regions are sized to their ranges whatever they were given.
+    map.write(0x07ff, 0xab);
+    map.load(0x0400, &[0xcd]);
+    assert_eq!(map.read(0x07ff), 0xab);
+    assert_eq!(map.peek(0x0400), 0xcd);
+    assert_eq!(map.read(0xffff), 0x22);
+}
+
+#[test]
+fn memory_map_cpu() {
+    let mut cpu: p6502::P6502<MemoryMap> = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x42, 0x8d, 0x10, 0x08, 0x8d, 0x00, 0xf0, 0xad, 0x00, 0xf0, 0xae, 0x10, 0x00,
+    ];
+
+    let mut rom: Vec<u8> = vec![0x00; 0x1000];
+    rom[0x000] = 0x99;
+    rom[0xffc] = 0x00;
+    rom[0xffd] = 0x06;
+
+    let mut map: MemoryMap = MemoryMap::new()
+        .ram(0x0000..=0x07ff)
+        .mirror(0x0800..=0x1fff, 0x0000..=0x07ff)
+        .rom(0xf000..=0xffff, rom);
+    map.load(p6502::P6502::PROGRAM_START, &program);
+    cpu.set_memory(map);
+    cpu.reset();
+    cpu.execute().unwrap();
+
+    assert_eq!(cpu.a, 0x99);
+    assert_eq!(cpu.x, 0x42);
+    assert_eq!(cpu.memory.peek(0x0010), 0x42);
+}
+
+/// Builds a memory with the program at the default start and an interrupt handler at $0700,
+/// which both the IRQ and NMI vectors point to.
+fn memory_with_handler(program: Vec<u8>, handler: Vec<u8>) -> p6502::Memory {
+    let mut memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    for (index, value) in handler.iter().enumerate() {
+        memory.write(0x0700 + index as u16, *value);
+    }
+    memory.write(0xfffa, 0x00);
+    memory.write(0xfffb, 0x07);
+    memory.write(0xfffe, 0x00);
+    memory.write(0xffff, 0x07);
+    memory
+}
+
+#[test]
+fn irq() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let memory: p6502::Memory = memory_with_handler(vec![0x58, 0xea], vec![0xa9, 0x55]);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.irq(true);
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x55);
+    assert!(cpu.flags.i);
+    assert_eq!(cpu.sp, 0xfa);
+    assert_eq!(cpu.memory.read(0x01fd), 0x06);
+    assert_eq!(cpu.memory.read(0x01fc), 0x01);
+    assert_eq!(cpu.memory.read(0x01fb), 0b00100000);
+}
+
+#[test]
+fn irq_masked() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let memory: p6502::Memory = memory_with_handler(vec![0xa9, 0x11, 0xea], vec![0xa9, 0x55]);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.flags.i = true;
+    cpu.irq(true);
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x11);
+    assert_eq!(cpu.sp, 0xfd);
+}
+
+#[test]
+fn nmi() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let memory: p6502::Memory = memory_with_handler(vec![0xe8, 0xe8], vec![0xa9, 0x55, 0x40]);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.flags.i = true;
+    cpu.nmi();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x55);
+    assert_eq!(cpu.x, 0x02);
+    assert_eq!(cpu.sp, 0xfd);
+}
+
+#[test]
+fn irq_from_device() {
+    let mut cpu: p6502::P6502<PortBus> = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let bus: PortBus = PortBus {
+        memory: memory_with_handler(
+            vec![0x58, 0xa2, 0x01],
+            vec![0xa9, 0x55, 0x8d, 0x00, 0xd0, 0x40],
+        ),
+        irq: true,
+        ..PortBus::default()
+    };
+    cpu.set_memory(bus);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.memory.port, vec![0x55]);
+    assert_eq!(cpu.x, 0x01);
+    assert!(!cpu.flags.i);
+}
+
+#[test]
+fn brk_interrupt() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.reset();
+
+    let memory: p6502::Memory =
+        memory_with_handler(vec![0xa2, 0x01, 0x00, 0xff, 0xe8], vec![0xa9, 0x55, 0x40]);
+    cpu.set_memory(memory);
+    cpu.reset();
+
+    for _ in 0..5 {
+        cpu.step().unwrap();
+    }
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x55);
+    assert_eq!(cpu.x, 0x02);
+    assert_eq!(cpu.pc, 0x0605);
+    assert_eq!(cpu.memory.read(0x01fd), 0x06);
+    assert_eq!(cpu.memory.read(0x01fc), 0x04);
+    assert_eq!(cpu.memory.read(0x01fb), 0b00110100);
+}
+
+#[test]
+fn reset_vector() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let mut memory: p6502::Memory = p6502::Memory::from_program_vec(vec![]);
+    memory.write(0x1234, 0xa9);
+    memory.write(0x1235, 0x42);
+    memory.write(0x00ff, 0x77);
+    memory.write(0xfffc, 0x34);
+    memory.write(0xfffd, 0x12);
+    cpu.set_memory(memory);
+    cpu.sp = 0x42;
+    cpu.flags.c = true;
+    cpu.flags.d = true;
+    cpu.reset();
+
+    // Only the interrupt mask and the B flag are set: the other flags are left as they were.
+    assert_eq!(cpu.pc, 0x1234);
+    assert_eq!(cpu.sp, 0xfd);
+    assert!(cpu.flags.i && cpu.flags.b);
+    assert!(cpu.flags.c && cpu.flags.d);
+    assert!(!cpu.flags.z && !cpu.flags.v && !cpu.flags.n);
+
+    cpu.execute().unwrap();
+    assert_eq!(cpu.a, 0x42);
+    assert_eq!(cpu.pc, 0x1236);
+    assert_eq!(cpu.memory.read(0x00ff), 0x77);
+
+    cpu.power_on();
+    assert_eq!(cpu.a, 0x00);
+    assert!(!cpu.flags.c && !cpu.flags.d);
+    assert_eq!(cpu.pc, 0x0000);
+    assert_eq!(cpu.memory.read(0x00ff), 0x00);
+}
+
+#[test]
+fn cmos_reset_clears_decimal() {
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+    cpu.flags.c = true;
+    cpu.flags.d = true;
+    cpu.reset();
+
+    assert!(cpu.flags.i && cpu.flags.c);
+    assert!(!cpu.flags.d);
+}
+
+/// Converts a number from 0 to 99 to its BCD representation.
+fn to_bcd(value: u8) -> u8 {
+    ((value / 10) << 4) | (value % 10)
+}
+
+/// Runs `SED`, `CLC`/`SEC`, `LDA #a` and the specified immediate instruction with `b` as its
+/// operand on an existing CPU.
+fn run_decimal(cpu: &mut p6502::P6502, instruction: u8, a: u8, b: u8, carry: bool) {
+    let program: [u8; 7] = [
+        0xf8,
+        if carry { 0x38 } else { 0x18 },
+        0xa9,
+        a,
+        instruction,
+        b,
+        0x00,
+    ];
+    for (index, value) in program.iter().enumerate() {
+        cpu.memory
+            .write(p6502::P6502::PROGRAM_START + index as u16, *value);
+    }
+    cpu.pc = p6502::P6502::PROGRAM_START;
+    cpu.execute().unwrap();
+}
+
+#[test]
+fn adc_decimal_exhaustive() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    for a in 0..100 {
+        for b in 0..100 {
+            for carry in [false, true] {
+                run_decimal(&mut cpu, 0x69, to_bcd(a), to_bcd(b), carry);
+                let sum: u8 = a + b + carry as u8;
+                let binary_sum: u8 = to_bcd(a).wrapping_add(to_bcd(b)).wrapping_add(carry as u8);
+
+                assert_eq!(cpu.a, to_bcd(sum % 100), "{} + {} + {}", a, b, carry);
+                assert_eq!(cpu.flags.c, sum > 99, "{} + {} + {}", a, b, carry);
+                assert_eq!(cpu.flags.z, binary_sum == 0, "{} + {} + {}", a, b, carry);
+            }
+        }
+    }
+}
+
+#[test]
+fn sbc_decimal_exhaustive() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    for a in 0..100 {
+        for b in 0..100 {
+            for carry in [false, true] {
+                run_decimal(&mut cpu, 0xe9, to_bcd(a), to_bcd(b), carry);
+                let difference: i16 = a as i16 - b as i16 - !carry as i16;
+                let binary_difference: u8 =
+                    to_bcd(a).wrapping_sub(to_bcd(b)).wrapping_sub(!carry as u8);
+
+                assert_eq!(
+                    cpu.a,
+                    to_bcd(difference.rem_euclid(100) as u8),
+                    "{} - {} - {}",
+                    a,
+                    b,
+                    !carry
+                );
+                assert_eq!(cpu.flags.c, difference >= 0, "{} - {} - {}", a, b, !carry);
+                assert_eq!(cpu.flags.z, binary_difference == 0);
+                assert_eq!(cpu.flags.n, binary_difference & 0x80 != 0);
+            }
+        }
+    }
+}
+
+/// A decimal addition: A, operand, carry, result, and the N, V, Z and C flags.
+type DecimalCase = (u8, u8, bool, u8, bool, bool, bool, bool);
+
+#[test]
+fn adc_decimal_nmos_flags() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let table: [DecimalCase; 5] = [
+        (0x00, 0x00, false, 0x00, false, false, true, false),
+        (0x99, 0x01, false, 0x00, true, false, false, true),
+        (0x79, 0x00, true, 0x80, true, true, false, false),
+        (0x50, 0x50, false, 0x00, true, true, false, true),
+        (0x09, 0x01, false, 0x10, false, false, false, false),
+    ];
+
+    for (a, b, carry, result, n, v, z, c) in table.iter() {
+        run_decimal(&mut cpu, 0x69, *a, *b, *carry);
+        println!("{}", cpu.status());
+
+        assert_eq!(cpu.a, *result);
+        assert_eq!(cpu.flags.n, *n);
+        assert_eq!(cpu.flags.v, *v);
+        assert_eq!(cpu.flags.z, *z);
+        assert_eq!(cpu.flags.c, *c);
+    }
+}
+
+#[test]
+fn lax_sax() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa9, 0x55, 0x85, 0x10, 0xa7, 0x10, 0xa9, 0x0f, 0x87, 0x11];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x0f);
+    assert_eq!(cpu.x, 0x55);
+    assert_eq!(cpu.memory.read(0x11), 0x05);
+}
+
+#[test]
+fn slo_rla_sre() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x81, 0x85, 0x20, 0xa9, 0x10, 0x07, 0x20, 0x85, 0x21, 0x2f, 0x20, 0x00, 0x85, 0x22,
+        0xa2, 0x01, 0x57, 0x20,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.memory.read(0x20), 0x05);
+    assert_eq!(cpu.memory.read(0x21), 0x09);
+    assert_eq!(cpu.memory.read(0x22), 0x00);
+    assert_eq!(cpu.a, 0x09);
+    assert_eq!(cpu.flags.as_binary(), 0b00110100);
+}
+
+#[test]
+fn dcp_isc() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x05, 0x85, 0x30, 0xa9, 0x04, 0xc7, 0x30, 0x08, 0xe7, 0x30,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.memory.read(0x01fd), 0b00110111);
+    assert_eq!(cpu.memory.read(0x30), 0x05);
+    assert_eq!(cpu.a, 0xff);
+    assert_eq!(cpu.flags.as_binary(), 0b10110100);
+}
+
+#[test]
+fn immediate_undocumented() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0xf0, 0xa2, 0x3c, 0xcb, 0x10, 0x86, 0x40, 0xa9, 0x81, 0x0b, 0xff, 0x85, 0x41, 0x08,
+        0xa9, 0xff, 0x6b, 0xff, 0x08, 0xa9, 0x03, 0x4b, 0x07,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    // SBX
+    assert_eq!(cpu.memory.read(0x40), 0x20);
+    // ANC
+    assert_eq!(cpu.memory.read(0x41), 0x81);
+    assert_eq!(cpu.memory.read(0x01fd), 0b10110101);
+    // ARR
+    assert_eq!(cpu.memory.read(0x01fc), 0b10110101);
+    // ALR
+    assert_eq!(cpu.a, 0x01);
+    assert_eq!(cpu.flags.as_binary(), 0b00110101);
+}
+
+#[test]
+fn shx_page_cross() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xa2, 0xff, 0xa0, 0x20, 0x9e, 0xf0, 0x12, 0x9e, 0x00, 0x12];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.memory.read(0x1310), 0x13);
+    assert_eq!(cpu.memory.read(0x1220), 0x13);
+}
+
+#[test]
+fn jam() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+
+    let program: Vec<u8> = vec![0xa2, 0x01, 0x02, 0xe8];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    let result: Result<(), Error> = cpu.execute();
+    println!("{}", cpu.status());
+
+    assert!(matches!(
+        result,
+        Err(Error::Jam {
+            opcode: 0x02,
+            pc: 0x0602
+        })
+    ));
+    assert!(cpu.is_jammed());
+    assert_eq!(cpu.x, 0x01);
+    assert_eq!(cpu.pc, 0x0602);
+
+    cpu.nmi();
+    assert!(matches!(cpu.execute(), Err(Error::Jam { .. })));
+    assert_eq!(cpu.pc, 0x0602);
+
+    cpu.reset();
+    assert!(!cpu.is_jammed());
+    assert_eq!(cpu.pc, 0x0600);
+}
+
+#[test]
+fn jmp_ind_fixed_65c02() {
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x40, 0x8d, 0x00, 0x30, 0xa9, 0x80, 0x8d, 0xff, 0x30, 0x6c, 0xff, 0x30,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+    assert_eq!(cpu.pc, 0x0080);
+}
+
+#[test]
+fn cmos_new_instructions() {
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa2, 0x12, 0xda, 0x7a, 0xa9, 0xff, 0x85, 0x10, 0x64, 0x10, 0xa9, 0xf0, 0x85, 0x11, 0xa9,
+        0x0f, 0x04, 0x11, 0x14, 0x11, 0x80, 0x02, 0xa2, 0xff, 0x1a,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.x, 0x12);
+    assert_eq!(cpu.y, 0x12);
+    assert_eq!(cpu.a, 0x10);
+    assert_eq!(cpu.memory.read(0x10), 0x00);
+    assert_eq!(cpu.memory.read(0x11), 0xf0);
+    assert!(!cpu.flags.z);
+}
+
+#[test]
+fn cmos_bit_instructions() {
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x81, 0x85, 0x20, 0x77, 0x20, 0x97, 0x20, 0x0f, 0x20, 0x02, 0xa2, 0x01, 0x9f, 0x20,
+        0x02, 0xa2, 0xff,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.memory.read(0x20), 0x03);
+    assert_eq!(cpu.x, 0x01);
+}
+
+#[test]
+fn cmos_indirect_modes() {
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x34, 0x85, 0x40, 0xa9, 0x12, 0x85, 0x41, 0xa9, 0x55, 0x92, 0x40, 0xa9, 0x00, 0xb2,
+        0x40, 0xa8, 0xa2, 0x04, 0xa9, 0x00, 0x8d, 0x04, 0x20, 0xa9, 0x07, 0x8d, 0x05, 0x20, 0x7c,
+        0x00, 0x20,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.memory.read(0x1234), 0x55);
+    assert_eq!(cpu.y, 0x55);
+    assert_eq!(cpu.pc, 0x0700);
+}
+
+#[test]
+fn cmos_undocumented_nops() {
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0x03, 0x02, 0xff, 0x44, 0x10, 0xdc, 0x00, 0x10, 0xfb, 0xa9, 0x01,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert!(!cpu.is_jammed());
+    assert_eq!(cpu.a, 0x01);
+    assert_eq!(cpu.pc, 0x060b);
+}
+
+#[test]
+fn cmos_decimal_and_interrupts() {
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let memory: p6502::Memory =
+        memory_with_handler(vec![0xf8, 0x18, 0xa9, 0x99, 0x69, 0x01], vec![0xa9, 0x55]);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    // Unlike the NMOS 6502, the Z flag reflects the decimal result.
+    assert_eq!(cpu.a, 0x00);
+    assert!(cpu.flags.c);
+    assert!(cpu.flags.z);
+    assert!(cpu.flags.d);
+
+    cpu.nmi();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+    assert_eq!(cpu.a, 0x55);
+    assert!(!cpu.flags.d);
+}
+
+#[test]
+fn cmos_wai_stp() {
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(vec![0xcb, 0xe8, 0xdb, 0xe8]);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    cpu.execute().unwrap();
+    assert_eq!(cpu.x, 0x00);
+
+    // The interrupt is masked, so the CPU just resumes after the WAI instruction.
+    cpu.irq(true);
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+    assert_eq!(cpu.x, 0x01);
+    assert!(cpu.is_jammed());
+    assert_eq!(cpu.pc, 0x0602);
+}
+
+#[test]
+fn ricoh_no_decimal() {
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Ricoh2A03);
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![0xf8, 0x18, 0xa9, 0x09, 0x69, 0x01, 0x38, 0xe9, 0x01];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert!(cpu.flags.d);
+    assert_eq!(cpu.a, 0x09);
+}
+
+#[test]
+fn ricoh_arr_no_decimal() {
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Ricoh2A03);
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    // SED; SEC; LDA #$FF; ARR #$FF, which would give $55 with the decimal adjustment
+    let program: Vec<u8> = vec![0xf8, 0x38, 0xa9, 0xff, 0x6b, 0xff];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert!(cpu.flags.d);
+    assert_eq!(cpu.a, 0xff);
+    assert!(cpu.flags.c);
+    assert!(!cpu.flags.v);
+    assert!(cpu.flags.n);
+}
+
+#[test]
+fn mos6510_io_port() {
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Mos6510);
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa9, 0x2f, 0x85, 0x00, 0xa9, 0x37, 0x85, 0x01, 0xa5, 0x01, 0xa6, 0x00,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.io_port.input = 0x7f;
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x77);
+    assert_eq!(cpu.x, 0x2f);
+    assert_eq!(cpu.io_port.data, 0x37);
+    assert_eq!(cpu.memory.read(0x0000), 0x00);
+    assert_eq!(cpu.memory.read(0x0001), 0x00);
+}
+
+#[test]
+fn opcode_tables() {
+    let lda: opcode::Opcode = opcode::decode(p6502::Variant::Nmos6502, p6502::P6502::INS_LDA_ABX);
+    assert_eq!(lda.mnemonic, Mnemonic::Lda);
+    assert_eq!(lda.mode, AddressingMode::AbsoluteX);
+    assert_eq!(lda.cycles, 4);
+    assert!(lda.page_penalty);
+    assert_eq!(lda.size(), 3);
+
+    let sta: opcode::Opcode = opcode::decode(p6502::Variant::Nmos6502, p6502::P6502::INS_STA_ABX);
+    assert_eq!(sta.cycles, 5);
+    assert!(!sta.page_penalty);
+
+    // The same opcode means different things on each instruction set.
+    let stz: u8 = p6502::P6502::INS_STZ_ABS;
+    assert_eq!(
+        opcode::decode(p6502::Variant::Nmos6502, stz).mnemonic,
+        Mnemonic::Shy
+    );
+    assert_eq!(
+        opcode::decode(p6502::Variant::Cmos65C02, stz).mnemonic,
+        Mnemonic::Stz
+    );
+    assert_eq!(opcode::decode(p6502::Variant::Ricoh2A03, 0x6c).cycles, 5);
+    assert_eq!(opcode::decode(p6502::Variant::Cmos65C02, 0x6c).cycles, 6);
+
+    let bbs: opcode::Opcode = opcode::decode(p6502::Variant::Cmos65C02, p6502::P6502::INS_BBS3_ZPR);
+    assert_eq!(bbs.mnemonic.to_string(), "BBS3");
+    assert_eq!(bbs.size(), 3);
+}
+
+#[test]
+fn zero_page_wrap_and_long_branch() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let program: Vec<u8> = vec![
+        0xa2, 0x10, 0xa9, 0x42, 0x85, 0x05, 0xa9, 0x00, 0xb5, 0xf5, 0xd0, 0x50,
+    ];
+
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.execute().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.a, 0x42);
+    assert_eq!(cpu.pc, 0x065c);
+}
+
+/// The cycles taken by every opcode of the NMOS 6502, without penalties. The JAM opcodes,
+/// which never finish, are marked with 0.
+#[rustfmt::skip]
+const NMOS_CYCLES: [usize; 256] = [
+    7, 6, 0, 8, 3, 3, 5, 5, 3, 2, 2, 2, 4, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    6, 6, 0, 8, 3, 3, 5, 5, 4, 2, 2, 2, 4, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    6, 6, 0, 8, 3, 3, 5, 5, 3, 2, 2, 2, 3, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    6, 6, 0, 8, 3, 3, 5, 5, 4, 2, 2, 2, 5, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    2, 6, 2, 6, 3, 3, 3, 3, 2, 2, 2, 2, 4, 4, 4, 4,
+    2, 6, 0, 6, 4, 4, 4, 4, 2, 5, 2, 5, 5, 5, 5, 5,
+    2, 6, 2, 6, 3, 3, 3, 3, 2, 2, 2, 2, 4, 4, 4, 4,
+    2, 5, 0, 5, 4, 4, 4, 4, 2, 4, 2, 4, 4, 4, 4, 4,
+    2, 6, 2, 8, 3, 3, 5, 5, 2, 2, 2, 2, 4, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    2, 6, 2, 8, 3, 3, 5, 5, 2, 2, 2, 2, 4, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+];
+
+/// The opcodes of the NMOS 6502 which take one more cycle when indexing crosses a page, not
+/// counting the branches.
+const NMOS_PAGE_PENALTIES: [u8; 32] = [
+    0x11, 0x31, 0x51, 0x71, 0xb1, 0xd1, 0xf1, 0xb3, 0x19, 0x39, 0x59, 0x79, 0xb9, 0xd9, 0xf9, 0xbe,
+    0xbf, 0xbb, 0x1d, 0x3d, 0x5d, 0x7d, 0xbd, 0xdd, 0xfd, 0xbc, 0x1c, 0x3c, 0x5c, 0x7c, 0xdc, 0xfc,
+];
+
+/// Executes a single instruction on a fresh CPU and returns the cycles it took. The operand is
+/// usually $2010, or $10 with a pointer to $2010, and both index registers are set to `index`.
+/// The Zero Page location $12 has all its bits set, and $13 has all of them clear.
+fn measure_cycles(variant: p6502::Variant, program: Vec<u8>, index: u8, flags: u8) -> usize {
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(variant);
+
+    let mut memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    memory.write(0x0010, 0x10);
+    memory.write(0x0011, 0x20);
+    memory.write(0x0012, 0xff);
+    cpu.set_memory(memory);
+    cpu.reset();
+    cpu.x = index;
+    cpu.y = index;
+    cpu.flags = p6502::P6502Flags::from_binary(flags);
+
+    // The JAM opcodes fail, but still take their cycles.
+    let start: usize = cpu.cycles();
+    cpu.step().ok();
+    cpu.cycles() - start
+}
+
+#[test]
+fn nmos_cycles() {
+    for opcode in 0..=255_u8 {
+        let entry: opcode::Opcode = opcode::decode(p6502::Variant::Nmos6502, opcode);
+        let reference: usize = NMOS_CYCLES[opcode as usize];
+        if reference == 0 {
+            assert_eq!(entry.mnemonic, Mnemonic::Jam);
+            continue;
+        }
+        assert_eq!(
+            entry.cycles as usize, reference,
+            "table, opcode ${:02x}",
+            opcode
+        );
+
+        if entry.mode == AddressingMode::Relative {
+            // The branch is taken with either all flags set or all of them clear.
+            let taken: u8 =
+                if measure_cycles(p6502::Variant::Nmos6502, vec![opcode, 0x10], 0, 0x00) == 3 {
+                    0x00
+                } else {
+                    0xff
+                };
+            let program: Vec<u8> = vec![opcode, 0x10];
+            assert_eq!(
+                measure_cycles(p6502::Variant::Nmos6502, program.clone(), 0, !taken),
+                2
+            );
+            assert_eq!(
+                measure_cycles(p6502::Variant::Nmos6502, program, 0, taken),
+                3
+            );
+            let program: Vec<u8> = vec![opcode, 0x80];
+            assert_eq!(
+                measure_cycles(p6502::Variant::Nmos6502, program, 0, taken),
+                4
+            );
+            continue;
+        }
+
+        let program: Vec<u8> = vec![opcode, 0x10, 0x20];
+        let cycles: usize = measure_cycles(p6502::Variant::Nmos6502, program.clone(), 0, 0x00);
+        assert_eq!(cycles, reference, "opcode ${:02x}", opcode);
+
+        let penalty: bool = NMOS_PAGE_PENALTIES.contains(&opcode);
+        assert_eq!(
+            entry.page_penalty, penalty,
+            "page penalty, opcode ${:02x}",
+            opcode
+        );
+        let crossing: usize = measure_cycles(p6502::Variant::Nmos6502, program, 0xff, 0x00);
+        assert_eq!(
+            crossing,
+            reference + penalty as usize,
+            "page cross, opcode ${:02x}",
+            opcode
+        );
+    }
+}
+
+/// The cycles taken by every opcode of the 65C02, without penalties, as listed in the W65C02S
+/// datasheet. The branches take their cycles when they are not taken, except BRA.
+#[rustfmt::skip]
+const CMOS_CYCLES: [usize; 256] = [
+    7, 6, 2, 1, 5, 3, 5, 5, 3, 2, 2, 1, 6, 4, 6, 5,
+    2, 5, 5, 1, 5, 4, 6, 5, 2, 4, 2, 1, 6, 4, 6, 5,
+    6, 6, 2, 1, 3, 3, 5, 5, 4, 2, 2, 1, 4, 4, 6, 5,
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 2, 1, 4, 4, 6, 5,
+    6, 6, 2, 1, 3, 3, 5, 5, 3, 2, 2, 1, 3, 4, 6, 5,
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 3, 1, 8, 4, 6, 5,
+    6, 6, 2, 1, 3, 3, 5, 5, 4, 2, 2, 1, 6, 4, 6, 5,
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 4, 1, 6, 4, 6, 5,
+    3, 6, 2, 1, 3, 3, 3, 5, 2, 2, 2, 1, 4, 4, 4, 5,
+    2, 6, 5, 1, 4, 4, 4, 5, 2, 5, 2, 1, 4, 5, 5, 5,
+    2, 6, 2, 1, 3, 3, 3, 5, 2, 2, 2, 1, 4, 4, 4, 5,
+    2, 5, 5, 1, 4, 4, 4, 5, 2, 4, 2, 1, 4, 4, 4, 5,
+    2, 6, 2, 1, 3, 3, 5, 5, 2, 2, 2, 3, 4, 4, 6, 5,
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 3, 3, 4, 4, 7, 5,
+    2, 6, 2, 1, 3, 3, 5, 5, 2, 2, 2, 1, 4, 4, 6, 5,
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 4, 1, 4, 4, 7, 5,
+];
+
+/// The opcodes of the 65C02 which take one more cycle when indexing crosses a page, not
+/// counting the branches. Unlike on the NMOS 6502, the shifts and rotations on Absolute,X are
+/// among them.
+const CMOS_PAGE_PENALTIES: [u8; 28] = [
+    0x11, 0x31, 0x51, 0x71, 0xb1, 0xd1, 0xf1, 0x19, 0x39, 0x59, 0x79, 0xb9, 0xd9, 0xf9, 0xbe, 0x1d,
+    0x3d, 0x5d, 0x7d, 0xbd, 0xdd, 0xfd, 0xbc, 0x3c, 0x1e, 0x3e, 0x5e, 0x7e,
+];
+
+#[test]
+fn cmos_cycles() {
+    for opcode in 0..=255_u8 {
+        let entry: opcode::Opcode = opcode::decode(p6502::Variant::Cmos65C02, opcode);
+        let expected: usize = CMOS_CYCLES[opcode as usize];
+        assert_eq!(
+            entry.cycles as usize, expected,
+            "table, opcode ${:02x}",
+            opcode
+        );
+
+        match entry.mode {
+            _ if entry.mnemonic == Mnemonic::Bra => {
+                assert_eq!(
+                    measure_cycles(p6502::Variant::Cmos65C02, vec![opcode, 0x10], 0, 0),
+                    3
+                );
+                assert_eq!(
+                    measure_cycles(p6502::Variant::Cmos65C02, vec![opcode, 0x80], 0, 0),
+                    4
+                );
+            }
+            AddressingMode::Relative => {
+                let program: Vec<u8> = vec![opcode, 0x10];
+                let clear: usize =
+                    measure_cycles(p6502::Variant::Cmos65C02, program.clone(), 0, 0x00);
+                let set: usize = measure_cycles(p6502::Variant::Cmos65C02, program, 0, 0xff);
+                assert_eq!((clear.min(set), clear.max(set)), (expected, expected + 1));
+            }
+            AddressingMode::ZeroPageRelative => {
+                let set: usize =
+                    measure_cycles(p6502::Variant::Cmos65C02, vec![opcode, 0x12, 0x10], 0, 0);
+                let clear: usize =
+                    measure_cycles(p6502::Variant::Cmos65C02, vec![opcode, 0x13, 0x10], 0, 0);
+                assert_eq!((clear.min(set), clear.max(set)), (expected, expected + 1));
+            }
+            _ => {
+                let program: Vec<u8> = vec![opcode, 0x10, 0x20];
+                let cycles: usize =
+                    measure_cycles(p6502::Variant::Cmos65C02, program.clone(), 0, 0x00);
+                assert_eq!(cycles, expected, "opcode ${:02x}", opcode);
+                let penalty: bool = CMOS_PAGE_PENALTIES.contains(&opcode);
+                assert_eq!(
+                    entry.page_penalty, penalty,
+                    "page penalty, opcode ${:02x}",
+                    opcode
+                );
+                let crossing: usize =
+                    measure_cycles(p6502::Variant::Cmos65C02, program, 0xff, 0x00);
+                assert_eq!(
+                    crossing,
+                    expected + penalty as usize,
+                    "page cross, opcode ${:02x}",
+                    opcode
+                );
+            }
+        }
+    }
+
+    // The 65C02 spends one more cycle in ADC and SBC in decimal mode.
+    let adc: Vec<u8> = vec![p6502::P6502::INS_ADC_IMM, 0x10];
+    assert_eq!(
+        measure_cycles(p6502::Variant::Cmos65C02, adc.clone(), 0, 0x08),
+        3
+    );
+    assert_eq!(measure_cycles(p6502::Variant::Nmos6502, adc, 0, 0x08), 2);
+}
+
+/// A bus which records every access: whether it is a write, the address and the data.
+#[derive(Default)]
+struct LogBus {
+    memory: p6502::Memory,
+    log: Vec<(bool, u16, u8)>,
+}
+
+impl Bus for LogBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        let data: u8 = self.memory.read(addr);
+        self.log.push((false, addr, data));
+        data
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        self.memory.write(addr, data);
+        self.log.push((true, addr, data));
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.memory.peek(addr)
+    }
+}
+
+/// Builds a CPU with a `LogBus`, right after the reset and with the log empty.
+fn logged_cpu(program: Vec<u8>) -> p6502::P6502<LogBus> {
+    let mut cpu: p6502::P6502<LogBus> = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let mut bus: LogBus = LogBus {
+        memory: p6502::Memory::from_program_vec(program),
+        ..LogBus::default()
+    };
+    bus.memory.write(0x0030, 0x41);
+    bus.memory.write(0x2110, 0x99);
+    cpu.set_memory(bus);
+    cpu.reset();
+    cpu.memory.log.clear();
+    cpu
+}
+
+#[test]
+fn tick_bus_accesses() {
+    let program: Vec<u8> = vec![0xa2, 0x20, 0xbd, 0xf0, 0x20, 0xe6, 0x30, 0x9d, 0x00, 0x20];
+    let mut cpu: p6502::P6502<LogBus> = logged_cpu(program.clone());
+
+    // The registers only change once the instruction is finished.
+    assert!(cpu.tick());
+    assert!(cpu.mid_instruction());
+    assert_eq!(cpu.pc, 0x0600);
+    assert!(cpu.tick());
+    assert!(!cpu.mid_instruction());
+    assert_eq!(cpu.x, 0x20);
+
+    let start: usize = cpu.cycles();
+    let mut ticks: usize = 0;
+    loop {
+        let running: bool = cpu.tick();
+        ticks += 1;
+        assert_eq!(cpu.memory.log.len(), ticks + 2);
+        if !running {
+            break;
+        }
+    }
+    println!("{}", cpu.status());
+    assert_eq!(cpu.cycles() - start, ticks);
+    assert_eq!(cpu.a, 0x99);
+
+    let expected: Vec<(bool, u16, u8)> = vec![
+        (false, 0x0600, 0xa2),
+        (false, 0x0601, 0x20),
+        (false, 0x0602, 0xbd),
+        (false, 0x0603, 0xf0),
+        (false, 0x0604, 0x20),
+        (false, 0x2010, 0x00),
+        (false, 0x2110, 0x99),
+        (false, 0x0605, 0xe6),
+        (false, 0x0606, 0x30),
+        (false, 0x0030, 0x41),
+        (true, 0x0030, 0x41),
+        (true, 0x0030, 0x42),
+        (false, 0x0607, 0x9d),
+        (false, 0x0608, 0x00),
+        (false, 0x0609, 0x20),
+        (false, 0x2020, 0x00),
+        (true, 0x2020, 0x99),
+        (false, 0x060a, 0x00),
+    ];
+    assert_eq!(cpu.memory.log, expected);
+
+    // Executing whole instructions performs the same accesses.
+    let mut atomic_cpu: p6502::P6502<LogBus> = logged_cpu(program);
+    atomic_cpu.execute().unwrap();
+    assert_eq!(atomic_cpu.memory.log, expected);
+}
+
+#[test]
+fn cmos_page_crossing_reads() {
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+
+    // LDX #$01; LDA $20FF,X; LDY #$01; LDA ($10),Y
+    let program: Vec<u8> = vec![0xa2, 0x01, 0xbd, 0xff, 0x20, 0xa0, 0x01, 0xb1, 0x10];
+    let mut memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    memory.write(0x0010, 0xff);
+    memory.write(0x0011, 0x20);
+    cpu.set_memory(memory);
+    cpu.reset();
+
+    // The 65C02 reads the last byte of the operand again instead of the unfixed address.
+    let mut accesses: Vec<(u16, bool)> = Vec::new();
+    for _ in 0..4 {
+        let result: p6502::StepResult = cpu.step().unwrap();
+        accesses.extend(
+            result
+                .accesses
+                .iter()
+                .map(|access| (access.addr, access.write)),
+        );
+    }
+    assert_eq!(
+        accesses,
+        vec![
+            (0x0600, false),
+            (0x0601, false),
+            (0x0602, false),
+            (0x0603, false),
+            (0x0604, false),
+            (0x0604, false),
+            (0x2100, false),
+            (0x0605, false),
+            (0x0606, false),
+            (0x0607, false),
+            (0x0608, false),
+            (0x0010, false),
+            (0x0011, false),
+            (0x0608, false),
+            (0x2100, false),
+        ]
+    );
+}
+
+#[test]
+fn tick_wai_and_irq() {
+    let mut cpu: p6502::P6502<PortBus> = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let bus: PortBus = PortBus {
+        memory: memory_with_handler(
+            vec![0x58, 0xcb, 0xa2, 0x01],
+            vec![0xa9, 0x55, 0x8d, 0x00, 0xd0, 0x40],
+        ),
+        ..PortBus::default()
+    };
+    cpu.set_memory(bus);
+    cpu.reset();
+
+    // The clock keeps running while the CPU waits for the interrupt.
+    for _ in 0..30 {
+        assert!(cpu.tick());
+    }
+    assert_eq!(cpu.pc, 0x0602);
+    assert_eq!(cpu.memory.ticks, cpu.cycles());
+
+    cpu.memory.irq = true;
+    while cpu.tick() {}
+    println!("{}", cpu.status());
+
+    assert_eq!(cpu.memory.port, vec![0x55]);
+    assert_eq!(cpu.x, 0x01);
+    assert_eq!(cpu.memory.ticks, cpu.cycles());
+}
+
+#[test]
+fn step_result() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    // LDX #$01; LDA $20FF,X; STA $10; BRK
+    let program: Vec<u8> = vec![0xa2, 0x01, 0xbd, 0xff, 0x20, 0x85, 0x10, 0x00];
+    let mut memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    memory.write(0x2100, 0x42);
+    cpu.set_memory(memory);
+    cpu.reset();
+
+    cpu.step().unwrap();
+    let result: p6502::StepResult = cpu.step().unwrap();
+    println!("{}", cpu.status());
+
+    assert_eq!(result.address, 0x0602);
+    assert_eq!(result.opcode, 0xbd);
+    assert_eq!(result.instruction.mnemonic, Mnemonic::Lda);
+    assert_eq!(result.instruction.mode, AddressingMode::AbsoluteX);
+    assert_eq!(result.interrupt, None);
+    assert_eq!(result.cycles, 5);
+    assert_eq!(result.status, p6502::StepStatus::Running);
+    let accesses: Vec<(u16, u8, bool)> = result
+        .accesses
+        .iter()
+        .map(|access| (access.addr, access.data, access.write))
+        .collect();
+    assert_eq!(
+        accesses,
+        vec![
+            (0x0602, 0xbd, false),
+            (0x0603, 0xff, false),
+            (0x0604, 0x20, false),
+            (0x2000, 0x00, false),
+            (0x2100, 0x42, false),
+        ]
+    );
+
+    let result: p6502::StepResult = cpu.step().unwrap();
+    assert_eq!(result.cycles, 3);
+    assert!(result.accesses.last().unwrap().write);
+    assert_eq!(result.accesses.last().unwrap().addr, 0x0010);
+
+    let result: p6502::StepResult = cpu.step().unwrap();
+    assert_eq!(result.address, 0x0607);
+    assert_eq!(result.status, p6502::StepStatus::Halted);
+    assert_eq!(cpu.memory.data[0x0010], 0x42);
+}
+
+#[test]
+fn step_status() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    // CLI; NOP; JAM
+    let memory: p6502::Memory = memory_with_handler(vec![0x58, 0xea, 0x02], vec![0xe8, 0x40]);
+    cpu.set_memory(memory);
+    cpu.reset();
+
+    cpu.step().unwrap();
+    cpu.irq(true);
+    let result: p6502::StepResult = cpu.step().unwrap();
+    cpu.irq(false);
+    assert_eq!(result.interrupt, Some(p6502::Interrupt::Irq));
+    assert_eq!(result.address, 0x0700);
+    assert_eq!(result.instruction.mnemonic, Mnemonic::Inx);
+    assert_eq!(result.cycles, 7 + 2);
+
+    cpu.step().unwrap();
+    cpu.step().unwrap();
+    assert!(matches!(
+        cpu.step(),
+        Err(Error::Jam {
+            opcode: 0x02,
+            pc: 0x0602
+        })
+    ));
+
+    // Nothing happens once the CPU is jammed.
+    let cycles: usize = cpu.cycles();
+    assert!(matches!(cpu.step(), Err(Error::Jam { pc: 0x0602, .. })));
+    assert_eq!(cpu.cycles(), cycles);
+    println!("{}", cpu.status());
+
+    // STP is not an error, but the CPU stays stopped too.
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(vec![0xdb, 0xe8]);
+    cpu.set_memory(memory);
+    cpu.reset();
+    let result: p6502::StepResult = cpu.step().unwrap();
+    assert_eq!(result.instruction.mnemonic, Mnemonic::Stp);
+    assert_eq!(result.cycles, 3);
+    assert_eq!(result.status, p6502::StepStatus::Stopped);
+    let result: p6502::StepResult = cpu.step().unwrap();
+    assert_eq!(result.cycles, 0);
+    assert!(result.accesses.is_empty());
+    assert_eq!(result.status, p6502::StepStatus::Stopped);
+}
+
+#[test]
+fn run_for_cycles_and_run_until() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    // loop: INX; JMP loop
+    let program: Vec<u8> = vec![0xe8, 0x4c, 0x00, 0x06];
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+
+    let start: usize = cpu.cycles();
+    let status: p6502::StepStatus = cpu.run_for_cycles(100).unwrap();
+    assert_eq!(status, p6502::StepStatus::Running);
+    assert_eq!(cpu.cycles() - start, 100);
+    assert_eq!(cpu.x, 20);
+
+    // Whole instructions only: 1 cycle can't be reached without running a 2-cycle INX.
+    cpu.run_for_cycles(1).unwrap();
+    assert_eq!(cpu.cycles() - start, 102);
+    assert_eq!(cpu.pc, 0x0601);
+
+    let status: p6502::StepStatus = cpu.run_until(|cpu| cpu.x == 0x80).unwrap();
+    println!("{}", cpu.status());
+    assert_eq!(status, p6502::StepStatus::Running);
+    assert_eq!(cpu.x, 0x80);
+    assert_eq!(cpu.pc, 0x0601);
+
+    // Stops when the program does, whatever the predicate says.
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(vec![0xe8, 0x00]);
+    cpu.set_memory(memory);
+    cpu.reset();
+    assert_eq!(cpu.run_until(|_| false).unwrap(), p6502::StepStatus::Halted);
+    assert_eq!(cpu.run_for_cycles(1000).unwrap(), p6502::StepStatus::Halted);
+    assert_eq!(cpu.x, 1);
+}
+
+#[test]
+fn run_for_cycles_waiting() {
+    let mut cpu: p6502::P6502<PortBus> = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let bus: PortBus = PortBus {
+        memory: memory_with_handler(
+            vec![0x58, 0xcb, 0xa2, 0x01],
+            vec![0xa9, 0x55, 0x8d, 0x00, 0xd0, 0x40],
+        ),
+        ..PortBus::default()
+    };
+    cpu.set_memory(bus);
+    cpu.reset();
+
+    // The clock keeps running while the CPU waits for the interrupt.
+    let start: usize = cpu.cycles();
+    assert_eq!(cpu.run_for_cycles(50).unwrap(), p6502::StepStatus::Waiting);
+    assert_eq!(cpu.cycles() - start, 50);
+    assert_eq!(cpu.memory.ticks, cpu.cycles());
+
+    cpu.memory.irq = true;
+    assert_eq!(cpu.run_for_cycles(50).unwrap(), p6502::StepStatus::Halted);
+    println!("{}", cpu.status());
+    assert_eq!(cpu.memory.port, vec![0x55]);
+    assert_eq!(cpu.x, 0x01);
+}
+
+#[test]
+fn illegal_policy() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+    cpu.illegal_policy = p6502::IllegalPolicy::Error;
+
+    // LDX #$01; NOP; NOP (undocumented); LAX $10
+    let program: Vec<u8> = vec![0xa2, 0x01, 0xea, 0x1a, 0xa7, 0x10];
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    cpu.set_memory(memory);
+    cpu.reset();
+    let cycles: usize = cpu.cycles();
+    let result: Result<(), Error> = cpu.execute();
+    println!("{}", cpu.status());
+
+    assert!(matches!(
+        result,
+        Err(Error::IllegalOpcode {
+            opcode: 0x1a,
+            pc: 0x0603
+        })
+    ));
+    assert_eq!(cpu.pc, 0x0603);
+    assert_eq!(cpu.cycles() - cycles, 4);
+
+    // The opcode is not fetched, so the error repeats until it is allowed.
+    assert!(matches!(cpu.step(), Err(Error::IllegalOpcode { .. })));
+    cpu.illegal_policy = p6502::IllegalPolicy::Execute;
+    cpu.step().unwrap();
+    assert_eq!(cpu.pc, 0x0604);
+
+    assert!(!opcode::is_documented(p6502::Variant::Nmos6502, 0xa7));
+    assert!(!opcode::is_documented(p6502::Variant::Cmos65C02, 0x03));
+    assert!(opcode::is_documented(p6502::Variant::Cmos65C02, 0x1a));
+    assert!(opcode::is_documented(p6502::Variant::Nmos6502, 0xea));
+}
+
+#[test]
+fn bus_fault() {
+    let mut cpu: p6502::P6502<MemoryMap> = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let mut map: MemoryMap = MemoryMap::new()
+        .ram(0x0000..=0x7fff)
+        .rom(0x8000..=0xffff, vec![])
+        .rom_writes(RomWrites::Fault);
+    // LDA #$01; STA $8000; INX
+    map.load(0x0600, &[0xa9, 0x01, 0x8d, 0x00, 0x80, 0xe8]);
+    map.load(0xfffc, &[0x00, 0x06]);
+    cpu.set_memory(map);
+    cpu.reset();
+    let result: Result<(), Error> = cpu.execute();
+    println!("{}", cpu.status());
+
+    // The instruction is completed before the execution stops.
+    assert!(matches!(
+        result,
+        Err(Error::BusFault {
+            addr: 0x8000,
+            pc: 0x0602
+        })
+    ));
+    assert_eq!(cpu.pc, 0x0605);
+    assert_eq!(cpu.memory.peek(0x8000), 0x00);
+    assert_eq!(cpu.x, 0x00);
+}
+
+/// A device with a single read-only register, which faults when written to.
+#[derive(Default)]
+struct ReadOnlyDevice {
+    fault: Option<u16>,
+}
+
+impl Bus for ReadOnlyDevice {
+    fn read(&mut self, _addr: u16) -> u8 {
+        0x5a
+    }
+
+    fn write(&mut self, addr: u16, _data: u8) {
+        self.fault = Some(addr);
+    }
+
+    fn peek(&self, _addr: u16) -> u8 {
+        0x5a
+    }
+
+    fn fault(&mut self) -> Option<u16> {
+        self.fault.take()
+    }
+}
+
+#[test]
+fn device_bus_fault() {
+    let mut cpu: p6502::P6502<MemoryMap> = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+
+    let mut map: MemoryMap = MemoryMap::new()
+        .ram(0x0000..=0xffff)
+        .device(0xd000..=0xd00f, Box::new(ReadOnlyDevice::default()));
+    // LDA $D004; STA $D004; INX
+    map.load(0x0600, &[0xad, 0x04, 0xd0, 0x8d, 0x04, 0xd0, 0xe8]);
+    map.load(0xfffc, &[0x00, 0x06]);
+    cpu.set_memory(map);
+    cpu.reset();
+    let result: Result<(), Error> = cpu.execute();
+    println!("{}", cpu.status());
+
+    // The device reports its own offset, which the map turns back into the CPU's address.
+    assert!(matches!(
+        result,
+        Err(Error::BusFault {
+            addr: 0xd004,
+            pc: 0x0603
+        })
+    ));
+    assert_eq!(cpu.a, 0x5a);
+    assert_eq!(cpu.x, 0x00);
+}
+
+#[test]
+fn load_hex_dump() {
+    let program: Vec<u8> = loader::parse_hex_dump("a9 01\n\n8d 00 02\n", false).unwrap();
+    assert_eq!(program, vec![0xa9, 0x01, 0x8d, 0x00, 0x02]);
+
+    let program: Vec<u8> = loader::parse_hex_dump("0600: a2 01\n0602: e8 00\n", true).unwrap();
+    assert_eq!(program, vec![0xa2, 0x01, 0xe8, 0x00]);
+
+    let result: Result<Vec<u8>, Error> = loader::parse_hex_dump("a9 01\n8d  0g 02", false);
+    match result {
+        Err(Error::Load {
+            line,
+            column,
+            token,
+        }) => {
+            assert_eq!((line, column), (2, 5));
+            assert_eq!(token, "0g");
+        }
+        _ => panic!("expected a load error"),
+    }
+
+    // Addresses are not bytes.
+    assert!(matches!(
+        loader::parse_hex_dump("0600: a2 01", false),
+        Err(Error::Load {
+            line: 1,
+            column: 1,
+            ..
+        })
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn read_hex_dump() {
+    assert!(matches!(
+        loader::read_hex_dump("/nonexistent/program.hex", false),
+        Err(Error::Io(_))
+    ));
+}
@@ -0,0 +1,287 @@
+use kw6502::assembler::{self, Assembly};
+use kw6502::bus::{BusAccess, MemoryMap};
+use kw6502::debugger::{Condition, Debugger, Register, Stop, Watch};
+use kw6502::disassembler::Symbols;
+use kw6502::error::Error;
+use kw6502::p6502::{self, Bus};
+
+/// A program which calls a subroutine twice, which in turn calls another one.
+const PROGRAM: &str = r#"
+start:  ldx #0
+        jsr sub
+        inx
+        jsr sub
+        brk
+sub:    lda #5
+        jsr inner
+        rts
+inner:  iny
+        rts
+"#;
+
+/// Returns a CPU ready to run `PROGRAM`, and its symbols.
+fn cpu_with_program() -> (p6502::P6502, Symbols) {
+    let assembly: Assembly = assembler::assemble(PROGRAM, p6502::Variant::Nmos6502).unwrap();
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+    cpu.set_memory(assembly.to_memory());
+    cpu.reset();
+    (cpu, Symbols::from_names(&assembly.symbols))
+}
+
+#[test]
+fn step_next_and_finish() {
+    let (mut cpu, symbols): (p6502::P6502, Symbols) = cpu_with_program();
+    let mut debugger: Debugger = Debugger::new();
+
+    assert_eq!(debugger.step(&mut cpu).unwrap(), Stop::Done);
+    assert_eq!(cpu.pc, 0x0602);
+
+    // The whole subroutine runs, including the one it calls.
+    assert_eq!(debugger.next(&mut cpu).unwrap(), Stop::Done);
+    assert_eq!(cpu.pc, 0x0605);
+    assert_eq!((cpu.a, cpu.y), (5, 1));
+
+    // Anything else is stepped.
+    assert_eq!(debugger.next(&mut cpu).unwrap(), Stop::Done);
+    assert_eq!(cpu.pc, 0x0606);
+
+    let inner: u16 = symbols.find("inner").unwrap();
+    let id: usize = debugger.add_breakpoint(inner, None);
+    assert_eq!(debugger.next(&mut cpu).unwrap(), Stop::Breakpoint(id));
+    assert_eq!(cpu.pc, inner);
+
+    // Out of `inner`, then out of `sub`.
+    assert_eq!(debugger.finish(&mut cpu).unwrap(), Stop::Done);
+    assert_eq!(cpu.pc, 0x060f);
+    assert_eq!(debugger.finish(&mut cpu).unwrap(), Stop::Done);
+    assert_eq!(cpu.pc, 0x0609);
+    println!("{}", cpu.status());
+
+    assert_eq!(
+        debugger.resume(&mut cpu).unwrap(),
+        Stop::Halted(p6502::StepStatus::Halted)
+    );
+}
+
+#[test]
+fn breakpoints() {
+    let (mut cpu, symbols): (p6502::P6502, Symbols) = cpu_with_program();
+    let mut debugger: Debugger = Debugger::new();
+
+    let inner: u16 = symbols.find("inner").unwrap();
+    let condition: Condition = Condition::parse("Y == 1 && X != 0", &symbols).unwrap();
+    let conditional: usize = debugger.add_breakpoint(inner, Some(condition));
+    let plain: usize = debugger.add_breakpoint(symbols.find("sub").unwrap(), None);
+    assert_eq!(debugger.breakpoints().len(), 2);
+
+    // The first call only stops at `sub`.
+    assert_eq!(debugger.resume(&mut cpu).unwrap(), Stop::Breakpoint(plain));
+    assert!(debugger.enable_breakpoint(plain, false));
+    assert_eq!(
+        debugger.resume(&mut cpu).unwrap(),
+        Stop::Breakpoint(conditional)
+    );
+    assert_eq!((cpu.x, cpu.y), (1, 1));
+
+    assert!(debugger.remove_breakpoint(conditional));
+    assert!(!debugger.remove_breakpoint(conditional));
+    assert_eq!(
+        debugger.resume(&mut cpu).unwrap(),
+        Stop::Halted(p6502::StepStatus::Halted)
+    );
+    assert_eq!(cpu.y, 2);
+}
+
+#[test]
+fn watchpoints() {
+    let source: &str = r#"
+        count = $10
+start:  ldx #0
+.loop:  lda table,x
+        sta count
+        inx
+        cpx #3
+        bne .loop
+        brk
+table:  .byte 7, 0, 9
+"#;
+    let assembly: Assembly = assembler::assemble(source, p6502::Variant::Nmos6502).unwrap();
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+    cpu.set_memory(assembly.to_memory());
+    cpu.reset();
+    let mut debugger: Debugger = Debugger::new();
+
+    // Only the write of a zero stops, once the STA is finished.
+    let zero: usize = debugger.add_watchpoint(0x0010..=0x0010, Watch::Write, Some(0));
+    assert_eq!(
+        debugger.resume(&mut cpu).unwrap(),
+        Stop::Watchpoint {
+            id: zero,
+            pc: 0x0605,
+            access: BusAccess {
+                addr: 0x0010,
+                data: 0,
+                write: true
+            }
+        }
+    );
+    assert_eq!((cpu.pc, cpu.x), (0x0607, 1));
+
+    // Fetching the instructions doesn't count as reading them.
+    let code: usize = debugger.add_watchpoint(0x0602..=0x0606, Watch::Read, None);
+    let table: usize = debugger.add_watchpoint(0x060f..=0x060f, Watch::Access, None);
+    match debugger.resume(&mut cpu).unwrap() {
+        Stop::Watchpoint { id, pc, access } => {
+            assert_eq!(
+                (id, pc, access.data, access.write),
+                (table, 0x0602, 9, false)
+            )
+        }
+        other => panic!("expected the table watchpoint, got {:?}", other),
+    }
+
+    // Executing stops before the instruction runs.
+    let inx: usize = debugger.add_watchpoint(0x0607..=0x0607, Watch::Execute, None);
+    match debugger.resume(&mut cpu).unwrap() {
+        Stop::Watchpoint { id, pc, access } => {
+            assert_eq!((id, pc, access.data), (inx, 0x0607, 0xe8))
+        }
+        other => panic!("expected the execute watchpoint, got {:?}", other),
+    }
+    assert_eq!(cpu.x, 2);
+
+    assert!(debugger.enable_watchpoint(inx, false));
+    assert!(debugger.remove_watchpoint(table));
+    assert_eq!(debugger.watchpoints().len(), 3);
+    assert_eq!(debugger.watchpoints()[1].id, code);
+    assert_eq!(
+        debugger.resume(&mut cpu).unwrap(),
+        Stop::Halted(p6502::StepStatus::Halted)
+    );
+    assert_eq!(cpu.memory.read(0x0010), 9);
+}
+
+#[test]
+fn stepping_back() {
+    let (mut cpu, _): (p6502::P6502, Symbols) = cpu_with_program();
+    let mut debugger: Debugger = Debugger::new();
+    let start: Vec<u8> = cpu.save_state().unwrap().as_bytes().to_vec();
+
+    // The JSRs push their return addresses, which is undone as well.
+    for _ in 0..6 {
+        assert_eq!(debugger.step(&mut cpu).unwrap(), Stop::Done);
+    }
+    assert_eq!(debugger.history_len(), 6);
+    for _ in 0..6 {
+        assert_eq!(debugger.step_back(&mut cpu), Stop::Done);
+    }
+    assert_eq!(cpu.save_state().unwrap().as_bytes(), &start[..]);
+    assert_eq!(debugger.step_back(&mut cpu), Stop::HistoryStart);
+
+    // Only the last instructions are remembered.
+    debugger.set_history_limit(2);
+    for _ in 0..3 {
+        debugger.step(&mut cpu).unwrap();
+    }
+    assert_eq!(debugger.step_back(&mut cpu), Stop::Done);
+    assert_eq!(debugger.step_back(&mut cpu), Stop::Done);
+    assert_eq!(debugger.step_back(&mut cpu), Stop::HistoryStart);
+    assert_eq!(cpu.pc, 0x0602);
+}
+
+#[test]
+fn reverse_resume() {
+    let (mut cpu, symbols): (p6502::P6502, Symbols) = cpu_with_program();
+    let mut debugger: Debugger = Debugger::new();
+    assert_eq!(
+        debugger.resume(&mut cpu).unwrap(),
+        Stop::Halted(p6502::StepStatus::Halted)
+    );
+
+    // Back to the second call of `inner`, then to the first one.
+    let inner: u16 = symbols.find("inner").unwrap();
+    let id: usize = debugger.add_breakpoint(inner, None);
+    assert_eq!(debugger.reverse_resume(&mut cpu), Stop::Breakpoint(id));
+    assert_eq!((cpu.pc, cpu.x, cpu.y), (inner, 1, 1));
+    assert_eq!(debugger.reverse_resume(&mut cpu), Stop::Breakpoint(id));
+    assert_eq!((cpu.pc, cpu.x, cpu.y), (inner, 0, 0));
+
+    // The first JSR pushes its return address, which is written again when going forwards.
+    debugger.remove_breakpoint(id);
+    let watch: usize = debugger.add_watchpoint(0x01fc..=0x01fd, Watch::Write, None);
+    assert_eq!(
+        debugger.reverse_resume(&mut cpu),
+        Stop::Watchpoint {
+            id: watch,
+            pc: 0x0602,
+            access: BusAccess {
+                addr: 0x01fd,
+                data: 0x06,
+                write: true
+            }
+        }
+    );
+    assert_eq!((cpu.pc, cpu.sp), (0x0602, 0xfd));
+    assert_eq!(debugger.reverse_resume(&mut cpu), Stop::HistoryStart);
+    assert_eq!(cpu.pc, 0x0600);
+}
+
+#[test]
+fn conditions() {
+    let mut symbols: Symbols = Symbols::new();
+    symbols.insert(0x0010, "count");
+
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.a = 0x10;
+    cpu.x = 2;
+    cpu.flags.c = true;
+    cpu.memory.write(0x0010, 3);
+    cpu.memory.write(0x0012, 0xff);
+
+    let holds = |text: &str| Condition::parse(text, &symbols).unwrap().evaluate(&cpu);
+    assert!(holds("A == $10 && [count] != 0"));
+    assert!(holds("[count + X] == 255"));
+    assert!(holds("(X + 1) & %11 == 3"));
+    assert!(holds("x < 3 || [$ffff] == 1"));
+    assert!(holds("C && !Z"));
+    assert!(holds("a >= 16 && a <= 16 && a > 15"));
+    assert!(!holds("[count] - 4 < 0"));
+
+    for text in ["A ==", "(A", "[1", "missing == 1", "A # 1"].iter() {
+        match Condition::parse(text, &symbols) {
+            Err(Error::Expression(message)) => println!("{}: {}", text, message),
+            other => panic!(
+                "expected an expression error for '{}', got {:?}",
+                text, other
+            ),
+        }
+    }
+}
+
+#[test]
+fn registers_and_memory_editing() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+
+    Register::parse("pc").unwrap().set(&mut cpu, 0x1234);
+    Register::parse("A").unwrap().set(&mut cpu, 0x1ff);
+    Register::parse("s").unwrap().set(&mut cpu, 0x80);
+    Register::parse("Z").unwrap().set(&mut cpu, 1);
+    assert_eq!((cpu.pc, cpu.a, cpu.sp), (0x1234, 0xff, 0x80));
+    assert!(cpu.flags.z);
+    assert_eq!(Register::P.get(&cpu) & 0x02, 0x02);
+    assert_eq!(Register::parse("Q"), None);
+
+    Register::P.set(&mut cpu, 0x81);
+    assert!(cpu.flags.n && cpu.flags.c && !cpu.flags.z);
+
+    // Poking changes ROM, which the CPU can't write.
+    let mut map: MemoryMap = MemoryMap::new()
+        .ram(0x0000..=0x7fff)
+        .rom(0x8000..=0xffff, vec![0xea; 0x8000]);
+    map.write(0x8000, 0x00);
+    assert_eq!(map.peek(0x8000), 0xea);
+    map.poke(0x8000, 0x00);
+    assert_eq!(map.peek(0x8000), 0x00);
+}
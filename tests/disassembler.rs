@@ -0,0 +1,105 @@
+use kw6502::assembler::{self, Assembly};
+use kw6502::disassembler::{self, Instruction, Symbols};
+use kw6502::opcode::{self, Mnemonic};
+use kw6502::p6502::{self, Bus};
+
+#[test]
+fn disassembly_format() {
+    // JMP $C5F5; LDX #$00; STX $00; LDA ($10),Y; ASL A; BNE *-2; BBS3 $12,*+3
+    let program: Vec<u8> = vec![
+        0x4c, 0xf5, 0xc5, 0xa2, 0x00, 0x86, 0x00, 0xb1, 0x10, 0x0a, 0xd0, 0xfc, 0xbf, 0x12, 0x00,
+    ];
+    let memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+
+    let lines: Vec<String> = disassembler::disassemble(
+        &memory,
+        p6502::Variant::Cmos65C02,
+        0x0600,
+        0x060c,
+        &Symbols::new(),
+    )
+    .iter()
+    .map(|instruction| instruction.to_string())
+    .collect();
+
+    assert_eq!(
+        lines,
+        vec![
+            "0600  4C F5 C5  JMP $C5F5",
+            "0603  A2 00     LDX #$00",
+            "0605  86 00     STX $00",
+            "0607  B1 10     LDA ($10),Y",
+            "0609  0A        ASL A",
+            "060A  D0 FC     BNE $0608",
+            "060C  BF 12 00  BBS3 $12,$060F",
+        ]
+    );
+}
+
+#[test]
+fn disassembly_symbols() {
+    let source: &str = "ptr = $10\nstart: lda (ptr),y\n.loop: jsr print\n bne .loop\nprint: rts";
+    let assembly: Assembly = assembler::assemble(source, p6502::Variant::Nmos6502).unwrap();
+    let symbols: Symbols = Symbols::from_names(&assembly.symbols);
+    let mut memory: p6502::Memory = p6502::Memory::default();
+    assembly.load_into(&mut memory);
+
+    let instructions: Vec<Instruction> =
+        disassembler::disassemble(&memory, p6502::Variant::Nmos6502, 0x0600, 0x0607, &symbols);
+
+    let texts: Vec<String> = instructions.iter().map(|line| line.text()).collect();
+    assert_eq!(
+        texts,
+        vec!["LDA (ptr),Y", "JSR print", "BNE start.loop", "RTS"]
+    );
+    assert_eq!(instructions[0].label.as_deref(), Some("start"));
+    assert_eq!(instructions[1].label.as_deref(), Some("start.loop"));
+    assert_eq!(instructions[2].label, None);
+    assert_eq!(instructions[3].next_address(), 0x0608);
+}
+
+#[test]
+fn disassembly_round_trip() {
+    // Whatever is disassembled assembles back into the same bytes.
+    for variant in [p6502::Variant::Nmos6502, p6502::Variant::Cmos65C02].iter() {
+        for byte in 0..=255_u8 {
+            if !opcode::is_documented(*variant, byte) {
+                continue;
+            }
+
+            let mut memory: p6502::Memory = p6502::Memory::default();
+            memory.write(0x0600, byte);
+            memory.write(0x0601, 0x34);
+            memory.write(0x0602, 0x12);
+
+            let instruction: Instruction =
+                disassembler::disassemble_instruction(&memory, *variant, 0x0600, &Symbols::new());
+            let assembly: Assembly = assembler::assemble(&instruction.text(), *variant).unwrap();
+            assert_eq!(
+                assembly.chunks[0].bytes, instruction.bytes,
+                "{}",
+                instruction
+            );
+        }
+    }
+}
+
+#[test]
+fn disassembly_wraps() {
+    let mut memory: p6502::Memory = p6502::Memory::default();
+    memory.write(0xffff, 0xad);
+    memory.write(0x0000, 0x34);
+    memory.write(0x0001, 0x12);
+
+    let instructions: Vec<Instruction> = disassembler::disassemble(
+        &memory,
+        p6502::Variant::Nmos6502,
+        0xffff,
+        0xffff,
+        &Symbols::new(),
+    );
+    assert_eq!(instructions.len(), 1);
+    assert_eq!(instructions[0].opcode.mnemonic, Mnemonic::Lda);
+    assert_eq!(instructions[0].operand, "$1234");
+    assert_eq!(instructions[0].next_address(), 0x0002);
+}
@@ -0,0 +1,115 @@
+//! Klaus Dormann's 6502 test suites (https://github.com/Klaus2m5/6502_65C02_functional_tests).
+//! Their binaries are not part of the repository, so the tests are ignored by default: once the
+//! binaries are in `tests/roms`, run them with `cargo test -- --ignored`. See
+//! `tests/roms/README.md`.
+
+use std::path::PathBuf;
+
+use kw6502::error::Error;
+use kw6502::p6502::{self, Bus};
+
+/// How many cycles a test program may run before it is considered stuck. Both suites need less
+/// than 100 million.
+const MAX_CYCLES: usize = 200_000_000;
+
+/// Where the functional test loops when every test passed, in the binary distributed with it.
+const FUNCTIONAL_SUCCESS: u16 = 0x3469;
+
+/// Where the functional test keeps the number of the test being run.
+const FUNCTIONAL_TEST_CASE: u16 = 0x0200;
+
+/// Where the decimal test is assembled and starts.
+const DECIMAL_START: u16 = 0x0200;
+
+/// Where the decimal test stores 0 if every test passed, and 1 otherwise.
+const DECIMAL_ERROR: u16 = 0x000b;
+
+/// Returns a CPU with a test program in memory and the Program Counter pointing at `start`.
+/// A 64 KB image is loaded as is, anything smaller at `start`.
+fn load_test(name: &str, start: u16) -> p6502::P6502 {
+    let path: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("roms")
+        .join(name);
+    let binary: Vec<u8> = std::fs::read(&path).unwrap_or_else(|error| {
+        panic!(
+            "unable to read {}: {} (see tests/roms/README.md)",
+            path.display(),
+            error
+        )
+    });
+
+    let mut memory: p6502::Memory = p6502::Memory::default();
+    let offset: usize = if binary.len() == memory.data.len() {
+        0
+    } else {
+        start as usize
+    };
+    memory.data[offset..offset + binary.len()].copy_from_slice(&binary);
+
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.set_memory(memory);
+    cpu.pc = start;
+    cpu
+}
+
+/// Runs a test program until it traps (an instruction jumps or branches to itself), stops or
+/// runs out of cycles. Returns the address where it ended, if it did.
+fn run_to_end(cpu: &mut p6502::P6502) -> Option<u16> {
+    let mut previous: u16 = cpu.pc;
+    let result: Result<p6502::StepStatus, Error> = cpu.run_until(|cpu| {
+        let trapped: bool = cpu.pc == previous;
+        previous = cpu.pc;
+        trapped || cpu.cycles() >= MAX_CYCLES
+    });
+
+    match result {
+        Ok(_) if cpu.cycles() >= MAX_CYCLES => None,
+        Ok(_) => Some(cpu.pc),
+        Err(error) => {
+            println!("The program stopped: {}.", error);
+            Some(cpu.pc)
+        }
+    }
+}
+
+#[test]
+#[ignore = "needs tests/roms/*.bin"]
+fn functional_test() {
+    let mut cpu: p6502::P6502 = load_test("6502_functional_test.bin", 0x0400);
+
+    let end: Option<u16> = run_to_end(&mut cpu);
+    let test_case: u8 = cpu.memory.peek(FUNCTIONAL_TEST_CASE);
+    assert_eq!(
+        end,
+        Some(FUNCTIONAL_SUCCESS),
+        "test ${:02X} failed after {} cycles\n{}",
+        test_case,
+        cpu.cycles(),
+        cpu.status()
+    );
+}
+
+#[test]
+#[ignore = "needs tests/roms/*.bin"]
+fn decimal_test() {
+    let mut cpu: p6502::P6502 = load_test("6502_decimal_test.bin", DECIMAL_START);
+    // The test ends with a 65C02 STP, which is undocumented on the NMOS 6502.
+    cpu.illegal_policy = p6502::IllegalPolicy::Error;
+
+    let end: Option<u16> = run_to_end(&mut cpu);
+    assert!(end.is_some(), "the test didn't finish\n{}", cpu.status());
+
+    // On failure, the operands are left at $00 and $01, and the expected and actual results
+    // at $06 and $04.
+    let memory: &p6502::Memory = &cpu.memory;
+    assert_eq!(
+        memory.peek(DECIMAL_ERROR),
+        0,
+        "${:02X} and ${:02X} gave ${:02X} instead of ${:02X}",
+        memory.peek(0x0000),
+        memory.peek(0x0001),
+        memory.peek(0x0004),
+        memory.peek(0x0006)
+    );
+}
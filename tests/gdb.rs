@@ -0,0 +1,205 @@
+#![cfg(feature = "std")]
+
+use std::io::{self, Cursor, Read, Write};
+
+use kw6502::assembler::{self, Assembly};
+use kw6502::debugger::Debugger;
+use kw6502::gdb::{Connection, GdbStub};
+use kw6502::p6502::{self, Bus};
+
+/// A connection to GDB which sends prepared packets and records the replies.
+struct MockStream {
+    input: Cursor<Vec<u8>>,
+    output: Vec<u8>,
+}
+
+impl Read for MockStream {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.input.read(buffer)
+    }
+}
+
+impl Write for MockStream {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        self.output.extend_from_slice(buffer);
+        Ok(buffer.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Connection for MockStream {
+    fn try_read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte: [u8; 1] = [0];
+        match self.input.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+}
+
+/// Frames the data of a packet.
+fn packet(data: &str) -> String {
+    let checksum: u8 = data.bytes().fold(0, |sum, byte| sum.wrapping_add(byte));
+    format!("${}#{:02x}", data, checksum)
+}
+
+/// Sends packets to a stub serving the CPU and returns the acknowledgements and the data of
+/// the replies.
+fn session(cpu: &mut p6502::P6502, debugger: &mut Debugger, input: &str) -> (String, Vec<String>) {
+    let stream: MockStream = MockStream {
+        input: Cursor::new(input.as_bytes().to_vec()),
+        output: Vec::new(),
+    };
+    let mut stub: GdbStub<MockStream> = GdbStub::new(stream);
+    stub.serve(cpu, debugger).unwrap();
+    let output: String = String::from_utf8(stub.into_inner().output).unwrap();
+    println!("{}", output);
+
+    // The acknowledgements are the characters between the packets.
+    let mut acks: String = String::new();
+    let mut replies: Vec<String> = Vec::new();
+    let mut rest: &str = &output;
+    while let Some(character) = rest.chars().next() {
+        if character == '$' {
+            let end: usize = rest.find('#').unwrap();
+            let data: &str = &rest[1..end];
+            assert_eq!(&rest[..end + 3], packet(data));
+            replies.push(data.to_string());
+            rest = &rest[end + 3..];
+        } else {
+            acks.push(character);
+            rest = &rest[1..];
+        }
+    }
+    (acks, replies)
+}
+
+#[test]
+fn gdb_session() {
+    let source: &str = r#"
+start:  ldx #0
+        jsr sub
+        inx
+        stx $10
+        brk
+sub:    iny
+        rts
+"#;
+    let assembly: Assembly = assembler::assemble(source, p6502::Variant::Nmos6502).unwrap();
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+    cpu.set_memory(assembly.to_memory());
+    cpu.reset();
+    let mut debugger: Debugger = Debugger::new();
+
+    let requests: Vec<&str> = vec![
+        "qSupported:multiprocess+",
+        "?",
+        "g",
+        "m0600,3",
+        "Z0,609,1",
+        "c",
+        "p4",
+        "z0,609,1",
+        "Z2,10,1",
+        "c",
+        "P0=2a",
+        "p0",
+        "M0200,2:beef",
+        "m0200,2",
+        "vCont?",
+        "s",
+        "bc",
+        "D",
+    ];
+    let input: String = requests.iter().map(|data| packet(data)).collect();
+    let (acks, replies): (String, Vec<String>) = session(&mut cpu, &mut debugger, &input);
+
+    assert_eq!(acks, "+".repeat(requests.len()));
+    assert_eq!(
+        replies,
+        vec![
+            "PacketSize=1000;QStartNoAckMode+;ReverseStep+;ReverseContinue+",
+            "S05",
+            "000000fd000634",
+            "a20020",
+            "OK",
+            "S05",
+            "0906",
+            "OK",
+            "OK",
+            "T05watch:0010;",
+            "OK",
+            "2a",
+            "OK",
+            "beef",
+            "",
+            "S05",
+            "T05replaylog:begin;",
+            "OK",
+        ]
+    );
+    assert_eq!(cpu.memory.read(0x0010), 1);
+    assert_eq!(cpu.a, 0x2a);
+
+    // What GDB set is gone once it detaches.
+    assert!(debugger.breakpoints().is_empty());
+    assert!(debugger.watchpoints().is_empty());
+}
+
+#[test]
+fn gdb_interrupt() {
+    let source: &str = "loop:   jmp loop\n";
+    let assembly: Assembly = assembler::assemble(source, p6502::Variant::Nmos6502).unwrap();
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.set_memory(assembly.to_memory());
+    cpu.reset();
+    let mut debugger: Debugger = Debugger::new();
+
+    // The interrupt is only noticed once the continue is running, and the packet after it is
+    // still answered.
+    let input: String = format!("{}\x03{}", packet("c"), packet("p4"));
+    let (acks, replies): (String, Vec<String>) = session(&mut cpu, &mut debugger, &input);
+
+    assert_eq!(acks, "++");
+    assert_eq!(replies, vec!["S02", "0006"]);
+}
+
+#[test]
+fn gdb_stop() {
+    let source: &str = "        stp\n";
+    let assembly: Assembly = assembler::assemble(source, p6502::Variant::Cmos65C02).unwrap();
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+    cpu.set_memory(assembly.to_memory());
+    cpu.reset();
+    let mut debugger: Debugger = Debugger::new();
+
+    // The CPU is stopped, but it can still be inspected.
+    let input: String = format!("{}{}", packet("c"), packet("p4"));
+    let (acks, replies): (String, Vec<String>) = session(&mut cpu, &mut debugger, &input);
+
+    assert_eq!(acks, "++");
+    assert_eq!(replies, vec!["S04", "0006"]);
+}
+
+#[test]
+fn gdb_framing() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.pc = 0x1234;
+    let mut debugger: Debugger = Debugger::new();
+
+    // A corrupted packet is rejected, then the acknowledgements stop.
+    let input: String = format!(
+        "+$p4#00{}{}\x03{}",
+        packet("p4"),
+        packet("QStartNoAckMode"),
+        packet("p4")
+    );
+    let (acks, replies): (String, Vec<String>) = session(&mut cpu, &mut debugger, &input);
+
+    assert_eq!(acks, "-++");
+    assert_eq!(replies, vec!["3412", "OK", "3412"]);
+}
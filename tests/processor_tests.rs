@@ -0,0 +1,314 @@
+//! A harness for the single-step tests of Tom Harte's ProcessorTests
+//! (https://github.com/SingleStepTests/65x02). Every JSON file holds the cases of one opcode:
+//! the registers and the memory before and after executing a single instruction, and its bus
+//! activity cycle by cycle.
+//!
+//! The suites are read from `tests/processor_tests`, laid out as upstream: one directory per
+//! variant (`6502`, `wdc65c02` and `nes6502`) holding the files in `v1`, from `00.json` to
+//! `ff.json`. The files may also be placed directly in the directory of the variant. Every opcode
+//! must have its file. The suites are not part of the repository, so their tests are ignored by
+//! default: once they are copied there, run them with
+//! `cargo test --test processor_tests -- --ignored`. The vectors in `tests/processor_tests/sample`
+//! are written by hand in the same format and only check the harness itself.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use kw6502::bus::BusAccess;
+use kw6502::error::Error;
+use kw6502::p6502::{self, Bus, P6502Flags, Variant};
+
+/// How many failing cases are shown.
+const MAX_REPORTED: usize = 20;
+
+/// A memory which records every access, so that they are known even when the CPU jams.
+#[derive(Default)]
+struct RecordingBus {
+    memory: p6502::Memory,
+    accesses: Vec<BusAccess>,
+}
+
+impl Bus for RecordingBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        let data: u8 = self.memory.read(addr);
+        self.accesses.push(BusAccess {
+            addr,
+            data,
+            write: false,
+        });
+        data
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        self.memory.write(addr, data);
+        self.accesses.push(BusAccess {
+            addr,
+            data,
+            write: true,
+        });
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.memory.peek(addr)
+    }
+}
+
+/// The registers and the memory before or after a case.
+struct State {
+    pc: u16,
+    s: u8,
+    a: u8,
+    x: u8,
+    y: u8,
+    p: u8,
+    ram: Vec<(u16, u8)>,
+}
+
+impl State {
+    fn from_json(value: &Value) -> Self {
+        let ram: Vec<(u16, u8)> = value["ram"]
+            .as_array()
+            .expect("the RAM is not a list")
+            .iter()
+            .map(|entry| (number(&entry[0]) as u16, number(&entry[1]) as u8))
+            .collect();
+
+        Self {
+            pc: number(&value["pc"]) as u16,
+            s: number(&value["s"]) as u8,
+            a: number(&value["a"]) as u8,
+            x: number(&value["x"]) as u8,
+            y: number(&value["y"]) as u8,
+            p: number(&value["p"]) as u8,
+            ram,
+        }
+    }
+}
+
+/// Reads a number of a test vector.
+fn number(value: &Value) -> u64 {
+    value
+        .as_u64()
+        .unwrap_or_else(|| panic!("{} is not a number", value))
+}
+
+/// Runs a case and returns what differs from the expected results.
+fn run_case(cpu: &mut p6502::P6502<RecordingBus>, case: &Value) -> Vec<String> {
+    let initial: State = State::from_json(&case["initial"]);
+    let expected: State = State::from_json(&case["final"]);
+
+    // Resetting only clears the internal state, such as a JAM, before the registers are set.
+    cpu.reset();
+    cpu.pc = initial.pc;
+    cpu.sp = initial.s;
+    cpu.a = initial.a;
+    cpu.x = initial.x;
+    cpu.y = initial.y;
+    cpu.flags = P6502Flags::from_binary(initial.p);
+    for (addr, data) in initial.ram.iter() {
+        cpu.memory.memory.write(*addr, *data);
+    }
+    cpu.memory.accesses.clear();
+
+    // The JAM opcodes lock up the CPU, which is what the vectors expect of them.
+    let mut mismatches: Vec<String> = Vec::new();
+    match cpu.step() {
+        Ok(_) | Err(Error::Jam { .. }) => {}
+        Err(error) => {
+            mismatches.push(format!("the CPU failed: {}", error));
+            return mismatches;
+        }
+    }
+    let accesses: Vec<BusAccess> = std::mem::take(&mut cpu.memory.accesses);
+
+    // The B flag and bit 5 only exist on the stack.
+    let registers: [(&str, u16, u16); 6] = [
+        ("PC", expected.pc, cpu.pc),
+        ("S", expected.s as u16, cpu.sp as u16),
+        ("A", expected.a as u16, cpu.a as u16),
+        ("X", expected.x as u16, cpu.x as u16),
+        ("Y", expected.y as u16, cpu.y as u16),
+        (
+            "P",
+            (expected.p | 0x30) as u16,
+            (cpu.flags.as_binary() | 0x30) as u16,
+        ),
+    ];
+    for (name, expected, found) in registers.iter() {
+        if expected != found {
+            let width: usize = if *name == "PC" { 4 } else { 2 };
+            mismatches.push(format!(
+                "{} is ${:0width$X} instead of ${:0width$X}",
+                name,
+                found,
+                expected,
+                width = width
+            ));
+        }
+    }
+
+    for (addr, data) in expected.ram.iter() {
+        let found: u8 = cpu.memory.peek(*addr);
+        if found != *data {
+            mismatches.push(format!(
+                "${:04X} holds ${:02X} instead of ${:02X}",
+                addr, found, data
+            ));
+        }
+    }
+
+    let cycles: &Vec<Value> = case["cycles"]
+        .as_array()
+        .expect("the cycles are not a list");
+    for index in 0..cycles.len().max(accesses.len()) {
+        let expected: Option<String> = cycles.get(index).map(|cycle| {
+            let kind: &str = cycle[2]
+                .as_str()
+                .expect("the kind of cycle is not a string");
+            describe_access(
+                number(&cycle[0]) as u16,
+                number(&cycle[1]) as u8,
+                kind == "write",
+            )
+        });
+        let found: Option<String> = accesses
+            .get(index)
+            .map(|access| describe_access(access.addr, access.data, access.write));
+        if expected != found {
+            mismatches.push(format!(
+                "cycle {} is {} instead of {}",
+                index + 1,
+                found.as_deref().unwrap_or("missing"),
+                expected.as_deref().unwrap_or("missing")
+            ));
+        }
+    }
+
+    mismatches
+}
+
+/// Describes a bus access as the reports show it.
+fn describe_access(addr: u16, data: u8, write: bool) -> String {
+    if write {
+        format!("a write of ${:02X} to ${:04X}", data, addr)
+    } else {
+        format!("a read of ${:02X} from ${:04X}", data, addr)
+    }
+}
+
+/// Returns the directory holding the vectors of a variant.
+fn vectors_path(directory: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("processor_tests")
+        .join(directory)
+}
+
+/// Runs every case of the files of a variant, in the order of the opcodes.
+fn run_files(directory: &str, files: &[PathBuf], variant: Variant) {
+    let mut cpu: p6502::P6502<RecordingBus> = p6502::P6502::with_variant(variant);
+    let mut cases: usize = 0;
+    let mut failures: Vec<String> = Vec::new();
+    for file in files.iter() {
+        let text: String = fs::read_to_string(file)
+            .unwrap_or_else(|error| panic!("unable to read {}: {}", file.display(), error));
+        let vectors: Value = serde_json::from_str(&text)
+            .unwrap_or_else(|error| panic!("{} is not valid JSON: {}", file.display(), error));
+
+        for case in vectors.as_array().expect("the cases are not a list") {
+            cases += 1;
+            let mismatches: Vec<String> = run_case(&mut cpu, case);
+            if !mismatches.is_empty() {
+                failures.push(format!(
+                    "{} '{}': {}",
+                    file.file_name().unwrap().to_string_lossy(),
+                    case["name"].as_str().unwrap_or("?"),
+                    mismatches.join(", ")
+                ));
+            }
+        }
+    }
+
+    println!("{}: {} cases in {} files", directory, cases, files.len());
+    assert!(
+        failures.is_empty(),
+        "{} of {} cases failed:\n{}",
+        failures.len(),
+        cases,
+        failures
+            .iter()
+            .take(MAX_REPORTED)
+            .cloned()
+            .collect::<Vec<String>>()
+            .join("\n")
+    );
+}
+
+/// Runs the full suite of a variant, which must have a file for every opcode.
+fn run_suite(directory: &str, variant: Variant) {
+    let mut path: PathBuf = vectors_path(directory);
+    if path.join("v1").is_dir() {
+        path.push("v1");
+    }
+    let files: Vec<PathBuf> = (0..=0xFF)
+        .map(|opcode: u8| path.join(format!("{:02x}.json", opcode)))
+        .collect();
+    let missing: Vec<String> = files
+        .iter()
+        .filter(|file| !file.is_file())
+        .map(|file| file.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert!(
+        missing.is_empty(),
+        "{} of the 256 opcodes have no vectors in {}: {}",
+        missing.len(),
+        path.display(),
+        missing.join(", ")
+    );
+
+    run_files(directory, &files, variant);
+}
+
+/// Runs the hand-written vectors of a variant, whichever opcodes they cover.
+fn run_sample(directory: &str, variant: Variant) {
+    let path: PathBuf = vectors_path(&format!("sample/{}", directory));
+    let mut files: Vec<PathBuf> = fs::read_dir(&path)
+        .unwrap_or_else(|error| panic!("unable to read {}: {}", path.display(), error))
+        .map(|entry| entry.unwrap().path())
+        .filter(|file| {
+            file.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    files.sort();
+
+    assert!(!files.is_empty(), "{} holds no vectors", path.display());
+    run_files(directory, &files, variant);
+}
+
+#[test]
+fn sample_vectors() {
+    run_sample("6502", Variant::Nmos6502);
+    run_sample("wdc65c02", Variant::Cmos65C02);
+    run_sample("nes6502", Variant::Ricoh2A03);
+}
+
+#[test]
+#[ignore = "needs the SingleStepTests vectors in tests/processor_tests/6502"]
+fn nmos_6502() {
+    run_suite("6502", Variant::Nmos6502);
+}
+
+#[test]
+#[ignore = "needs the SingleStepTests vectors in tests/processor_tests/wdc65c02"]
+fn wdc_65c02() {
+    run_suite("wdc65c02", Variant::Cmos65C02);
+}
+
+#[test]
+#[ignore = "needs the SingleStepTests vectors in tests/processor_tests/nes6502"]
+fn ricoh_2a03() {
+    run_suite("nes6502", Variant::Ricoh2A03);
+}
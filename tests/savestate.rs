@@ -0,0 +1,215 @@
+use kw6502::bus::{Memory, MemoryMap};
+use kw6502::error::{Error, Result};
+use kw6502::p6502::{self, Bus};
+use kw6502::savestate::SaveState;
+
+/// A program which keeps changing the registers and the memory: it fills $0200-$02FF with a
+/// running sum and stops.
+const PROGRAM: [u8; 14] = [
+    0xa2, 0x00, // LDX #$00
+    0x18, // CLC
+    0x69, 0x07, // ADC #$07
+    0x9d, 0x00, 0x02, // STA $0200,X
+    0xe8, // INX
+    0xd0, 0xf7, // BNE *-9
+    0x00, // BRK
+    0xea, 0xea,
+];
+
+/// Returns the registers, the cycles and the memory which the program changes.
+fn machine(cpu: &p6502::P6502) -> (u16, u8, u8, u8, u8, u8, usize, Vec<u8>) {
+    (
+        cpu.pc,
+        cpu.sp,
+        cpu.a,
+        cpu.x,
+        cpu.y,
+        cpu.flags.as_binary(),
+        cpu.cycles(),
+        cpu.memory.data[0x0200..0x0300].to_vec(),
+    )
+}
+
+/// Returns the message of a save state error.
+fn state_error<T: std::fmt::Debug>(result: Result<T>) -> String {
+    match result {
+        Err(Error::SaveState(message)) => message,
+        other => panic!("expected a save state error, got {:?}", other),
+    }
+}
+
+#[test]
+fn save_and_restore() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+    cpu.set_memory(Memory::from_program_vec(PROGRAM.to_vec()));
+    cpu.reset();
+    cpu.run_for_cycles(2000).unwrap();
+    let checkpoint: SaveState = cpu.save_state().unwrap();
+    let middle = machine(&cpu);
+
+    cpu.execute().unwrap();
+    let end = machine(&cpu);
+    println!("{}", cpu.status());
+
+    // Restoring goes back in time, and the rest of the run is the same again.
+    cpu.load_state(&checkpoint).unwrap();
+    assert_eq!(machine(&cpu), middle);
+    cpu.execute().unwrap();
+    assert_eq!(machine(&cpu), end);
+
+    // Into another CPU as well, through the binary format.
+    let mut other: p6502::P6502 = p6502::P6502::default();
+    other.break_policy = p6502::BreakPolicy::Halt;
+    let state: SaveState = SaveState::from_bytes(checkpoint.as_bytes().to_vec()).unwrap();
+    other.load_state(&state).unwrap();
+    assert_eq!(machine(&other), middle);
+    other.execute().unwrap();
+    assert_eq!(machine(&other), end);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn save_state_file() {
+    let mut cpu: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+    cpu.set_memory(Memory::from_program_vec(PROGRAM.to_vec()));
+    cpu.reset();
+    cpu.run_for_cycles(100).unwrap();
+
+    let path: std::path::PathBuf = std::env::temp_dir().join("kw6502_save_state_file.sav");
+    cpu.save_state().unwrap().write_file(&path).unwrap();
+    let state: SaveState = SaveState::read_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(state, cpu.save_state().unwrap());
+    // A header, the registers and the 64 KB.
+    assert_eq!(state.as_bytes().len(), 33 + 0x10000);
+
+    let mut other: p6502::P6502 = p6502::P6502::with_variant(p6502::Variant::Cmos65C02);
+    other.load_state(&state).unwrap();
+    assert_eq!(machine(&other), machine(&cpu));
+
+    // The variant must match.
+    let mut nmos: p6502::P6502 = p6502::P6502::default();
+    assert_eq!(
+        state_error(nmos.load_state(&state)),
+        "it was saved from a Cmos65C02 CPU, not a Nmos6502 one"
+    );
+}
+
+/// A device which counts the cycles elapsed.
+#[derive(Default)]
+struct Timer {
+    count: u8,
+}
+
+impl Bus for Timer {
+    fn read(&mut self, _addr: u16) -> u8 {
+        self.count
+    }
+
+    fn write(&mut self, _addr: u16, data: u8) {
+        self.count = data;
+    }
+
+    fn peek(&self, _addr: u16) -> u8 {
+        self.count
+    }
+
+    fn tick(&mut self, cycles: usize) {
+        self.count = self.count.wrapping_add(cycles as u8);
+    }
+
+    fn save_state(&self, state: &mut Vec<u8>) {
+        state.push(self.count);
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<()> {
+        self.count = state[0];
+        Ok(())
+    }
+}
+
+/// Returns a map with RAM, a timer and the program in ROM.
+fn memory_map() -> MemoryMap {
+    let mut rom: Vec<u8> = vec![0; 0x1000];
+    rom[..PROGRAM.len()].copy_from_slice(&PROGRAM);
+    rom[0xffc] = 0x00;
+    rom[0xffd] = 0xf0;
+
+    MemoryMap::new()
+        .ram(0x0000..=0x07ff)
+        .mirror(0x0800..=0x1fff, 0x0000..=0x07ff)
+        .device(0xd000..=0xd000, Box::new(Timer::default()))
+        .rom(0xf000..=0xffff, rom)
+}
+
+#[test]
+fn memory_map_state() {
+    let mut cpu: p6502::P6502<MemoryMap> =
+        p6502::P6502::new(memory_map(), p6502::Variant::Nmos6502);
+    cpu.reset();
+    cpu.run_for_cycles(500).unwrap();
+    let state: SaveState = cpu.save_state().unwrap();
+    let timer: u8 = cpu.memory.peek(0xd000);
+    let ram: Vec<u8> = (0x0200..0x0300).map(|addr| cpu.memory.peek(addr)).collect();
+
+    // The RAM, the ROM and the timer only hold what the state says.
+    let mut other: p6502::P6502<MemoryMap> =
+        p6502::P6502::new(memory_map(), p6502::Variant::Nmos6502);
+    other.memory.poke(0xf000, 0xff);
+    other.load_state(&state).unwrap();
+    assert_eq!(other.memory.peek(0xd000), timer);
+    assert_eq!(other.memory.peek(0xf000), PROGRAM[0]);
+    assert_eq!(
+        (0x0200..0x0300)
+            .map(|addr| other.memory.peek(addr))
+            .collect::<Vec<u8>>(),
+        ram
+    );
+    assert_eq!((other.pc, other.cycles()), (cpu.pc, cpu.cycles()));
+
+    // Another map doesn't fit.
+    let mut smaller: p6502::P6502<MemoryMap> = p6502::P6502::new(
+        MemoryMap::new().ram(0x0000..=0x07ff),
+        p6502::Variant::Nmos6502,
+    );
+    assert!(smaller.load_state(&state).is_err());
+}
+
+#[test]
+fn invalid_save_states() {
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    let state: SaveState = cpu.save_state().unwrap();
+    let bytes: &[u8] = state.as_bytes();
+
+    assert_eq!(
+        state_error(SaveState::from_bytes(b"SNES\x01".to_vec())),
+        "not a kw6502 save state"
+    );
+    assert_eq!(
+        state_error(SaveState::from_bytes(b"KW65\x07".to_vec())),
+        "version 7 is not supported"
+    );
+    assert_eq!(
+        state_error(SaveState::from_bytes(b"KW6".to_vec())),
+        "it is truncated"
+    );
+
+    let truncated: SaveState = SaveState::from_bytes(bytes[..bytes.len() - 1].to_vec()).unwrap();
+    assert_eq!(state_error(cpu.load_state(&truncated)), "it is truncated");
+    let mut longer: Vec<u8> = bytes.to_vec();
+    longer.push(0);
+    let longer: SaveState = SaveState::from_bytes(longer).unwrap();
+    assert_eq!(
+        state_error(cpu.load_state(&longer)),
+        "it is longer than expected"
+    );
+
+    // Not in the middle of an instruction.
+    cpu.tick();
+    assert!(cpu.mid_instruction());
+    assert_eq!(
+        state_error(cpu.save_state()),
+        "the CPU is in the middle of an instruction"
+    );
+}
@@ -0,0 +1,97 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use kw6502::p6502::{self, Bus};
+use kw6502::tracer;
+
+/// Runs the program until it halts and returns its trace.
+fn trace(cpu: &mut p6502::P6502) -> Vec<String> {
+    let lines: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+    let sink: Rc<RefCell<Vec<String>>> = Rc::clone(&lines);
+    cpu.set_tracer(move |line| sink.borrow_mut().push(line.to_string()));
+    cpu.execute().unwrap();
+    cpu.clear_tracer();
+    println!("{}", lines.borrow().join("\n"));
+
+    let trace: Vec<String> = lines.borrow().clone();
+    trace
+}
+
+#[test]
+fn nestest_format() {
+    // The beginning of nestest: JMP $C5F5; ...; LDX #$00; STX $00
+    let mut memory: p6502::Memory = p6502::Memory::default();
+    for (offset, byte) in [0x4c, 0xf5, 0xc5].iter().enumerate() {
+        memory.write(0xc000 + offset as u16, *byte);
+    }
+    for (offset, byte) in [0xa2, 0x00, 0x86, 0x00].iter().enumerate() {
+        memory.write(0xc5f5 + offset as u16, *byte);
+    }
+    memory.write(0xfffd, 0xc0);
+
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+    cpu.set_memory(memory);
+    cpu.reset();
+    assert_eq!(
+        tracer::trace_line(&cpu),
+        "C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD CYC:7"
+    );
+
+    let lines: Vec<String> = trace(&mut cpu);
+    assert_eq!(
+        lines[..3].to_vec(),
+        vec![
+            "C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD CYC:7",
+            "C5F5  A2 00     LDX #$00                        A:00 X:00 Y:00 P:24 SP:FD CYC:10",
+            "C5F7  86 00     STX $00 = 00                    A:00 X:00 Y:00 P:26 SP:FD CYC:12",
+        ]
+    );
+    // The BRK which halts the CPU is traced as well.
+    assert_eq!(lines.len(), 4);
+}
+
+#[test]
+fn operand_details() {
+    let program: Vec<u8> = vec![
+        0xa2, 0x02, // LDX #$02
+        0xa0, 0x03, // LDY #$03
+        0xb5, 0x0e, // LDA $0E,X
+        0xa1, 0x0e, // LDA ($0E,X)
+        0xb1, 0x10, // LDA ($10),Y
+        0xa7, 0x10, // LAX $10 (X = 0)
+        0xff, 0x00, 0x02, // ISC $0200,X
+        0x8d, 0x00, 0x03, // STA $0300
+        0x6c, 0xff, 0x02, // JMP ($02FF)
+    ];
+    let mut memory: p6502::Memory = p6502::Memory::from_program_vec(program);
+    memory.write(0x0011, 0x02);
+    memory.write(0x0200, 0x06);
+    memory.write(0x02ff, 0x22);
+
+    let mut cpu: p6502::P6502 = p6502::P6502::default();
+    cpu.break_policy = p6502::BreakPolicy::Halt;
+    cpu.set_memory(memory);
+    cpu.reset();
+
+    let disassembly: Vec<String> = trace(&mut cpu)
+        .iter()
+        .map(|line| line[..48].trim_end().to_string())
+        .collect();
+    assert_eq!(
+        disassembly,
+        vec![
+            "0600  A2 02     LDX #$02",
+            "0602  A0 03     LDY #$03",
+            "0604  B5 0E     LDA $0E,X @ 10 = 00",
+            "0606  A1 0E     LDA ($0E,X) @ 10 = 0200 = 06",
+            "0608  B1 10     LDA ($10),Y = 0200 @ 0203 = 00",
+            "060A  A7 10    *LAX $10 = 00",
+            "060C  FF 00 02 *ISB $0200,X @ 0200 = 06",
+            "060F  8D 00 03  STA $0300 = 00",
+            // The NMOS indirect JMP takes the MSB from $0200, which ISC incremented.
+            "0612  6C FF 02  JMP ($02FF) = 0722",
+            "0722  00        BRK",
+        ]
+    );
+}